@@ -810,6 +810,9 @@ pub async fn list_profiles() -> impl IntoResponse {
         ("research", ToolProfile::Research),
         ("messaging", ToolProfile::Messaging),
         ("automation", ToolProfile::Automation),
+        ("browser", ToolProfile::Browser),
+        ("devops", ToolProfile::Devops),
+        ("writing", ToolProfile::Writing),
         ("full", ToolProfile::Full),
     ];
 
@@ -4616,6 +4619,7 @@ pub async fn run_migrate(Json(req): Json<MigrateRequest>) -> impl IntoResponse {
         source_dir: std::path::PathBuf::from(&req.source_dir),
         target_dir: std::path::PathBuf::from(&req.target_dir),
         dry_run: req.dry_run,
+        ..Default::default()
     };
 
     match openfang_migrate::run_migration(&options) {
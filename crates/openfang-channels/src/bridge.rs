@@ -401,8 +401,9 @@ async fn dispatch_message(
                         return;
                     }
                 }
-                GroupPolicy::MentionOnly => {
-                    // Pass through — adapters should only forward mentioned messages.
+                GroupPolicy::MentionOnly | GroupPolicy::ReplyOnly => {
+                    // Pass through — adapters should only forward mentioned messages
+                    // (or replies to the bot, for reply_only).
                     // This is a hint for adapters, not enforced here.
                 }
                 GroupPolicy::All => {}
@@ -3024,6 +3024,7 @@ fn cmd_migrate(args: MigrateArgs) {
         source_dir,
         target_dir,
         dry_run: args.dry_run,
+        ..Default::default()
     };
 
     match openfang_migrate::run_migration(&options) {
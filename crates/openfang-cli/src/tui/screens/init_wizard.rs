@@ -849,6 +849,7 @@ fn handle_migration_key(
                             source_dir,
                             target_dir,
                             dry_run: false,
+                            ..Default::default()
                         };
                         let result =
                             openfang_migrate::run_migration(&options).map_err(|e| format!("{e}"));
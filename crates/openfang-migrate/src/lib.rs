@@ -6,12 +6,14 @@
 pub mod openclaw;
 pub mod report;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Source framework to migrate from.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum MigrateSource {
     /// OpenClaw agent framework.
+    #[default]
     OpenClaw,
     /// LangChain (future).
     LangChain,
@@ -29,6 +31,40 @@ impl std::fmt::Display for MigrateSource {
     }
 }
 
+/// How to handle a source agent whose id maps to an agent directory that is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateAgentPolicy {
+    /// Migrate the later agent into a numbered directory (`<id>-2`, `<id>-3`, ...).
+    #[default]
+    Rename,
+    /// Keep the first agent and report the later ones as skipped.
+    Skip,
+}
+
+/// Names of the files a migration writes into the target directory.
+///
+/// Relative paths are resolved against `MigrateOptions::target_dir`; absolute paths
+/// are used as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputPaths {
+    /// Main OpenFang config (default `config.toml`).
+    pub config: PathBuf,
+    /// Env file that extracted credentials are written to (default `secrets.env`).
+    pub secrets: PathBuf,
+    /// Markdown migration report (default `migration_report.md`).
+    pub report: PathBuf,
+}
+
+impl Default for OutputPaths {
+    fn default() -> Self {
+        Self {
+            config: PathBuf::from("config.toml"),
+            secrets: PathBuf::from("secrets.env"),
+            report: PathBuf::from("migration_report.md"),
+        }
+    }
+}
+
 /// Options for running a migration.
 #[derive(Debug, Clone)]
 pub struct MigrateOptions {
@@ -40,6 +76,151 @@ pub struct MigrateOptions {
     pub target_dir: PathBuf,
     /// If true, only report what would be done without making changes.
     pub dry_run: bool,
+    /// If true, keep retired model IDs verbatim instead of mapping them to their successors.
+    pub preserve_model_ids: bool,
+    /// Optional TOML/JSON file of `openclaw_name = "openfang_name"` tool mappings,
+    /// consulted before the builtin mapping table.
+    pub tool_map: Option<PathBuf>,
+    /// `openclaw_name -> openfang_name` tool mappings given directly, validated like
+    /// `tool_map` entries and taking precedence over them.
+    pub tool_aliases: HashMap<String, String>,
+    /// If true, accept `tool_map` and `tool_aliases` targets that are not known OpenFang built-in tools.
+    pub trust_unknown_targets: bool,
+    /// Provider to use when the source configures no model (defaults to `anthropic`).
+    pub default_provider: Option<String>,
+    /// Model to use when the source configures no model (defaults to `claude-sonnet-4-20250514`).
+    pub default_model: Option<String>,
+    /// What to do when two agents share an id, or collide once sanitized into directory names.
+    pub duplicate_agents: DuplicateAgentPolicy,
+    /// If true, suppress the migration's own `tracing` output; the report is unaffected.
+    pub quiet: bool,
+    /// Rename agents on the way in (`OpenClaw id -> OpenFang name`), applied to the
+    /// manifest name and the agent, memory and workspace directories.
+    pub agent_renames: HashMap<String, String>,
+    /// If true, an agent's own identity replaces `agents.defaults.identity` instead of
+    /// being appended to it.
+    pub replace_default_identity: bool,
+    /// If true, checksum every copied workspace file against its source and re-copy
+    /// once on mismatch.
+    pub verify_copies: bool,
+    /// If true, leave workspace files whose copy already matches the source (same size
+    /// and mtime, or same checksum with `verify_copies`) instead of copying them again.
+    pub incremental_copies: bool,
+    /// Where the config, secrets and report files are written.
+    pub output_paths: OutputPaths,
+    /// If set, only agents whose id matches one of these patterns (`*`/`?` globs) are
+    /// migrated, along with their memory and workspaces.
+    pub include_agents: Option<Vec<String>>,
+    /// Agents whose id matches one of these patterns are left out, even if included.
+    pub exclude_agents: Vec<String>,
+    /// If set, only these exact agent ids are migrated, along with their memory and
+    /// workspaces; the rest are reported as skipped "not in selection".
+    pub agent_ids: Option<Vec<String>>,
+    /// If true (the default), write a `MIGRATION_NOTES.md` next to each migrated
+    /// `agent.toml` explaining how it was converted.
+    pub write_agent_notes: bool,
+    /// If true, unmapped tools, unknown or unsupported channels and providers
+    /// OpenFang cannot load abort the migration with [`MigrateError::Strict`]
+    /// before anything is written, instead of becoming warnings or skipped items.
+    pub strict: bool,
+    /// Abort with [`MigrateError::TooManyAgents`] when the source defines more agents
+    /// than this (after filters), to catch corrupted or generated configs.
+    pub max_agents: usize,
+    /// If set, session logs whose newest message (or, without timestamps, last
+    /// modification) predates this are not migrated.
+    pub sessions_since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Like `sessions_since`, counted back this many days from now. The later of
+    /// the two cutoffs applies when both are set.
+    pub sessions_max_age_days: Option<u32>,
+    /// Agent id that receives the main session's memory (`memory/default/` or
+    /// `memory/main/`) when no agent has that id. Defaults to the first agent, which
+    /// is also used (with a warning) when this names no migrated agent.
+    pub default_agent_target: Option<String>,
+    /// MEMORY.md files larger than this many bytes are cut into
+    /// `imported_memory_NNN.md` chunks and only the most recent ones that fit are
+    /// kept. `None` imports memory of any size in full.
+    pub memory_size_limit: Option<u64>,
+    /// If true, read the text chunks out of OpenClaw's `memory-search/index.db` into
+    /// `imported_memory_index.jsonl` per agent. Embeddings are always rebuilt.
+    pub import_memory_index: bool,
+    /// If true, copy `memory-search/index.db` as-is into the target's `memory/`
+    /// directory. Only useful when OpenFang's embedding model matches OpenClaw's.
+    pub copy_vector_index: bool,
+    /// If true, re-read the written `config.toml` and every `agents/*/agent.toml`
+    /// after migrating and report any that OpenFang could not load as warnings.
+    pub validate_after: bool,
+    /// Append the most recent this-many dated journal files (`memory/<agent>/YYYY-MM-DD.md`)
+    /// to the agent's imported memory. All journal files are copied to
+    /// `imported_journal/` either way.
+    pub recent_journals_in_memory: usize,
+    /// If true, convert each session log into the JSONL layout of the kernel's
+    /// session mirror (`agents/<agent>/imported_sessions/<uuid>.jsonl`) so it reads
+    /// like OpenFang history. This is an archive: live sessions are kept in SQLite
+    /// and these files are never loaded. Otherwise (or when a log has nothing
+    /// convertible) logs are copied as-is into `imported_sessions/`. Tool calls are
+    /// renamed to their OpenFang tools; calls to tools OpenFang lacks become system notes.
+    pub convert_sessions: bool,
+    /// If true, `.jsonl.gz` session archives are decompressed and migrated like
+    /// plain `.jsonl` logs instead of being skipped.
+    pub decompress_sessions: bool,
+    /// When converting sessions, a log is skipped entirely if more than this fraction
+    /// of its lines cannot be parsed; below that, bad lines are dropped and counted.
+    pub session_bad_line_limit: f64,
+    /// If set, session logs are migrated newest first until this many bytes
+    /// (decompressed, before conversion) have been taken; older logs are reported
+    /// as skipped. See [`report::parse_size`] for reading it from text like `500MB`.
+    pub sessions_size_limit: Option<u64>,
+}
+
+/// Default for [`MigrateOptions::memory_size_limit`] (1 MB).
+pub const DEFAULT_MEMORY_SIZE_LIMIT: u64 = 1024 * 1024;
+
+/// Default for [`MigrateOptions::session_bad_line_limit`] (half the lines).
+pub const DEFAULT_SESSION_BAD_LINE_LIMIT: f64 = 0.5;
+
+/// Default for [`MigrateOptions::max_agents`].
+pub const DEFAULT_MAX_AGENTS: usize = 200;
+
+impl Default for MigrateOptions {
+    fn default() -> Self {
+        Self {
+            source: MigrateSource::default(),
+            source_dir: PathBuf::new(),
+            target_dir: PathBuf::new(),
+            dry_run: false,
+            preserve_model_ids: false,
+            tool_map: None,
+            tool_aliases: HashMap::new(),
+            trust_unknown_targets: false,
+            default_provider: None,
+            default_model: None,
+            duplicate_agents: DuplicateAgentPolicy::default(),
+            quiet: false,
+            agent_renames: HashMap::new(),
+            replace_default_identity: false,
+            verify_copies: false,
+            incremental_copies: false,
+            output_paths: OutputPaths::default(),
+            include_agents: None,
+            exclude_agents: Vec::new(),
+            agent_ids: None,
+            write_agent_notes: true,
+            strict: false,
+            max_agents: DEFAULT_MAX_AGENTS,
+            sessions_since: None,
+            sessions_max_age_days: None,
+            default_agent_target: None,
+            memory_size_limit: Some(DEFAULT_MEMORY_SIZE_LIMIT),
+            import_memory_index: false,
+            copy_vector_index: false,
+            validate_after: false,
+            recent_journals_in_memory: 0,
+            convert_sessions: false,
+            decompress_sessions: false,
+            session_bad_line_limit: DEFAULT_SESSION_BAD_LINE_LIMIT,
+            sessions_size_limit: None,
+        }
+    }
 }
 
 /// Run a migration with the given options.
@@ -56,7 +237,12 @@ pub fn run_migration(options: &MigrateOptions) -> Result<report::MigrationReport
 }
 
 /// Errors that can occur during migration.
+///
+/// Wrapped IO, YAML and TOML errors are exposed through
+/// [`std::error::Error::source`]. New variants may be added, so matches need a
+/// wildcard arm.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum MigrateError {
     #[error("Source directory not found: {0}")]
     SourceNotFound(PathBuf),
@@ -72,6 +258,50 @@ pub enum MigrateError {
     Json5Parse(String),
     #[error("TOML serialization error: {0}")]
     TomlSerialize(#[from] toml::ser::Error),
+    #[error("Invalid agent rename: {0}")]
+    AgentRename(String),
     #[error("Unsupported source: {0}")]
     UnsupportedSource(String),
+    #[error("Strict mode: {0}")]
+    Strict(String),
+    #[error(
+        "Source defines {found} agents, more than the limit of {limit} (largest: {largest}); \
+         raise max_agents if this is intended"
+    )]
+    TooManyAgents {
+        found: usize,
+        limit: usize,
+        largest: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_error_display_and_source() {
+        let missing = PathBuf::from("/nonexistent/openclaw-home");
+        let err = run_migration(&MigrateOptions {
+            source_dir: missing.clone(),
+            target_dir: std::env::temp_dir(),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(err, MigrateError::SourceNotFound(_)));
+        assert!(err.to_string().contains(&missing.display().to_string()));
+        assert!(err.source().is_none());
+
+        let err = MigrateError::from(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "config.toml is read-only",
+        ));
+        let io = err
+            .source()
+            .and_then(|e| e.downcast_ref::<std::io::Error>())
+            .unwrap();
+        assert_eq!(io.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(err.to_string().contains("config.toml is read-only"));
+    }
 }
@@ -21,8 +21,10 @@
 //! └── workspaces/                       # Per-agent working directories
 //! ```
 
-use crate::report::{ItemKind, MigrateItem, MigrationReport, SkippedItem};
-use crate::{MigrateError, MigrateOptions};
+use crate::report::{
+    format_bytes, ItemDetails, ItemKind, MigrateItem, MigrationReport, SkippedItem,
+};
+use crate::{DuplicateAgentPolicy, MigrateError, MigrateOptions};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
@@ -35,6 +37,8 @@ use tracing::{info, warn};
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawRoot {
+    version: Option<serde_json::Value>,
+    schema_version: Option<serde_json::Value>,
     auth: Option<OpenClawAuth>,
     models: Option<OpenClawModels>,
     agents: Option<OpenClawAgents>,
@@ -47,6 +51,39 @@ struct OpenClawRoot {
     session: Option<serde_json::Value>,
 }
 
+/// Newest `openclaw.json` `version`/`schemaVersion` this migrator knows the layout of.
+const NEWEST_KNOWN_CONFIG_VERSION: &str = "1";
+
+impl OpenClawRoot {
+    /// The declared config version, `version` first, as text.
+    fn source_version(&self) -> Option<String> {
+        match self.version.as_ref().or(self.schema_version.as_ref())? {
+            serde_json::Value::String(s) => Some(s.trim().to_string()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+        .filter(|v| !v.is_empty())
+    }
+}
+
+/// Whether dotted version `version` is newer than `known` (`"2.0"` > `"1"`).
+/// Versions with non-numeric parts are never considered newer.
+fn version_is_newer(version: &str, known: &str) -> bool {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| p.parse().ok())
+            .collect()
+    };
+    let (Some(mut version), Some(mut known)) = (parse(version), parse(known)) else {
+        return false;
+    };
+    let len = version.len().max(known.len());
+    version.resize(len, 0);
+    known.resize(len, 0);
+    version > known
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawAuth {
@@ -63,21 +100,87 @@ struct OpenClawModels {
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawRootTools {
-    #[allow(dead_code)]
     profile: Option<String>,
-    #[allow(dead_code)]
     allow: Option<Vec<String>>,
-    #[allow(dead_code)]
     deny: Option<Vec<String>>,
+    mcp: Option<std::collections::BTreeMap<String, OpenClawMcpServer>>,
 }
 
+/// An MCP server declared under `tools.mcp`.
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
+struct OpenClawMcpServer {
+    #[serde(alias = "type")]
+    transport: Option<String>,
+    command: Option<String>,
+    args: Vec<String>,
+    env: std::collections::BTreeMap<String, String>,
+    url: Option<String>,
+    /// Tools this server exposes to agents.
+    allow: Vec<String>,
+}
+
+/// The `agents` block: `{ defaults, list: [...] }`, or the older map form
+/// `{ defaults, coder: {...}, researcher: {...} }` where each key is the agent id.
+#[derive(Debug, Default)]
 struct OpenClawAgents {
     defaults: Option<OpenClawAgentDefaults>,
     list: Vec<OpenClawAgentEntry>,
 }
 
+impl<'de> Deserialize<'de> for OpenClawAgents {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AgentsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for AgentsVisitor {
+            type Value = OpenClawAgents;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an agents object with a `list`, or a map of agent id to agent")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut agents = OpenClawAgents::default();
+                let mut list = None;
+                // Object-valued keys, in source order; agents only when there's no `list`
+                let mut keyed = Vec::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "defaults" => agents.defaults = map.next_value()?,
+                        "list" => list = Some(map.next_value()?),
+                        _ => {
+                            let value: serde_json::Value = map.next_value()?;
+                            if value.is_object() {
+                                keyed.push((key, value));
+                            }
+                        }
+                    }
+                }
+                agents.list = match list {
+                    Some(list) => list,
+                    None => keyed
+                        .into_iter()
+                        .map(|(id, value)| {
+                            let mut entry: OpenClawAgentEntry = serde_json::from_value(value)
+                                .map_err(|e| {
+                                    serde::de::Error::custom(format!("agent '{id}': {e}"))
+                                })?;
+                            entry.id = id;
+                            Ok(entry)
+                        })
+                        .collect::<Result<_, A::Error>>()?,
+                };
+                Ok(agents)
+            }
+        }
+
+        deserializer.deserialize_map(AgentsVisitor)
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawAgentDefaults {
@@ -85,6 +188,59 @@ struct OpenClawAgentDefaults {
     workspace: Option<String>,
     tools: Option<OpenClawAgentTools>,
     identity: Option<String>,
+    #[serde(alias = "api_key_env")]
+    api_key_env: Option<String>,
+    /// Literal API key; moved into secrets.env on migration.
+    #[serde(alias = "api_key")]
+    api_key: Option<String>,
+    #[serde(flatten)]
+    limits: OpenClawLimits,
+}
+
+/// Cost controls set on an agent or on `agents.defaults`.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct OpenClawLimits {
+    max_context_tokens: Option<u64>,
+    max_output_tokens: Option<u64>,
+    history_limit: Option<u64>,
+}
+
+/// Memory scoping read out of a root-level or per-agent `memory` block.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct OpenClawMemoryScope {
+    /// Whether the agent can see memories beyond its own.
+    shared: Option<bool>,
+    /// Explicit memory namespaces the agent works in.
+    namespaces: Option<Vec<String>>,
+}
+
+impl OpenClawMemoryScope {
+    /// Scope from a `memory` block; blocks that aren't objects express nothing.
+    fn from_value(value: Option<&serde_json::Value>) -> Self {
+        value
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Where MEMORY.md files live, read out of a root-level or per-agent `memory` block.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OpenClawMemoryLocation {
+    /// Root holding `<agent>/MEMORY.md` (root block only).
+    dir: Option<String>,
+    /// An agent's own MEMORY.md, or a directory containing one (agent block only).
+    path: Option<String>,
+}
+
+impl OpenClawMemoryLocation {
+    fn from_value(value: Option<&serde_json::Value>) -> Self {
+        value
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
 }
 
 /// Agent model reference — either `"provider/model"` or `{ primary, fallbacks }`.
@@ -100,6 +256,8 @@ enum OpenClawAgentModel {
 struct OpenClawAgentModelDetailed {
     primary: Option<String>,
     fallbacks: Vec<String>,
+    /// Sampling/reasoning parameters (`temperature`, `maxOutputTokens`, ...).
+    params: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize)]
@@ -107,11 +265,41 @@ struct OpenClawAgentModelDetailed {
 struct OpenClawAgentEntry {
     id: String,
     name: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
     model: Option<OpenClawAgentModel>,
     tools: Option<OpenClawAgentTools>,
     workspace: Option<String>,
     skills: Option<Vec<String>>,
     identity: Option<String>,
+    /// Env var holding this agent's API key, overriding the provider default.
+    #[serde(alias = "api_key_env")]
+    api_key_env: Option<String>,
+    /// Literal API key; moved into secrets.env on migration.
+    #[serde(alias = "api_key")]
+    api_key: Option<String>,
+    /// Per-agent memory settings; see [`OpenClawMemoryScope`].
+    memory: Option<serde_json::Value>,
+    /// Child agents the parent spawns on demand.
+    subagents: Vec<OpenClawAgentEntry>,
+    /// Periodic wake-up, e.g. `{ every: "30m", prompt: "check the queue" }`.
+    heartbeat: Option<OpenClawSchedule>,
+    /// Same shape as `heartbeat`; used when no heartbeat is set.
+    schedule: Option<OpenClawSchedule>,
+    #[serde(flatten)]
+    limits: OpenClawLimits,
+}
+
+/// An agent `heartbeat` / `schedule` block.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+struct OpenClawSchedule {
+    /// Interval such as `"30m"`, `"1h30m"` or `"daily"`.
+    every: Option<String>,
+    /// Cron expression; handled by the cron migration, not here.
+    cron: Option<String>,
+    prompt: Option<String>,
+    enabled: Option<bool>,
 }
 
 #[derive(Debug, Default, Clone, Deserialize)]
@@ -121,6 +309,37 @@ struct OpenClawAgentTools {
     allow: Option<Vec<String>>,
     deny: Option<Vec<String>>,
     also_allow: Option<Vec<String>>,
+    /// Per-tool option objects, e.g. `webFetch: { allowDomains: [...] }`.
+    #[serde(flatten)]
+    options: std::collections::BTreeMap<String, serde_json::Value>,
+}
+
+impl OpenClawAgentTools {
+    /// The option object configured for an OpenFang tool, matched by OpenClaw key.
+    fn options_for(&self, of_tool: &str) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.options.iter().find_map(|(key, value)| {
+            let resolved = match key.as_str() {
+                "browser" => Some("browser_navigate".to_string()),
+                _ => resolve_tool_name(key, &ToolMap::default()).map(|(name, _)| name),
+            };
+            if resolved.as_deref() == Some(of_tool) {
+                value.as_object()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// A string-list option (e.g. `allowDomains`) for an OpenFang tool.
+    fn option_list(&self, of_tool: &str, keys: &[&str]) -> Option<Vec<String>> {
+        let opts = self.options_for(of_tool)?;
+        let list = keys.iter().find_map(|k| opts.get(*k))?.as_array()?;
+        Some(
+            list.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+        )
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -145,11 +364,40 @@ struct OpenClawChannels {
     other: serde_json::Map<String, serde_json::Value>,
 }
 
+impl OpenClawChannels {
+    /// Names of the typed channels present in the config, in declaration order.
+    fn configured(&self) -> Vec<&'static str> {
+        [
+            ("telegram", self.telegram.is_some()),
+            ("discord", self.discord.is_some()),
+            ("slack", self.slack.is_some()),
+            ("whatsapp", self.whatsapp.is_some()),
+            ("signal", self.signal.is_some()),
+            ("matrix", self.matrix.is_some()),
+            ("google_chat", self.google_chat.is_some()),
+            ("teams", self.teams.is_some()),
+            ("irc", self.irc.is_some()),
+            ("mattermost", self.mattermost.is_some()),
+            ("feishu", self.feishu.is_some()),
+            ("imessage", self.imessage.is_some()),
+            ("bluebubbles", self.bluebubbles.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| present.then_some(name))
+        .collect()
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawTelegramConfig {
     bot_token: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
+    /// Group chat IDs the bot may answer in; unrestricted when unset.
+    #[serde(alias = "allowedChats", alias = "allow_chats", alias = "allowed_chats")]
+    allow_chats: Option<Vec<String>>,
     group_policy: Option<String>,
     dm_policy: Option<String>,
     enabled: Option<bool>,
@@ -163,6 +411,8 @@ struct OpenClawDiscordConfig {
     dm_policy: Option<String>,
     group_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -174,6 +424,8 @@ struct OpenClawSlackConfig {
     dm_policy: Option<String>,
     group_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -181,8 +433,12 @@ struct OpenClawSlackConfig {
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawWhatsAppConfig {
     auth_dir: Option<String>,
+    #[serde(alias = "account")]
+    phone_number: Option<String>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     group_policy: Option<String>,
     enabled: Option<bool>,
 }
@@ -193,9 +449,12 @@ struct OpenClawSignalConfig {
     http_url: Option<String>,
     http_host: Option<String>,
     http_port: Option<u16>,
+    #[serde(alias = "phoneNumber")]
     account: Option<String>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -205,12 +464,42 @@ struct OpenClawMatrixConfig {
     homeserver: Option<String>,
     user_id: Option<String>,
     access_token: Option<String>,
-    rooms: Option<Vec<String>>,
+    rooms: Option<Vec<OpenClawMatrixRoom>>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
+/// Matrix room entry — either a plain room ID or `{ id, dmPolicy, groupPolicy }`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum OpenClawMatrixRoom {
+    Id(String),
+    Detailed(OpenClawMatrixRoomDetailed),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct OpenClawMatrixRoomDetailed {
+    id: String,
+    dm_policy: Option<String>,
+    group_policy: Option<String>,
+    allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
+}
+
+impl OpenClawMatrixRoom {
+    fn id(&self) -> &str {
+        match self {
+            Self::Id(id) => id,
+            Self::Detailed(d) => &d.id,
+        }
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default, rename_all = "camelCase")]
 struct OpenClawGoogleChatConfig {
@@ -229,6 +518,8 @@ struct OpenClawTeamsConfig {
     tenant_id: Option<String>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -240,9 +531,16 @@ struct OpenClawIrcConfig {
     tls: Option<bool>,
     nick: Option<String>,
     password: Option<String>,
+    /// Authenticate with SASL (`true`) or NickServ (`false`).
+    sasl: Option<bool>,
+    /// Explicit auth mechanism (`sasl` / `nickserv`); wins over `sasl`.
+    #[serde(alias = "auth_mode")]
+    auth_mode: Option<String>,
     channels: Option<Vec<String>>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -253,6 +551,8 @@ struct OpenClawMattermostConfig {
     base_url: Option<String>,
     dm_policy: Option<String>,
     allow_from: Option<Vec<String>>,
+    #[serde(alias = "denyFrom")]
+    block_from: Option<Vec<String>>,
     enabled: Option<bool>,
 }
 
@@ -298,11 +598,11 @@ struct OpenClawSkills {
 // ---------------------------------------------------------------------------
 
 /// OpenClaw's legacy config.yaml structure.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 struct LegacyYamlConfig {
-    provider: String,
-    model: String,
+    provider: Option<String>,
+    model: Option<String>,
     api_key_env: Option<String>,
     base_url: Option<String>,
     #[allow(dead_code)]
@@ -312,20 +612,6 @@ struct LegacyYamlConfig {
     memory: Option<LegacyYamlMemoryConfig>,
 }
 
-impl Default for LegacyYamlConfig {
-    fn default() -> Self {
-        Self {
-            provider: "anthropic".to_string(),
-            model: "claude-sonnet-4-20250514".to_string(),
-            api_key_env: None,
-            base_url: None,
-            temperature: None,
-            max_tokens: None,
-            memory: None,
-        }
-    }
-}
-
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 struct LegacyYamlMemoryConfig {
@@ -382,6 +668,8 @@ struct LegacyYamlChannelConfig {
     verify_token_env: Option<String>,
     #[allow(dead_code)]
     webhook_port: Option<u16>,
+    #[serde(alias = "account")]
+    phone_number: Option<String>,
     allowed_users: Vec<String>,
     default_agent: Option<String>,
 }
@@ -398,6 +686,23 @@ struct OpenFangConfig {
     network: OpenFangNetworkSection,
     #[serde(skip_serializing_if = "Option::is_none")]
     channels: Option<toml::Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    mcp_servers: Vec<OpenFangMcpServer>,
+}
+
+#[derive(Serialize)]
+struct OpenFangMcpServer {
+    name: String,
+    transport: OpenFangMcpTransport,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    env: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenFangMcpTransport {
+    Stdio { command: String, args: Vec<String> },
+    Sse { url: String },
 }
 
 #[derive(Serialize)]
@@ -412,6 +717,69 @@ struct OpenFangModelConfig {
 #[derive(Serialize)]
 struct OpenFangMemorySection {
     decay_rate: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consolidation_threshold: Option<u64>,
+}
+
+/// Decay rate written when the source configures none.
+const DEFAULT_DECAY_RATE: f32 = 0.05;
+
+/// Keys of the JSON5 `memory` block that are migrated (location, scope and tunables).
+const MIGRATED_MEMORY_KEYS: &[&str] = &[
+    "dir",
+    "shared",
+    "namespaces",
+    "decayRate",
+    "decay_rate",
+    "maxEntries",
+    "max_entries",
+];
+
+impl OpenFangMemorySection {
+    /// Tunables from the JSON5 `memory` block: `decayRate` becomes `decay_rate` and
+    /// `maxEntries` the consolidation threshold. `ttlDays` has no OpenFang
+    /// equivalent and is reported as skipped.
+    fn from_json(memory: Option<&serde_json::Value>, report: &mut MigrationReport) -> Self {
+        let get = |camel: &str, snake: &str| {
+            memory
+                .and_then(|m| m.get(camel).or_else(|| m.get(snake)))
+                .filter(|v| !v.is_null())
+        };
+        let decay_rate = match get("decayRate", "decay_rate") {
+            None => DEFAULT_DECAY_RATE,
+            Some(v) => match v.as_f64().filter(|r| (0.0..=1.0).contains(r)) {
+                Some(rate) => rate as f32,
+                None => {
+                    report.warnings.push(format!(
+                        "memory.decayRate {v} is not between 0 and 1; using {DEFAULT_DECAY_RATE}"
+                    ));
+                    DEFAULT_DECAY_RATE
+                }
+            },
+        };
+        let consolidation_threshold = get("maxEntries", "max_entries").and_then(|v| {
+            let max = v.as_u64().filter(|n| *n > 0);
+            if max.is_none() {
+                report.warnings.push(format!(
+                    "memory.maxEntries {v} is not a positive integer; not migrated"
+                ));
+            }
+            max
+        });
+        if let Some(ttl) = get("ttlDays", "ttl_days") {
+            report.skipped.push(SkippedItem {
+                kind: ItemKind::Config,
+                name: "memory.ttlDays".to_string(),
+                reason: format!(
+                    "OpenFang has no memory TTL ({ttl} days); tune memory.decay_rate instead"
+                ),
+            });
+        }
+        Self {
+            decay_rate,
+            consolidation_threshold,
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -439,12 +807,24 @@ fn write_secret_env(path: &Path, key: &str, value: &str) -> Result<(), std::io::
         Vec::new()
     };
 
-    // Upsert
-    let prefix = format!("{key}=");
-    if let Some(pos) = lines.iter().position(|l| l.starts_with(&prefix)) {
-        lines[pos] = format!("{key}={value}");
-    } else {
-        lines.push(format!("{key}={value}"));
+    // Upsert only the matching assignment; comments, blank lines and the
+    // order of other keys are left untouched
+    let existing = lines.iter().position(|l| {
+        let l = l.trim_start();
+        let l = l.strip_prefix("export ").unwrap_or(l).trim_start();
+        l.strip_prefix(key)
+            .is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(pos) => {
+            let export = if lines[pos].trim_start().starts_with("export ") {
+                "export "
+            } else {
+                ""
+            };
+            lines[pos] = format!("{export}{key}={value}");
+        }
+        None => lines.push(format!("{key}={value}")),
     }
 
     if let Some(parent) = path.parent() {
@@ -463,6 +843,42 @@ fn write_secret_env(path: &Path, key: &str, value: &str) -> Result<(), std::io::
     Ok(())
 }
 
+/// In dry-run mode, record a planned output that already exists in the target.
+fn note_conflict(dry_run: bool, path: &Path, report: &mut MigrationReport) {
+    if dry_run && path.exists() {
+        let p = path.display().to_string();
+        if !report.conflicts.contains(&p) {
+            report.conflicts.push(p);
+        }
+    }
+}
+
+/// Write a secret to the secrets env file and report it.
+fn emit_secret(path: &Path, dry_run: bool, key: &str, value: &str, report: &mut MigrationReport) {
+    if value.is_empty() {
+        return;
+    }
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string());
+    note_conflict(dry_run, path, report);
+    if !dry_run {
+        if let Err(e) = write_secret_env(path, key, value) {
+            report
+                .warnings
+                .push(format!("Failed to write {key} to {file_name}: {e}"));
+            return;
+        }
+    }
+    report.imported.push(MigrateItem {
+        kind: ItemKind::Secret,
+        name: key.to_string(),
+        destination: file_name,
+        details: None,
+    });
+}
+
 /// Map OpenClaw DM policy to OpenFang DM policy string.
 fn map_dm_policy(oc: &str) -> &'static str {
     match oc.to_lowercase().as_str() {
@@ -473,14 +889,156 @@ fn map_dm_policy(oc: &str) -> &'static str {
     }
 }
 
-/// Map OpenClaw group policy to OpenFang group policy string.
+/// OpenFang group policy string for a recognized OpenClaw group policy.
+fn parse_group_policy(oc: &str) -> Option<&'static str> {
+    match oc.to_lowercase().replace(['_', '-'], "").as_str() {
+        "open" => Some("all"),
+        "mention" | "mentiononly" | "mentioned" | "mentions" | "tag" | "tagged" => {
+            Some("mention_only")
+        }
+        "replyonly" | "respondonreply" | "reply" | "replies" => Some("reply_only"),
+        "disabled" => Some("ignore"),
+        _ => None,
+    }
+}
+
+/// Map OpenClaw group policy to OpenFang group policy string. Unrecognized values
+/// get the kernel's default, `mention_only`; see [`unknown_group_policy_warnings`].
 fn map_group_policy(oc: &str) -> &'static str {
-    match oc.to_lowercase().as_str() {
-        "open" => "respond",
-        "mention" | "mention_only" => "mention_only",
-        "disabled" => "ignore",
-        _ => "respond",
+    parse_group_policy(oc).unwrap_or("mention_only")
+}
+
+/// Warnings for group policies [`map_group_policy`] did not recognize.
+fn unknown_group_policy_warnings(channels: &OpenClawChannels) -> Vec<String> {
+    let matrix_rooms = channels
+        .matrix
+        .iter()
+        .flat_map(|m| m.rooms.iter().flatten())
+        .filter_map(|room| match room {
+            OpenClawMatrixRoom::Detailed(d) => Some(("matrix", &d.group_policy)),
+            _ => None,
+        });
+    [
+        (
+            "telegram",
+            channels.telegram.as_ref().map(|c| &c.group_policy),
+        ),
+        (
+            "discord",
+            channels.discord.as_ref().map(|c| &c.group_policy),
+        ),
+        ("slack", channels.slack.as_ref().map(|c| &c.group_policy)),
+        (
+            "whatsapp",
+            channels.whatsapp.as_ref().map(|c| &c.group_policy),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(name, policy)| Some((name, policy?)))
+    .chain(matrix_rooms)
+    .filter_map(|(name, policy)| {
+        let policy = policy.as_deref()?;
+        parse_group_policy(policy).is_none().then(|| {
+            format!(
+                "{name} group policy '{policy}' is not recognized; using mention_only \
+                 (respond only when mentioned)"
+            )
+        })
+    })
+    .collect()
+}
+
+/// IRC auth mechanism from `authMode`, or from `sasl` (`false` with a password means
+/// NickServ). `Err` carries an unrecognized `authMode` value.
+fn irc_auth_mode(irc: &OpenClawIrcConfig) -> Result<Option<&'static str>, String> {
+    if let Some(ref mode) = irc.auth_mode {
+        return match mode.to_lowercase().replace(['_', '-'], "").as_str() {
+            "sasl" | "saslplain" => Ok(Some("sasl")),
+            "nickserv" | "ns" => Ok(Some("nickserv")),
+            _ => Err(mode.clone()),
+        };
+    }
+    Ok(match irc.sasl {
+        Some(true) => Some("sasl"),
+        Some(false) if irc.password.is_some() => Some("nickserv"),
+        _ => None,
+    })
+}
+
+/// Whether an optional config string is present and non-blank.
+fn filled(value: &Option<String>) -> bool {
+    value.as_deref().is_some_and(|v| !v.trim().is_empty())
+}
+
+/// Skip a channel whose config lacks structural fields its OpenFang adapter needs
+/// (hosts, IDs), given as `(OpenClaw field name, present)`. Credentials are not
+/// checked: they are read from env vars, which can be set after migrating.
+/// Returns true if the channel was skipped.
+fn skip_incomplete_channel(
+    name: &str,
+    required: &[(&str, bool)],
+    report: &mut MigrationReport,
+) -> bool {
+    let missing: Vec<String> = required
+        .iter()
+        .filter(|(_, present)| !present)
+        .map(|(field, _)| format!("`{field}`"))
+        .collect();
+    if missing.is_empty() {
+        return false;
+    }
+    report.skipped.push(SkippedItem {
+        kind: ItemKind::Channel,
+        name: name.to_string(),
+        reason: format!(
+            "Incomplete config: missing required {} {}",
+            if missing.len() == 1 {
+                "field"
+            } else {
+                "fields"
+            },
+            missing.join(", ")
+        ),
+    });
+    true
+}
+
+/// Numeric user IDs from a Telegram `allowFrom`, the only form OpenFang's allowlist
+/// takes; other entries are dropped with a warning. Returns `None`, after reporting
+/// the channel as skipped, when entries remain but none is an ID: an empty
+/// `allowed_users` would let everyone talk to the bot.
+fn telegram_allowed_ids(
+    allow_from: Option<&[String]>,
+    report: &mut MigrationReport,
+) -> Option<Vec<i64>> {
+    let users = allow_from.unwrap_or_default();
+    let (ids, names): (Vec<&String>, Vec<&String>) =
+        users.iter().partition(|u| u.trim().parse::<i64>().is_ok());
+    let ids: Vec<i64> = ids.iter().filter_map(|u| u.trim().parse().ok()).collect();
+    if names.is_empty() {
+        return Some(ids);
+    }
+    let names = names
+        .iter()
+        .map(|n| format!("'{n}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if ids.is_empty() {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Channel,
+            name: "telegram".to_string(),
+            reason: format!(
+                "allowFrom has no numeric user IDs ({names}); migrating it would open the bot \
+                 to everyone, so add the users' IDs and re-run"
+            ),
+        });
+        return None;
     }
+    report.warnings.push(format!(
+        "Telegram allowFrom entries {names} are not numeric user IDs and were dropped \
+         from allowed_users; add their IDs to let them in"
+    ));
+    Some(ids)
 }
 
 /// Build a TOML table for a channel with the given fields and optional overrides.
@@ -530,18 +1088,213 @@ fn build_channel_table(
     toml::Value::Table(table)
 }
 
+/// Warning for a WhatsApp phone number, which has no config field: the Cloud API
+/// adapter takes the number's `phone_number_id`, a different value.
+fn whatsapp_phone_warning(phone: &str) -> String {
+    format!(
+        "WhatsApp phone number {phone} was not migrated: OpenFang needs its Cloud API \
+         phone_number_id instead; look it up in WhatsApp Manager and set it under \
+         [channels.whatsapp]"
+    )
+}
+
+/// Report a channel's `blockFrom` list as skipped: OpenFang has no block list, so
+/// those senders would be let in.
+fn block_list_skipped(name: &str, block_from: Option<&[String]>, report: &mut MigrationReport) {
+    if let Some(users) = block_from.filter(|b| !b.is_empty()) {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Channel,
+            name: format!("{name} blockFrom"),
+            reason: format!(
+                "OpenFang cannot block senders, so {} will not be blocked; restrict the \
+                 channel with allowFrom instead",
+                users.join(", ")
+            ),
+        });
+    }
+}
+
+/// Report a Matrix room's own policies as skipped: the room is still listened in,
+/// under the channel-wide policy.
+fn matrix_room_policies_skipped(room: &OpenClawMatrixRoomDetailed, report: &mut MigrationReport) {
+    let list = |users: &Option<Vec<String>>| {
+        users
+            .as_ref()
+            .filter(|u| !u.is_empty())
+            .map(|u| u.join(", "))
+    };
+    let policies: Vec<String> = [
+        ("dmPolicy", room.dm_policy.clone()),
+        ("groupPolicy", room.group_policy.clone()),
+        ("allowFrom", list(&room.allow_from)),
+        ("blockFrom", list(&room.block_from)),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| Some(format!("{key} {}", value?)))
+    .collect();
+    if policies.is_empty() {
+        return;
+    }
+    report.skipped.push(SkippedItem {
+        kind: ItemKind::Channel,
+        name: format!("matrix room {}", room.id),
+        reason: format!(
+            "Per-room policies ({}) are not supported; the room uses the channel-wide policy",
+            policies.join("; ")
+        ),
+    });
+}
+
+/// A parsed OpenClaw model reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ModelRef {
+    provider: String,
+    model: String,
+    /// Azure OpenAI deployment name, from the `@suffix` in `azure/gpt-4o@my-deployment`.
+    deployment: Option<String>,
+}
+
+/// Parse an OpenClaw model reference like `"provider/model"` or `"provider:model"`.
+///
+/// The provider segment is case-normalized; the model keeps its case. A `:` only
+/// separates a known provider, so Ollama-style tags like `llama3:8b` stay intact.
+/// With no separator, the provider falls back to `"anthropic"`.
+fn parse_model_ref(model_ref: &str) -> ModelRef {
+    let colon_split = model_ref
+        .split_once(':')
+        .filter(|(p, _)| is_mapped_provider(p));
+    let (provider, model) = if let Some((p, m)) = model_ref.split_once('/') {
+        (map_provider(p), m)
+    } else if let Some((p, m)) = colon_split {
+        (map_provider(p), m)
+    } else {
+        ("anthropic".to_string(), model_ref)
+    };
+
+    // Azure refs address a deployment rather than a bare model name
+    if provider == "azure-openai" {
+        if let Some((m, d)) = model.split_once('@') {
+            if !d.is_empty() {
+                return ModelRef {
+                    provider,
+                    model: m.to_string(),
+                    deployment: Some(d.to_string()),
+                };
+            }
+        }
+    }
+
+    ModelRef {
+        provider,
+        model: model.to_string(),
+        deployment: None,
+    }
+}
+
+/// Warning for a model reference with no provider segment, which [`parse_model_ref`]
+/// resolves to Anthropic. Bare names like `gpt-4o` often belong elsewhere.
+fn bare_model_warning(model_ref: &str) -> Option<String> {
+    let known_prefix = model_ref
+        .split_once(':')
+        .is_some_and(|(p, _)| is_mapped_provider(p));
+    (!model_ref.is_empty() && !model_ref.contains('/') && !known_prefix)
+        .then(|| format!("model '{model_ref}' had no provider prefix; assumed anthropic"))
+}
+
 /// Split an OpenClaw model reference like `"provider/model"` into `(provider, model)`.
 /// If there's no slash, returns `("anthropic", input)` as a fallback.
 fn split_model_ref(model_ref: &str) -> (String, String) {
-    if let Some(pos) = model_ref.find('/') {
-        let provider = &model_ref[..pos];
-        let model = &model_ref[pos + 1..];
-        (map_provider(provider), model.to_string())
-    } else {
-        ("anthropic".to_string(), model_ref.to_string())
+    let r = parse_model_ref(model_ref);
+    (r.provider, r.model)
+}
+
+/// Retired model IDs and their current successors, as `(provider, retired, successor)`.
+/// Add a row here when a provider retires a model that OpenClaw configs still reference.
+const MODEL_ALIASES: &[(&str, &str, &str)] = &[
+    (
+        "anthropic",
+        "claude-3-5-sonnet-20240620",
+        "claude-sonnet-4-20250514",
+    ),
+    (
+        "anthropic",
+        "claude-3-5-sonnet-20241022",
+        "claude-sonnet-4-20250514",
+    ),
+    (
+        "anthropic",
+        "claude-3-opus-20240229",
+        "claude-opus-4-20250514",
+    ),
+    (
+        "anthropic",
+        "claude-3-haiku-20240307",
+        "claude-haiku-4-5-20251001",
+    ),
+    ("openai", "gpt-4-turbo", "gpt-4o"),
+    ("openai", "gpt-4-turbo-preview", "gpt-4o"),
+    ("openai", "gpt-3.5-turbo", "gpt-4o-mini"),
+    ("google", "gemini-1.5-pro", "gemini-2.5-pro"),
+    ("google", "gemini-1.5-flash", "gemini-2.5-flash"),
+    ("google", "gemini-pro", "gemini-2.5-pro"),
+];
+
+/// Map a retired model ID to its current successor.
+/// Returns the (possibly unchanged) model ID and a warning when a remap happened.
+fn normalize_model_id(provider: &str, model: &str) -> (String, Option<String>) {
+    for (p, retired, successor) in MODEL_ALIASES {
+        if *p == provider && *retired == model {
+            return (
+                successor.to_string(),
+                Some(format!("{model} is retired; migrated to {successor}")),
+            );
+        }
+    }
+    (model.to_string(), None)
+}
+
+/// Credential hint for providers that don't authenticate with a single API key.
+fn provider_credential_note(provider: &str) -> Option<&'static str> {
+    match provider {
+        "bedrock" => Some(
+            "Bedrock uses AWS credentials — set AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY and AWS_REGION",
+        ),
+        "vertex" => Some(
+            "Vertex AI uses a service account — point GOOGLE_APPLICATION_CREDENTIALS at its JSON key file",
+        ),
+        _ => None,
     }
 }
 
+/// `baseUrl` of the `models.providers` entry for a mapped provider name, if any.
+fn provider_base_url(root: Option<&OpenClawRoot>, provider: &str) -> Option<String> {
+    root.and_then(|r| r.models.as_ref())
+        .and_then(|m| m.providers.as_ref())?
+        .iter()
+        .filter(|(name, _)| map_provider(name) == provider)
+        .find_map(|(_, entry)| {
+            entry
+                .get("baseUrl")
+                .or_else(|| entry.get("base_url"))
+                .and_then(|u| u.as_str())
+                .filter(|u| !u.trim().is_empty())
+                .map(str::to_string)
+        })
+}
+
+/// Warnings for Azure deployment names, which have no manifest field. Cloud
+/// providers without a `base_url` are flagged by [`is_openfang_provider`].
+fn cloud_model_warnings(model: &ModelRef) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(ref deployment) = model.deployment {
+        warnings.push(format!(
+            "Azure deployment '{deployment}' was dropped (OpenFang has no deployment setting); \
+             include it in base_url (.../openai/deployments/{deployment})"
+        ));
+    }
+    warnings
+}
+
 /// Extract the primary model string from an agent entry, falling back to defaults.
 fn extract_primary_model(
     agent: &OpenClawAgentEntry,
@@ -570,26 +1323,51 @@ fn extract_primary_model(
     None
 }
 
-/// Extract fallback model strings from an agent entry.
+/// Model params from the agent's detailed model, falling back to the defaults' model.
+fn extract_model_params<'a>(
+    agent: &'a OpenClawAgentEntry,
+    defaults: Option<&'a OpenClawAgentDefaults>,
+) -> Option<&'a serde_json::Map<String, serde_json::Value>> {
+    let detailed = |model: Option<&'a OpenClawAgentModel>| match model {
+        Some(OpenClawAgentModel::Detailed(d)) if !d.params.is_empty() => Some(&d.params),
+        _ => None,
+    };
+    detailed(agent.model.as_ref()).or_else(|| detailed(defaults.and_then(|d| d.model.as_ref())))
+}
+
+/// Resolve the fallback models for an agent. The first non-empty source wins:
+///
+/// 1. the agent's detailed model `fallbacks`;
+/// 2. the defaults' detailed model `fallbacks` — inherited even when the agent
+///    sets its primary as a simple `"provider/model"` string;
+/// 3. model refs (`provider/model`) listed in `auth.order`, minus the primary;
+/// 4. none.
 fn extract_fallback_models(
     agent: &OpenClawAgentEntry,
     defaults: Option<&OpenClawAgentDefaults>,
+    auth_order: Option<&serde_json::Value>,
+    primary: &str,
 ) -> Vec<String> {
-    // Try agent-level
-    if let Some(OpenClawAgentModel::Detailed(ref d)) = agent.model {
-        if !d.fallbacks.is_empty() {
-            return d.fallbacks.clone();
-        }
-    }
-    // Fall back to defaults
-    if let Some(defs) = defaults {
-        if let Some(OpenClawAgentModel::Detailed(ref d)) = defs.model {
-            if !d.fallbacks.is_empty() {
-                return d.fallbacks.clone();
-            }
+    let detailed_fallbacks = |model: Option<&OpenClawAgentModel>| match model {
+        Some(OpenClawAgentModel::Detailed(d)) if !d.fallbacks.is_empty() => {
+            Some(d.fallbacks.clone())
         }
-    }
-    vec![]
+        _ => None,
+    };
+    if let Some(fallbacks) = detailed_fallbacks(agent.model.as_ref()) {
+        return fallbacks;
+    }
+    if let Some(fallbacks) = detailed_fallbacks(defaults.and_then(|d| d.model.as_ref())) {
+        return fallbacks;
+    }
+    auth_order
+        .and_then(|o| o.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .filter(|r| r.contains('/') && *r != primary)
+        .map(str::to_string)
+        .collect()
 }
 
 /// Which config file does this dir contain? Returns the path if found.
@@ -614,28 +1392,204 @@ fn find_config_file(dir: &Path) -> Option<PathBuf> {
     None
 }
 
-// Tool name mapping and recognition are shared with the skill system.
-use openfang_types::tool_compat::{is_known_openfang_tool, map_tool_name};
+/// Provider used when neither the config nor [`MigrateOptions::default_provider`] names one.
+const DEFAULT_PROVIDER: &str = "anthropic";
+/// Model used when neither the config nor [`MigrateOptions::default_model`] names one.
+const DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
+
+/// Provider and model to fall back on when the source has no model configured.
+fn fallback_model(options: &MigrateOptions) -> (String, String) {
+    let provider = options
+        .default_provider
+        .as_deref()
+        .map(map_provider)
+        .unwrap_or_else(|| DEFAULT_PROVIDER.to_string());
+    let model = options
+        .default_model
+        .clone()
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+    (provider, model)
+}
 
-/// Map OpenClaw tool profile to OpenFang capability tool list.
-/// Delegates to `ToolProfile` so the migration and kernel use identical definitions.
-fn tools_for_profile(profile: &str) -> Vec<String> {
-    use openfang_types::agent::ToolProfile;
-    let p = match profile {
-        "minimal" => ToolProfile::Minimal,
-        "coding" => ToolProfile::Coding,
-        "research" => ToolProfile::Research,
-        "messaging" => ToolProfile::Messaging,
-        "automation" => ToolProfile::Automation,
-        _ => ToolProfile::Full,
-    };
-    p.tools()
+// Tool name mapping and recognition are shared with the skill system.
+use openfang_types::tool_compat::{
+    is_known_openfang_tool, map_tool_name, KNOWN_OPENFANG_TOOLS, OPENCLAW_TOOL_ALIASES,
+};
+
+/// User-supplied tool mappings from [`MigrateOptions::tool_map`] and
+/// [`MigrateOptions::tool_aliases`].
+#[derive(Debug, Default)]
+struct ToolMap {
+    entries: std::collections::HashMap<String, String>,
+    /// MCP server tool allowlists, keyed by server name.
+    mcp: std::collections::BTreeMap<String, Vec<String>>,
 }
 
-/// Map OpenClaw provider name to OpenFang provider name.
-fn map_provider(openclaw_provider: &str) -> String {
-    match openclaw_provider.to_lowercase().as_str() {
-        "anthropic" | "claude" => "anthropic".to_string(),
+impl ToolMap {
+    /// Load and validate the tool map file, if one was given, followed by the
+    /// inline aliases.
+    ///
+    /// Entries whose target is not a known OpenFang tool are dropped unless
+    /// `trust_unknown_targets` is set. Entries that override a builtin mapping
+    /// are kept, with a warning.
+    fn load(options: &MigrateOptions, warnings: &mut Vec<String>) -> Result<Self, MigrateError> {
+        let mut raw: std::collections::BTreeMap<String, String> = match options.tool_map {
+            Some(ref path) => {
+                let content = std::fs::read_to_string(path)?;
+                if path.extension().is_some_and(|e| e == "json") {
+                    serde_json::from_str(&content).map_err(|e| {
+                        MigrateError::ConfigParse(format!("tool map {}: {e}", path.display()))
+                    })?
+                } else {
+                    toml::from_str(&content).map_err(|e| {
+                        MigrateError::ConfigParse(format!("tool map {}: {e}", path.display()))
+                    })?
+                }
+            }
+            None => std::collections::BTreeMap::new(),
+        };
+        raw.extend(
+            options
+                .tool_aliases
+                .iter()
+                .map(|(from, to)| (from.clone(), to.clone())),
+        );
+
+        let mut entries = std::collections::HashMap::new();
+        for (from, to) in raw {
+            if !options.trust_unknown_targets && !is_known_openfang_tool(&to) {
+                warnings.push(format!(
+                    "Tool map entry '{from}' -> '{to}' ignored: '{to}' is not a known OpenFang tool"
+                ));
+                continue;
+            }
+            let builtin = if is_known_openfang_tool(&from) {
+                Some(from.as_str())
+            } else {
+                map_tool_name(&from)
+            };
+            if let Some(builtin) = builtin.filter(|b| *b != to) {
+                warnings.push(format!(
+                    "Tool map entry '{from}' -> '{to}' overrides builtin mapping to '{builtin}'"
+                ));
+            }
+            entries.insert(from, to);
+        }
+        Ok(Self {
+            entries,
+            ..Default::default()
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    /// The MCP server an agent tool entry refers to (`<server>` or `mcp:<server>`).
+    fn mcp_server_ref(&self, name: &str) -> Option<&str> {
+        let server = name.strip_prefix("mcp:").unwrap_or(name);
+        self.mcp.get_key_value(server).map(|(k, _)| k.as_str())
+    }
+}
+
+/// Map OpenClaw tool profile to OpenFang capability tool list.
+/// Delegates to `ToolProfile` so the migration and kernel use identical definitions.
+/// Unknown profiles get the Full set; see [`unknown_profile_warning`].
+fn tools_for_profile(profile: &str) -> Vec<String> {
+    parse_tool_profile(profile)
+        .unwrap_or(openfang_types::agent::ToolProfile::Full)
+        .tools()
+}
+
+/// The OpenFang profile an OpenClaw profile name corresponds to, if any.
+fn parse_tool_profile(profile: &str) -> Option<openfang_types::agent::ToolProfile> {
+    use openfang_types::agent::ToolProfile;
+    Some(match profile.to_lowercase().as_str() {
+        "minimal" => ToolProfile::Minimal,
+        "coding" => ToolProfile::Coding,
+        "research" => ToolProfile::Research,
+        "messaging" => ToolProfile::Messaging,
+        "automation" => ToolProfile::Automation,
+        "browser" => ToolProfile::Browser,
+        "devops" => ToolProfile::Devops,
+        "writing" => ToolProfile::Writing,
+        "full" => ToolProfile::Full,
+        _ => return None,
+    })
+}
+
+/// Warning for a profile [`tools_for_profile`] had to widen to Full, if it did.
+fn unknown_profile_warning(profile: &str) -> Option<String> {
+    parse_tool_profile(profile).is_none().then(|| {
+        format!(
+            "Unknown tool profile '{profile}'; granted the Full profile (all tools, including shell and network access)"
+        )
+    })
+}
+
+/// Canonical OpenFang provider names produced by [`map_provider`] that OpenFang's
+/// drivers load natively.
+const KNOWN_PROVIDERS: &[&str] = &[
+    "anthropic",
+    "openai",
+    "groq",
+    "ollama",
+    "openrouter",
+    "deepseek",
+    "together",
+    "mistral",
+    "fireworks",
+    "google",
+    "xai",
+    "zai",
+    "zai-global",
+    "cerebras",
+    "sambanova",
+];
+
+/// Cloud providers [`map_provider`] produces that OpenFang has no driver for; an
+/// agent using one loads only when a `base_url` points at an OpenAI-compatible
+/// endpoint.
+const CLOUD_PROVIDERS: &[&str] = &["azure-openai", "bedrock", "vertex"];
+
+/// Providers OpenFang's drivers accept beyond [`KNOWN_PROVIDERS`], mirroring
+/// `openfang_runtime::drivers::known_providers` (this crate does not depend on
+/// the runtime). OpenClaw names for these pass through [`map_provider`] as-is.
+const PASSTHROUGH_PROVIDERS: &[&str] = &[
+    "vllm",
+    "lmstudio",
+    "perplexity",
+    "cohere",
+    "ai21",
+    "huggingface",
+    "replicate",
+    "github-copilot",
+    "moonshot",
+    "qwen",
+    "minimax",
+    "zhipu",
+    "qianfan",
+];
+
+/// Whether OpenFang can load a model from a mapped provider: one its drivers know,
+/// or any provider given a `base_url` (treated as OpenAI-compatible).
+fn is_openfang_provider(provider: &str, base_url: Option<&str>) -> bool {
+    base_url.is_some()
+        || KNOWN_PROVIDERS.contains(&provider)
+        || PASSTHROUGH_PROVIDERS.contains(&provider)
+}
+
+/// Whether an OpenClaw provider name maps to a provider OpenFang knows by name,
+/// with or without a driver for it.
+fn is_mapped_provider(openclaw_provider: &str) -> bool {
+    let provider = map_provider(openclaw_provider);
+    KNOWN_PROVIDERS.contains(&provider.as_str()) || CLOUD_PROVIDERS.contains(&provider.as_str())
+}
+
+/// Map OpenClaw provider name to OpenFang provider name.
+fn map_provider(openclaw_provider: &str) -> String {
+    match openclaw_provider.to_lowercase().as_str() {
+        "anthropic" | "claude" => "anthropic".to_string(),
         "openai" | "gpt" => "openai".to_string(),
         "groq" => "groq".to_string(),
         "ollama" => "ollama".to_string(),
@@ -650,10 +1604,69 @@ fn map_provider(openclaw_provider: &str) -> String {
         "z.ai-global" | "zai-global" | "zai_global" => "zai-global".to_string(),
         "cerebras" => "cerebras".to_string(),
         "sambanova" => "sambanova".to_string(),
+        "azure" | "azure-openai" | "azure_openai" | "azureopenai" => "azure-openai".to_string(),
+        "bedrock" | "aws-bedrock" | "amazon-bedrock" => "bedrock".to_string(),
+        "vertex" | "vertex-ai" | "vertexai" | "google-vertex" => "vertex".to_string(),
         other => other.to_string(),
     }
 }
 
+/// Resolve an `identity` value that may be a file reference rather than inline text.
+///
+/// Single-line values that look like paths (`./`, `../`, `~/`, absolute, or a
+/// `.md`/`.txt` name) are read relative to the source dir; a missing file keeps
+/// the literal string and records a warning.
+fn resolve_identity(identity: &str, source_dir: &Path, warnings: &mut Vec<String>) -> String {
+    let value = identity.trim();
+    let path_like = !value.contains('\n')
+        && (value.starts_with("./")
+            || value.starts_with("../")
+            || value.starts_with("~/")
+            || Path::new(value).is_absolute()
+            || value.ends_with(".md")
+            || value.ends_with(".txt"));
+    if !path_like {
+        return identity.to_string();
+    }
+
+    match expand_config_path(value, source_dir).map(|p| std::fs::read_to_string(&p)) {
+        Some(Ok(content)) => content.trim_end().to_string(),
+        _ => {
+            warnings.push(format!(
+                "Identity file '{value}' not found; using the value as literal text"
+            ));
+            identity.to_string()
+        }
+    }
+}
+
+/// Resolve a path from openclaw.json: `~/` is the home dir, relative paths are
+/// taken from the source dir. `None` only if the home dir is unknown.
+fn expand_config_path(value: &str, source_dir: &Path) -> Option<PathBuf> {
+    match value.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|h| h.join(rest)),
+        None => Some(source_dir.join(value)),
+    }
+}
+
+/// Agent-specific env var for a literal per-agent API key, e.g. `CODER_ANTHROPIC_API_KEY`.
+fn agent_api_key_env(agent_id: &str, provider: &str) -> String {
+    let prefix: String = agent_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match default_api_key_env(provider) {
+        env if env.is_empty() => format!("{prefix}_API_KEY"),
+        env => format!("{prefix}_{env}"),
+    }
+}
+
 /// Map OpenClaw provider to its default API key env var.
 fn default_api_key_env(provider: &str) -> String {
     match provider {
@@ -671,14 +1684,66 @@ fn default_api_key_env(provider: &str) -> String {
         "zai-global" => "ZAI_GLOBAL_API_KEY".to_string(),
         "cerebras" => "CEREBRAS_API_KEY".to_string(),
         "sambanova" => "SAMBANOVA_API_KEY".to_string(),
+        "azure-openai" => "AZURE_OPENAI_API_KEY".to_string(),
+        "bedrock" => "AWS_ACCESS_KEY_ID".to_string(),
+        "vertex" => "GOOGLE_APPLICATION_CREDENTIALS".to_string(),
         "ollama" => String::new(), // Ollama doesn't need an API key
         _ => format!("{}_API_KEY", provider.to_uppercase()),
     }
 }
 
+/// Override the tool-derived memory grants with the scope the source configured.
+///
+/// Agent settings win over root ones field by field; explicit namespaces win over
+/// the `shared` flag. Returns a short description of the applied scope, or `None`
+/// when the source expressed nothing and the tool-derived defaults stand.
+fn apply_memory_scope(
+    caps: &mut AgentCapabilities,
+    agent: &OpenClawMemoryScope,
+    root: &OpenClawMemoryScope,
+) -> Option<String> {
+    let namespaces = agent
+        .namespaces
+        .as_ref()
+        .or(root.namespaces.as_ref())
+        .filter(|n| !n.is_empty());
+    if let Some(namespaces) = namespaces {
+        let mut globs = vec!["self.*".to_string()];
+        for ns in namespaces {
+            let glob = if ns.contains('*') {
+                ns.clone()
+            } else {
+                format!("{ns}.*")
+            };
+            if !globs.contains(&glob) {
+                globs.push(glob);
+            }
+        }
+        caps.memory_read = globs.clone();
+        caps.memory_write = globs;
+        return Some(format!("namespaces {}", namespaces.join(", ")));
+    }
+    match agent.shared.or(root.shared)? {
+        true => {
+            caps.memory_read = vec!["*".to_string()];
+            caps.memory_write = vec!["self.*".to_string(), "shared.*".to_string()];
+            Some("shared".to_string())
+        }
+        false => {
+            caps.memory_read = vec!["self.*".to_string()];
+            caps.memory_write = vec!["self.*".to_string()];
+            Some("isolated".to_string())
+        }
+    }
+}
+
 /// Derive capability grants from the tool list.
 fn derive_capabilities(tools: &[String]) -> AgentCapabilities {
-    let mut caps = AgentCapabilities::default();
+    let mut caps = AgentCapabilities {
+        memory_read: vec!["*".to_string()],
+        memory_write: vec!["self.*".to_string()],
+        ..Default::default()
+    };
 
     for tool in tools {
         match tool.as_str() {
@@ -687,14 +1752,18 @@ fn derive_capabilities(tools: &[String]) -> AgentCapabilities {
                 caps.network = vec!["*".to_string()];
                 caps.agent_message = vec!["*".to_string()];
                 caps.agent_spawn = true;
+                caps.memory_read = vec!["*".to_string()];
+                caps.memory_write = vec!["*".to_string()];
             }
             "shell_exec" => {
                 caps.shell = vec!["*".to_string()];
             }
-            "web_fetch" | "web_search" | "browser_navigate" => {
-                if caps.network.is_empty() {
-                    caps.network = vec!["*".to_string()];
-                }
+            "web_fetch" | "web_search" if caps.network.is_empty() => {
+                caps.network = vec!["*".to_string()];
+            }
+            // Browser automation fetches arbitrary pages
+            t if t.starts_with("browser_") && caps.network.is_empty() => {
+                caps.network = vec!["*".to_string()];
             }
             "agent_send" | "agent_list" => {
                 if caps.agent_message.is_empty() {
@@ -702,6 +1771,9 @@ fn derive_capabilities(tools: &[String]) -> AgentCapabilities {
                 }
                 caps.agent_spawn = true;
             }
+            "memory_store" if caps.memory_write == ["self.*"] => {
+                caps.memory_write = vec!["self.*".to_string(), "shared.*".to_string()];
+            }
             _ => {}
         }
     }
@@ -709,14 +1781,470 @@ fn derive_capabilities(tools: &[String]) -> AgentCapabilities {
     caps
 }
 
-#[derive(Default)]
+/// Narrow the network and shell capabilities to the allowlists configured on
+/// the tools that need them (`webFetch.allowDomains`, `bash.allow`, ...).
+fn scope_capabilities(
+    caps: &mut AgentCapabilities,
+    tools: &[String],
+    agent_tools: Option<&OpenClawAgentTools>,
+    warnings: &mut Vec<String>,
+) {
+    let Some(agent_tools) = agent_tools else {
+        return;
+    };
+    if tools.iter().any(|t| t == "*") {
+        return;
+    }
+
+    if !caps.network.is_empty() {
+        if let Some(domains) = scoped_capability(
+            "network",
+            &["web_fetch", "web_search", "browser_navigate"],
+            &["allowDomains", "allowedDomains"],
+            tools,
+            agent_tools,
+            warnings,
+        ) {
+            caps.network = domains
+                .iter()
+                .flat_map(|d| net_connect_patterns(d))
+                .collect();
+        }
+    }
+
+    if !caps.shell.is_empty() {
+        if let Some(commands) = scoped_capability(
+            "shell",
+            &["shell_exec"],
+            &["allow", "allowCommands"],
+            tools,
+            agent_tools,
+            warnings,
+        ) {
+            caps.shell = commands;
+        }
+        if agent_tools
+            .options_for("shell_exec")
+            .is_some_and(|o| o.contains_key("deny") || o.contains_key("denyCommands"))
+        {
+            warnings.push(
+                "shell deny patterns have no OpenFang equivalent; shell access is wider than before"
+                    .to_string(),
+            );
+        }
+    }
+}
+
+/// `NetConnect` patterns for an allowlisted domain.
+///
+/// Grants are matched against `host:port` and allow a single `*`, so a bare
+/// domain gets any port, while a wildcard domain (which has used up its `*`)
+/// gets the two web ports. Entries that already name a port are kept.
+fn net_connect_patterns(domain: &str) -> Vec<String> {
+    if domain == "*" || domain.contains(':') {
+        vec![domain.to_string()]
+    } else if domain.contains('*') {
+        vec![format!("{domain}:443"), format!("{domain}:80")]
+    } else {
+        vec![format!("{domain}:*")]
+    }
+}
+
+/// The patterns a capability should be limited to, based on per-tool allowlists.
+///
+/// Returns `None` to keep the wildcard: either no tool in `family` is restricted,
+/// or only some are, in which case a warning records that the scope was widened.
+fn scoped_capability(
+    kind: &str,
+    family: &[&str],
+    keys: &[&str],
+    tools: &[String],
+    agent_tools: &OpenClawAgentTools,
+    warnings: &mut Vec<String>,
+) -> Option<Vec<String>> {
+    let mut patterns = Vec::new();
+    let mut restricted = Vec::new();
+    let mut unrestricted = Vec::new();
+    for tool in family {
+        if !tools.iter().any(|t| t == tool) {
+            continue;
+        }
+        match agent_tools.option_list(tool, keys) {
+            Some(list) => {
+                restricted.push(*tool);
+                patterns.extend(list);
+            }
+            None => unrestricted.push(*tool),
+        }
+    }
+
+    if restricted.is_empty() {
+        return None;
+    }
+    if !unrestricted.is_empty() {
+        warnings.push(format!(
+            "{kind} scope widened to \"*\": {} restricted but {} did not",
+            restricted.join(", "),
+            unrestricted.join(", ")
+        ));
+        return None;
+    }
+    patterns.sort();
+    patterns.dedup();
+    Some(patterns)
+}
+
+/// `[capabilities]` table of a migrated agent; field order is output order.
+#[derive(Default, Serialize)]
 struct AgentCapabilities {
-    shell: Vec<String>,
+    tools: Vec<String>,
+    memory_read: Vec<String>,
+    memory_write: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     network: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    shell: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     agent_message: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
     agent_spawn: bool,
 }
 
+impl AgentCapabilities {
+    /// One `name: values` line per granted capability, for the migration notes.
+    fn summary(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, values) in [
+            ("memory_read", &self.memory_read),
+            ("memory_write", &self.memory_write),
+            ("network", &self.network),
+            ("shell", &self.shell),
+            ("agent_message", &self.agent_message),
+        ] {
+            if !values.is_empty() {
+                lines.push(format!("{name}: {}", values.join(", ")));
+            }
+        }
+        if self.agent_spawn {
+            lines.push("agent_spawn".to_string());
+        }
+        lines
+    }
+}
+
+/// A migrated `agent.toml`; field order is output order.
+#[derive(Serialize)]
+struct AgentManifest {
+    name: String,
+    version: String,
+    description: String,
+    author: String,
+    module: String,
+    /// Tool profile the kernel expands when `capabilities.tools` is empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    /// OpenClaw profile with no OpenFang equivalent, kept as a comment.
+    #[serde(skip)]
+    unknown_profile: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    mcp_servers: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<ManifestSchedule>,
+    model: ManifestModel,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fallback_models: Vec<ManifestModel>,
+    capabilities: AgentCapabilities,
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    metadata: std::collections::BTreeMap<String, String>,
+}
+
+impl AgentManifest {
+    /// Manifest with the fixed fields every migrated agent shares.
+    fn new(name: &str, description: String, model: ManifestModel) -> Self {
+        Self {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            description,
+            author: "openfang".to_string(),
+            module: "builtin:chat".to_string(),
+            profile: None,
+            unknown_profile: None,
+            tags: Vec::new(),
+            mcp_servers: Vec::new(),
+            schedule: None,
+            model,
+            fallback_models: Vec::new(),
+            capabilities: AgentCapabilities::default(),
+            metadata: Default::default(),
+        }
+    }
+
+    /// Record an OpenClaw tool profile as the manifest's top-level `profile` when it
+    /// names an OpenFang [`ToolProfile`](openfang_types::agent::ToolProfile); other
+    /// names would fail to load, so they are only kept as a comment.
+    fn set_tool_profile(&mut self, profile: Option<String>) {
+        let Some(profile) = profile else {
+            return;
+        };
+        let known = serde_json::from_value::<openfang_types::agent::ToolProfile>(
+            serde_json::Value::String(profile.clone()),
+        )
+        .is_ok();
+        if known {
+            self.profile = Some(profile);
+        } else {
+            self.unknown_profile = Some(profile);
+        }
+    }
+
+    /// Render as TOML under a header comment naming the source agent.
+    fn to_toml(&self, source_id: &str) -> Result<String, MigrateError> {
+        let mut out = format!(
+            "# OpenFang agent manifest\n# Migrated from OpenClaw agent '{source_id}'\n\n{}",
+            toml::to_string_pretty(self)?
+        );
+        if let Some(ref profile) = self.unknown_profile {
+            out.push_str(&format!(
+                "\n# OpenClaw tool profile '{profile}' has no OpenFang equivalent; \
+                 its tools are listed under [capabilities]\n"
+            ));
+        }
+        Ok(out)
+    }
+}
+
+/// Smallest `maxOutputTokens` that still leaves room for a useful reply.
+const MIN_OUTPUT_TOKENS: u64 = 64;
+
+/// Apply an agent's `maxContextTokens` / `maxOutputTokens` / `historyLimit`, each
+/// falling back to `agents.defaults`.
+///
+/// The output cap becomes `[model] max_tokens`: an agent's own value beats model
+/// params, while an inherited one only fills a gap. A value below a working floor
+/// is kept, with a warning. The other two have no kernel field and are returned
+/// as skipped items for agent `id`.
+fn resolve_limits(
+    id: &str,
+    entry: &OpenClawAgentEntry,
+    defaults: Option<&OpenClawAgentDefaults>,
+    params: &mut ModelParams,
+    warnings: &mut Vec<String>,
+) -> Vec<SkippedItem> {
+    let inherited = defaults.map(|d| &d.limits);
+    let pick = |f: fn(&OpenClawLimits) -> Option<u64>| f(&entry.limits).or(inherited.and_then(f));
+    let context = pick(|l| l.max_context_tokens);
+    let history = pick(|l| l.history_limit);
+
+    if let Some(own) = entry.limits.max_output_tokens {
+        params.max_tokens = Some(clamp_max_tokens(own, warnings));
+    } else if params.max_tokens.is_none() {
+        params.max_tokens = inherited
+            .and_then(|l| l.max_output_tokens)
+            .map(|t| clamp_max_tokens(t, warnings));
+    }
+    let output = params.max_tokens.map(u64::from);
+
+    if let Some(out) = output.filter(|&t| t < MIN_OUTPUT_TOKENS) {
+        warnings.push(format!(
+            "maxOutputTokens {out} is below {MIN_OUTPUT_TOKENS}; replies will be cut off"
+        ));
+    }
+
+    [
+        ("maxContextTokens", context, "tokens", "context"),
+        ("historyLimit", history, "messages", "history"),
+    ]
+    .into_iter()
+    .filter_map(|(key, value, unit, what)| {
+        Some(SkippedItem {
+            kind: ItemKind::Config,
+            name: format!("{id}.{key}"),
+            reason: format!(
+                "{} {unit}; OpenFang has no per-agent {what} limit, so it was not applied",
+                value?
+            ),
+        })
+    })
+    .collect()
+}
+
+/// `[schedule]` of a migrated agent, mirroring the kernel's `ScheduleMode`.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ManifestSchedule {
+    /// Wakes every interval; `cron` uses the kernel's `every <N><unit>` form.
+    Periodic { cron: String },
+}
+
+/// Convert an agent's heartbeat (or, failing that, schedule) into a periodic schedule.
+///
+/// Returns the schedule and its prompt, if any. The kernel sends its own tick
+/// prompt, so callers keep the OpenClaw prompt in `[metadata]`. Cron expressions
+/// are left to the cron migration with a warning.
+fn convert_schedule(
+    entry: &OpenClawAgentEntry,
+    warnings: &mut Vec<String>,
+) -> Option<(ManifestSchedule, Option<String>)> {
+    let (field, block) = match (&entry.heartbeat, &entry.schedule) {
+        (Some(heartbeat), schedule) => {
+            if schedule.is_some() {
+                warnings.push(
+                    "has both heartbeat and schedule; only the heartbeat was migrated".to_string(),
+                );
+            }
+            ("heartbeat", heartbeat)
+        }
+        (None, Some(schedule)) => ("schedule", schedule),
+        (None, None) => return None,
+    };
+    if block.enabled == Some(false) {
+        return None;
+    }
+    let cron = block.cron.as_deref().or_else(|| {
+        block
+            .every
+            .as_deref()
+            .filter(|e| e.split_whitespace().count() >= 5)
+    });
+    if let Some(cron) = cron {
+        warnings.push(format!(
+            "{field} uses cron syntax ('{cron}'); migrate it with the cron \
+             migration instead — the agent stays reactive"
+        ));
+        return None;
+    }
+    let every = block.every.as_deref()?;
+    let Some(secs) = parse_interval_secs(every) else {
+        warnings.push(format!(
+            "{field} interval '{every}' is not understood; the agent stays reactive"
+        ));
+        return None;
+    };
+    let prompt = block.prompt.clone().filter(|p| !p.trim().is_empty());
+    Some((
+        ManifestSchedule::Periodic {
+            cron: every_expression(secs),
+        },
+        prompt,
+    ))
+}
+
+/// Parse `"30m"`, `"1h30m"`, `"90"` (seconds), `"hourly"` or `"daily"` into seconds.
+fn parse_interval_secs(text: &str) -> Option<u64> {
+    let text = text.trim().to_lowercase();
+    match text.as_str() {
+        "hourly" => return Some(3600),
+        "daily" => return Some(86_400),
+        "weekly" => return Some(7 * 86_400),
+        _ => {}
+    }
+    let text = text
+        .strip_prefix("every ")
+        .unwrap_or(&text)
+        .replace(' ', "");
+    if let Ok(secs) = text.parse::<u64>() {
+        return (secs > 0).then_some(secs);
+    }
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            'w' => 7 * 86_400,
+            _ => return None,
+        };
+        total += digits.parse::<u64>().ok()? * unit;
+        digits.clear();
+    }
+    (digits.is_empty() && total > 0).then_some(total)
+}
+
+/// Render seconds in the single-unit `every <N><unit>` form the kernel parses.
+fn every_expression(secs: u64) -> String {
+    let (n, unit) = if secs.is_multiple_of(86_400) {
+        (secs / 86_400, 'd')
+    } else if secs.is_multiple_of(3600) {
+        (secs / 3600, 'h')
+    } else if secs.is_multiple_of(60) {
+        (secs / 60, 'm')
+    } else {
+        (secs, 's')
+    };
+    format!("every {n}{unit}")
+}
+
+/// `[model]` / `[[fallback_models]]` entry of a migrated agent.
+#[derive(Serialize)]
+struct ManifestModel {
+    provider: String,
+    model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key_env: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base_url: Option<String>,
+    #[serde(flatten)]
+    params: ModelParams,
+}
+
+/// Tuning parameters of a migrated `[model]` table: only the ones the kernel's
+/// `ModelConfig` has.
+#[derive(Default, Serialize)]
+struct ModelParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+}
+
+impl ModelParams {
+    /// Convert OpenClaw model params. Keys are matched ignoring case and `_`/`-`;
+    /// anything else (or of the wrong type) is dropped with a warning.
+    fn from_openclaw(
+        params: Option<&serde_json::Map<String, serde_json::Value>>,
+        warnings: &mut Vec<String>,
+    ) -> Self {
+        let mut out = Self::default();
+        for (key, value) in params.into_iter().flatten() {
+            let recognized = match key.to_lowercase().replace(['_', '-'], "").as_str() {
+                "temperature" => value.as_f64().map(|v| out.temperature = Some(v)),
+                "maxtokens" | "maxoutputtokens" | "maxcompletiontokens" => value
+                    .as_u64()
+                    .map(|v| out.max_tokens = Some(clamp_max_tokens(v, warnings))),
+                _ => None,
+            };
+            if recognized.is_none() {
+                warnings.push(format!(
+                    "model param '{key}' has no OpenFang equivalent and was dropped"
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Fit an output token cap into the kernel's `u32` `max_tokens`, warning when
+/// it has to be clamped.
+fn clamp_max_tokens(tokens: u64, warnings: &mut Vec<String>) -> u32 {
+    u32::try_from(tokens).unwrap_or_else(|_| {
+        warnings.push(format!(
+            "max output tokens {tokens} exceed OpenFang's limit; clamped to {}",
+            u32::MAX
+        ));
+        u32::MAX
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Auto-detection
 // ---------------------------------------------------------------------------
@@ -780,29 +2308,131 @@ pub fn scan_openclaw_workspace(path: &Path) -> ScanResult {
         channels: vec![],
         skills: vec![],
         has_memory: false,
+        scan_errors: vec![],
+        agent_count: 0,
+        source_version: None,
     };
 
     if let (true, Some(ref cf)) = (is_json5, &config_file) {
         scan_from_json5(path, cf, &mut result);
     } else {
         scan_from_legacy_yaml(path, &mut result);
+        if config_file.is_none() {
+            scan_from_agent_dirs(path, &mut result);
+        }
+        result.agent_count = result.agents.len();
     }
 
     result
 }
 
+/// With no config to read, list agents by the `memory/<id>/` and `workspaces/<id>/`
+/// directories they left behind. Model and tool details are unknown.
+fn scan_from_agent_dirs(base: &Path, result: &mut ScanResult) {
+    let mut ids = std::collections::BTreeSet::new();
+    for dir in ["memory", "workspaces"] {
+        let Ok(entries) = std::fs::read_dir(base.join(dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.path().is_dir() {
+                ids.insert(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+
+    for id in ids {
+        if result.agents.iter().any(|a| a.name == id) {
+            continue;
+        }
+        let has_memory = base.join("memory").join(&id).join("MEMORY.md").exists();
+        if has_memory {
+            result.has_memory = true;
+        }
+        result.agents.push(ScannedAgent {
+            has_memory,
+            has_sessions: base.join("sessions").exists(),
+            has_workspace: base.join("workspaces").join(&id).exists(),
+            name: id,
+            description: String::new(),
+            provider: String::new(),
+            model: String::new(),
+            tool_count: 0,
+            tags: Vec::new(),
+        });
+    }
+}
+
+/// Parse `openclaw.json`, tolerating a leading UTF-8 BOM and CRLF line endings.
+///
+/// On failure the error names the byte offset of the problem in the original file.
+fn parse_openclaw_json5(content: &str, path: &Path) -> Result<OpenClawRoot, MigrateError> {
+    let bom_len = if content.starts_with('\u{feff}') {
+        '\u{feff}'.len_utf8()
+    } else {
+        0
+    };
+    let body = &content[bom_len..];
+    let normalized = body.replace("\r\n", "\n");
+
+    json5::from_str(&normalized).map_err(|e| {
+        let json5::Error::Message { msg, location } = e;
+        match location {
+            Some(loc) => {
+                let offset = bom_len + json5_byte_offset(body, loc.line, loc.column);
+                MigrateError::Json5Parse(format!(
+                    "{}: {msg} at byte {offset} (line {}, column {})",
+                    path.display(),
+                    loc.line,
+                    loc.column
+                ))
+            }
+            None => MigrateError::Json5Parse(format!("{}: {msg}", path.display())),
+        }
+    })
+}
+
+/// Convert a one-based line/column (in chars) into a byte offset within `text`.
+///
+/// Lines are split on `\n`, so CRLF normalisation does not shift line numbers.
+fn json5_byte_offset(text: &str, line: usize, column: usize) -> usize {
+    let mut offset = 0;
+    for (i, l) in text.split_inclusive('\n').enumerate() {
+        if i + 1 == line {
+            return offset
+                + l.char_indices()
+                    .nth(column.saturating_sub(1))
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(l.len());
+        }
+        offset += l.len();
+    }
+    text.len()
+}
+
 fn scan_from_json5(base: &Path, config_path: &Path, result: &mut ScanResult) {
     let content = match std::fs::read_to_string(config_path) {
         Ok(c) => c,
-        Err(_) => return,
+        Err(e) => {
+            result
+                .scan_errors
+                .push(format!("Failed to read {}: {e}", config_path.display()));
+            return;
+        }
     };
-    let root: OpenClawRoot = match json5::from_str(&content) {
+    let root = match parse_openclaw_json5(&content, config_path) {
         Ok(r) => r,
-        Err(_) => return,
+        Err(e) => {
+            result.scan_errors.push(e.to_string());
+            return;
+        }
     };
 
+    result.source_version = root.source_version();
+
     // Agents from JSON config
     if let Some(ref agents) = root.agents {
+        result.agent_count = expand_subagents(&agents.list, None).len();
         for entry in &agents.list {
             let id = entry.id.clone();
             let name = entry.name.clone().unwrap_or_else(|| id.clone());
@@ -836,58 +2466,23 @@ fn scan_from_json5(base: &Path, config_path: &Path, result: &mut ScanResult) {
 
             result.agents.push(ScannedAgent {
                 name,
-                description: String::new(),
+                description: entry.description.clone().unwrap_or_default(),
                 provider,
                 model,
                 tool_count,
                 has_memory,
                 has_sessions,
                 has_workspace,
+                tags: entry.tags.clone(),
             });
         }
     }
 
-    // Channels from JSON config — scan all 13 typed fields + catch-all
+    // Channels from JSON config — typed fields + catch-all
     if let Some(ref channels) = root.channels {
-        if channels.telegram.is_some() {
-            result.channels.push("telegram".to_string());
-        }
-        if channels.discord.is_some() {
-            result.channels.push("discord".to_string());
-        }
-        if channels.slack.is_some() {
-            result.channels.push("slack".to_string());
-        }
-        if channels.whatsapp.is_some() {
-            result.channels.push("whatsapp".to_string());
-        }
-        if channels.signal.is_some() {
-            result.channels.push("signal".to_string());
-        }
-        if channels.matrix.is_some() {
-            result.channels.push("matrix".to_string());
-        }
-        if channels.google_chat.is_some() {
-            result.channels.push("google_chat".to_string());
-        }
-        if channels.teams.is_some() {
-            result.channels.push("teams".to_string());
-        }
-        if channels.irc.is_some() {
-            result.channels.push("irc".to_string());
-        }
-        if channels.mattermost.is_some() {
-            result.channels.push("mattermost".to_string());
-        }
-        if channels.feishu.is_some() {
-            result.channels.push("feishu".to_string());
-        }
-        if channels.imessage.is_some() {
-            result.channels.push("imessage".to_string());
-        }
-        if channels.bluebubbles.is_some() {
-            result.channels.push("bluebubbles".to_string());
-        }
+        result
+            .channels
+            .extend(channels.configured().into_iter().map(str::to_string));
         for key in channels.other.keys() {
             result.channels.push(key.clone());
         }
@@ -948,20 +2543,30 @@ fn scan_from_legacy_yaml(path: &Path, result: &mut ScanResult) {
                 let mut provider = String::new();
                 let mut model = String::new();
                 let mut tool_count = 0;
-
-                if let Ok(yaml_str) = std::fs::read_to_string(&agent_yaml) {
-                    if let Ok(oc) = serde_yaml::from_str::<LegacyYamlAgent>(&yaml_str) {
-                        description = oc.description.clone();
-                        provider = oc.provider.unwrap_or_default();
-                        model = oc.model.unwrap_or_default();
-                        tool_count = if !oc.tools.is_empty() {
-                            oc.tools.len()
-                        } else if oc.tool_profile.is_some() {
-                            tools_for_profile(oc.tool_profile.as_deref().unwrap_or("")).len()
-                        } else {
-                            3
-                        };
-                    }
+                let mut tags = Vec::new();
+
+                match std::fs::read_to_string(&agent_yaml) {
+                    Ok(yaml_str) => match serde_yaml::from_str::<LegacyYamlAgent>(&yaml_str) {
+                        Ok(oc) => {
+                            description = oc.description.clone();
+                            tags = oc.tags;
+                            provider = oc.provider.unwrap_or_default();
+                            model = oc.model.unwrap_or_default();
+                            tool_count = if !oc.tools.is_empty() {
+                                oc.tools.len()
+                            } else if oc.tool_profile.is_some() {
+                                tools_for_profile(oc.tool_profile.as_deref().unwrap_or("")).len()
+                            } else {
+                                3
+                            };
+                        }
+                        Err(e) => result
+                            .scan_errors
+                            .push(format!("Failed to parse {}: {e}", agent_yaml.display())),
+                    },
+                    Err(e) => result
+                        .scan_errors
+                        .push(format!("Failed to read {}: {e}", agent_yaml.display())),
                 }
 
                 result.agents.push(ScannedAgent {
@@ -973,6 +2578,7 @@ fn scan_from_legacy_yaml(path: &Path, result: &mut ScanResult) {
                     has_memory,
                     has_sessions,
                     has_workspace,
+                    tags,
                 });
             }
         }
@@ -1026,6 +2632,28 @@ fn scan_from_legacy_yaml(path: &Path, result: &mut ScanResult) {
     }
 }
 
+/// Channels the migrator converts into OpenFang channel config, by their
+/// `config.toml` table names.
+pub const SUPPORTED_CHANNELS: &[&str] = &[
+    "telegram",
+    "discord",
+    "slack",
+    "whatsapp",
+    "signal",
+    "matrix",
+    "google_chat",
+    "teams",
+    "irc",
+    "mattermost",
+    "feishu",
+];
+
+/// Whether the migrator can convert the named channel (false for iMessage,
+/// BlueBubbles and anything unknown).
+pub fn channel_is_supported(name: &str) -> bool {
+    SUPPORTED_CHANNELS.contains(&name)
+}
+
 /// Result of scanning an OpenClaw workspace.
 #[derive(Debug, Clone, Serialize)]
 pub struct ScanResult {
@@ -1035,6 +2663,13 @@ pub struct ScanResult {
     pub channels: Vec<String>,
     pub skills: Vec<String>,
     pub has_memory: bool,
+    /// Files that could not be read or parsed while scanning.
+    pub scan_errors: Vec<String>,
+    /// Agents a migration would create, subagents included; compare against
+    /// [`MigrateOptions::max_agents`] before running.
+    pub agent_count: usize,
+    /// The config's `version`/`schemaVersion`, if it declares one.
+    pub source_version: Option<String>,
 }
 
 /// An agent found during scanning.
@@ -1048,6 +2683,7 @@ pub struct ScannedAgent {
     pub has_memory: bool,
     pub has_sessions: bool,
     pub has_workspace: bool,
+    pub tags: Vec<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -1056,6 +2692,15 @@ pub struct ScannedAgent {
 
 /// Run the OpenClaw migration.
 pub fn migrate(options: &MigrateOptions) -> Result<MigrationReport, MigrateError> {
+    if options.quiet {
+        // Scope a no-op subscriber to this call so embedders don't need a global filter
+        let silent = tracing::subscriber::NoSubscriber::default();
+        return tracing::subscriber::with_default(silent, || run_migrate(options));
+    }
+    run_migrate(options)
+}
+
+fn run_migrate(options: &MigrateOptions) -> Result<MigrationReport, MigrateError> {
     let source = &options.source_dir;
     let target = &options.target_dir;
 
@@ -1078,61 +2723,187 @@ pub fn migrate(options: &MigrateOptions) -> Result<MigrationReport, MigrateError
         .is_some_and(|p| p.extension().is_some_and(|e| e == "json"));
 
     if is_json5 {
-        migrate_from_json5(source, target, options.dry_run, &mut report)?;
+        migrate_from_json5(source, target, options, &mut report)?;
     } else {
-        migrate_from_legacy_yaml(source, target, options.dry_run, &mut report)?;
-    }
-
-    // Save report
-    if !options.dry_run {
-        let report_md = report.to_markdown();
-        let report_path = target.join("migration_report.md");
-        let _ = std::fs::write(&report_path, &report_md);
+        migrate_from_legacy_yaml(source, target, options, &mut report)?;
     }
 
+    validate_output(target, options, &mut report);
+    save_report(&report, target, options);
     Ok(report)
 }
 
-// ---------------------------------------------------------------------------
-// JSON5 migration flow (modern OpenClaw)
-// ---------------------------------------------------------------------------
+/// Migrate the contents of an `openclaw.json` into `target` without a workspace on disk.
+///
+/// Runs the config, channel and agent phases only; memory, workspaces and
+/// sessions are skipped. Identity file references resolve against
+/// `options.source_dir`, and `options.target_dir` is ignored in favour of `target`.
+pub fn migrate_config_str(
+    json5: &str,
+    target: &Path,
+    options: &MigrateOptions,
+) -> Result<MigrationReport, MigrateError> {
+    let run = || {
+        let mut report = MigrationReport {
+            source: "OpenClaw".to_string(),
+            dry_run: options.dry_run,
+            ..Default::default()
+        };
+        let root = parse_openclaw_json5(json5, Path::new("openclaw.json"))?;
+        migrate_config_and_agents(&root, json5, target, options, &mut report)?;
+        report_skipped_features(&root, None, &mut report);
+        check_provider_references(&root, &mut report);
+        validate_output(target, options, &mut report);
+        save_report(&report, target, options);
+        Ok(report)
+    };
+    if options.quiet {
+        let silent = tracing::subscriber::NoSubscriber::default();
+        return tracing::subscriber::with_default(silent, run);
+    }
+    run()
+}
+
+/// With `validate_after`, load the written config and agent manifests back the way
+/// OpenFang will and warn about any that fail.
+fn validate_output(target: &Path, options: &MigrateOptions, report: &mut MigrationReport) {
+    if !options.validate_after || options.dry_run {
+        return;
+    }
+    fn check<T: serde::de::DeserializeOwned>(path: &Path, report: &mut MigrationReport) {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let error = toml::from_str::<toml::Value>(&text)
+            .err()
+            .or_else(|| toml::from_str::<T>(&text).err());
+        if let Some(e) = error {
+            report.warnings.push(format!(
+                "Validation: {} could not be loaded: {}",
+                path.display(),
+                e.message()
+            ));
+        }
+    }
+
+    check::<openfang_types::config::KernelConfig>(
+        &target.join(&options.output_paths.config),
+        report,
+    );
+    let mut manifests: Vec<PathBuf> = std::fs::read_dir(target.join("agents"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path().join("agent.toml"))
+        .filter(|p| p.is_file())
+        .collect();
+    manifests.sort();
+    for manifest in manifests {
+        check::<openfang_types::agent::AgentManifest>(&manifest, report);
+    }
+}
+
+/// Write the markdown report into `target` unless this is a dry run.
+fn save_report(report: &MigrationReport, target: &Path, options: &MigrateOptions) {
+    if !options.dry_run {
+        let report_path = target.join(&options.output_paths.report);
+        let _ = std::fs::write(&report_path, report.to_markdown());
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON5 migration flow (modern OpenClaw)
+// ---------------------------------------------------------------------------
 
 fn migrate_from_json5(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let config_path = find_config_file(source).ok_or_else(|| {
         MigrateError::ConfigParse("No openclaw.json found in workspace".to_string())
     })?;
 
     let content = std::fs::read_to_string(&config_path)?;
-    let root: OpenClawRoot = json5::from_str(&content)
-        .map_err(|e| MigrateError::Json5Parse(format!("{}: {e}", config_path.display())))?;
-
-    // 1. Migrate config
-    migrate_config_from_json(&root, target, dry_run, report)?;
+    let root = parse_openclaw_json5(&content, &config_path)?;
 
-    // 2. Migrate agents
-    migrate_agents_from_json(&root, target, dry_run, report)?;
+    migrate_config_and_agents(&root, &content, target, options, report)?;
 
-    // 3. Migrate memory files
-    migrate_memory_files(source, &root, target, dry_run, report)?;
+    // 3. Migrate memory files and the search index
+    migrate_memory_files(source, &root, target, options, report)?;
+    migrate_memory_index(source, &root, target, options, report)?;
 
     // 4. Migrate workspace dirs
-    migrate_workspace_dirs(source, &root, target, dry_run, report)?;
+    migrate_workspace_dirs(source, &root, target, options, report)?;
 
     // 5. Migrate sessions
-    migrate_sessions(source, target, dry_run, report)?;
+    migrate_sessions(source, &root, target, options, report)?;
 
-    // 6. Report skipped features
-    report_skipped_features(&root, source, report);
+    // 6. Copy file-only skills; the rest are reported as skipped
+    migrate_file_skills(source, &root, target, dry_run, report);
+
+    // 7. Report skipped features
+    report_skipped_features(&root, Some(source), report);
+
+    // 8. Cross-check agent providers against models.providers
+    check_provider_references(&root, report);
 
     info!("JSON5 migration complete");
     Ok(())
 }
 
+/// Pre-flight checks, then phases 1 (config and channels) and 2 (agents) of a JSON5 migration.
+fn migrate_config_and_agents(
+    root: &OpenClawRoot,
+    content: &str,
+    target: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(), MigrateError> {
+    let agent_ids: Vec<String> = root
+        .agents
+        .as_ref()
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(e, _)| e.id)
+        .collect();
+    report.source_version = root.source_version();
+    if let Some(ref version) = report.source_version {
+        if version_is_newer(version, NEWEST_KNOWN_CONFIG_VERSION) {
+            report.warnings.push(format!(
+                "openclaw.json declares version {version}, but this migrator knows versions up to \
+                 {NEWEST_KNOWN_CONFIG_VERSION}; newer settings may have been missed"
+            ));
+        }
+    }
+    check_agent_count(root, content, options)?;
+    check_agent_renames(&agent_ids, options, report)?;
+    if let Some(ref wanted) = options.default_agent_target {
+        let agent_dirs = AgentDirs::from_root(root, options);
+        if main_agent_target(options, &agent_ids, &agent_dirs) != Some(wanted) {
+            report.warnings.push(format!(
+                "Default agent target '{wanted}' is not a migrated agent; main-session memory \
+                 and sessions go to {} instead",
+                agent_ids
+                    .first()
+                    .map_or("no agent".to_string(), |id| format!("'{id}'"))
+            ));
+        }
+    }
+    if options.strict {
+        check_strict(root, options)?;
+    }
+
+    // 1. Migrate config
+    migrate_config_from_json(root, target, options, report)?;
+
+    // 2. Migrate agents
+    migrate_agents_from_json(root, target, options, report)
+}
+
 // ---------------------------------------------------------------------------
 // Config migration from JSON5
 // ---------------------------------------------------------------------------
@@ -1140,11 +2911,12 @@ fn migrate_from_json5(
 fn migrate_config_from_json(
     root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     // Extract default model from agents.defaults.model
-    let (provider, model) = root
+    let default_ref = root
         .agents
         .as_ref()
         .and_then(|a| a.defaults.as_ref())
@@ -1153,34 +2925,72 @@ fn migrate_config_from_json(
             OpenClawAgentModel::Simple(s) => Some(s.clone()),
             OpenClawAgentModel::Detailed(d) => d.primary.clone(),
         })
-        .map(|m| split_model_ref(&m))
+        .map(|m| {
+            if let Some(warning) = bare_model_warning(&m) {
+                report.warnings.push(format!("Default model: {warning}"));
+            }
+            parse_model_ref(&m)
+        })
         .unwrap_or_else(|| {
-            (
-                "anthropic".to_string(),
-                "claude-sonnet-4-20250514".to_string(),
-            )
+            let (provider, model) = fallback_model(options);
+            ModelRef {
+                provider,
+                model,
+                deployment: None,
+            }
         });
+    let base_url = provider_base_url(Some(root), &default_ref.provider);
+    if !is_openfang_provider(&default_ref.provider, base_url.as_deref()) {
+        report.warnings.push(format!(
+            "Default model: unrecognized provider '{}': OpenFang will not load it until the \
+             provider is changed to a supported one, or a base_url is set for an \
+             OpenAI-compatible endpoint",
+            default_ref.provider
+        ));
+    }
+    for warning in cloud_model_warnings(&default_ref) {
+        report.warnings.push(format!("Default model: {warning}"));
+    }
+    let ModelRef {
+        provider, model, ..
+    } = default_ref;
+
+    let model = if options.preserve_model_ids {
+        model
+    } else {
+        let (normalized, note) = normalize_model_id(&provider, &model);
+        if let Some(note) = note {
+            report.warnings.push(format!("Default model: {note}"));
+        }
+        normalized
+    };
 
     let api_key_env = default_api_key_env(&provider);
+    if let Some(note) = provider_credential_note(&provider) {
+        report.warnings.push(format!("Default model: {note}"));
+    }
 
-    // Extract channels (writes secrets.env)
-    let channels = migrate_channels_from_json(root, target, dry_run, report);
+    // Extract channels and MCP servers (both write secrets.env)
+    let channels = migrate_channels_from_json(root, target, options, report);
+    let mcp_servers = migrate_mcp_servers_from_json(root, target, options, report);
 
     let of_config = OpenFangConfig {
         default_model: OpenFangModelConfig {
             provider,
             model,
             api_key_env,
-            base_url: None,
+            base_url,
         },
-        memory: OpenFangMemorySection { decay_rate: 0.05 },
+        memory: OpenFangMemorySection::from_json(root.memory.as_ref(), report),
         network: OpenFangNetworkSection {
             listen_addr: "127.0.0.1:4200".to_string(),
         },
         channels,
+        mcp_servers,
     };
 
     let toml_str = toml::to_string_pretty(&of_config)?;
+    record_env_refs(&toml_str, report);
 
     let config_content = format!(
         "# OpenFang Agent OS configuration\n\
@@ -1189,7 +2999,8 @@ fn migrate_config_from_json(
         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
     );
 
-    let dest = target.join("config.toml");
+    let dest = target.join(&options.output_paths.config);
+    note_conflict(dry_run, &dest, report);
 
     if !dry_run {
         std::fs::create_dir_all(target)?;
@@ -1200,56 +3011,230 @@ fn migrate_config_from_json(
         kind: ItemKind::Config,
         name: "openclaw.json".to_string(),
         destination: dest.display().to_string(),
+        details: None,
     });
 
-    info!("Migrated openclaw.json -> config.toml");
+    info!("Migrated openclaw.json -> {}", dest.display());
     Ok(())
 }
 
 // ---------------------------------------------------------------------------
-// Channel migration from JSON5
+// MCP server migration from JSON5
 // ---------------------------------------------------------------------------
 
-fn migrate_channels_from_json(
+/// Convert an OpenClaw MCP server's transport, or explain why it can't be migrated.
+fn convert_mcp_transport(server: &OpenClawMcpServer) -> Result<OpenFangMcpTransport, String> {
+    let transport = match server.transport.as_deref() {
+        Some(t) => t.to_lowercase(),
+        None if server.command.is_some() => "stdio".to_string(),
+        None if server.url.is_some() => "http".to_string(),
+        None => return Err("no command or url configured".to_string()),
+    };
+    match transport.as_str() {
+        "stdio" => match server.command {
+            Some(ref command) => Ok(OpenFangMcpTransport::Stdio {
+                command: command.clone(),
+                args: server.args.clone(),
+            }),
+            None => Err("stdio transport without a command".to_string()),
+        },
+        "http" | "sse" | "streamable-http" | "streamable_http" => match server.url {
+            Some(ref url) => Ok(OpenFangMcpTransport::Sse { url: url.clone() }),
+            None => Err(format!("{transport} transport without a url")),
+        },
+        other => Err(format!("unsupported MCP transport '{other}'")),
+    }
+}
+
+/// Whether an MCP env var name looks like it holds a credential.
+fn looks_like_secret(key: &str) -> bool {
+    let upper = key.to_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL", "PAT"]
+        .iter()
+        .any(|marker| upper.split('_').any(|part| part == *marker) || upper.ends_with(marker))
+}
+
+fn migrate_mcp_servers_from_json(
     root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
-) -> Option<toml::Value> {
-    let oc_channels = root.channels.as_ref()?;
+) -> Vec<OpenFangMcpServer> {
+    let Some(servers) = root.tools.as_ref().and_then(|t| t.mcp.as_ref()) else {
+        return Vec::new();
+    };
+    let dry_run = options.dry_run;
+    let secrets_path = target.join(&options.output_paths.secrets);
+
+    let mut migrated = Vec::new();
+    for (name, server) in servers {
+        let transport = match convert_mcp_transport(server) {
+            Ok(t) => t,
+            Err(reason) => {
+                report.skipped.push(SkippedItem {
+                    kind: ItemKind::McpServer,
+                    name: name.clone(),
+                    reason,
+                });
+                continue;
+            }
+        };
 
-    let mut channels_table = toml::map::Map::new();
-    let secrets_path = target.join("secrets.env");
-
-    /// Helper: write a secret and report it.
-    fn emit_secret(
-        path: &Path,
-        dry_run: bool,
-        key: &str,
-        value: &str,
-        report: &mut MigrationReport,
-    ) {
-        if value.is_empty() {
-            return;
-        }
-        if !dry_run {
-            if let Err(e) = write_secret_env(path, key, value) {
-                report
-                    .warnings
-                    .push(format!("Failed to write {key} to secrets.env: {e}"));
-                return;
+        // OpenFang passes env vars through by name, so values must live in the environment.
+        let mut env = Vec::new();
+        for (key, value) in &server.env {
+            // `${VAR}` references an existing variable; pass that one through instead.
+            if let Some(var) = value.strip_prefix("${").and_then(|v| v.strip_suffix('}')) {
+                env.push(var.to_string());
+            } else if looks_like_secret(key) {
+                emit_secret(&secrets_path, dry_run, key, value, report);
+                env.push(key.clone());
+            } else {
+                report.warnings.push(format!(
+                    "MCP server '{name}': set {key}={value} in the OpenFang environment"
+                ));
+                env.push(key.clone());
             }
         }
+
         report.imported.push(MigrateItem {
-            kind: ItemKind::Secret,
-            name: key.to_string(),
-            destination: "secrets.env".to_string(),
+            kind: ItemKind::McpServer,
+            name: name.clone(),
+            destination: "config.toml [[mcp_servers]]".to_string(),
+            details: None,
         });
+        migrated.push(OpenFangMcpServer {
+            name: name.clone(),
+            transport,
+            env,
+        });
+    }
+    migrated
+}
+
+/// Tool allowlists of the MCP servers that will be migrated, keyed by server name.
+fn mcp_tool_allowlists(root: &OpenClawRoot) -> std::collections::BTreeMap<String, Vec<String>> {
+    root.tools
+        .as_ref()
+        .and_then(|t| t.mcp.as_ref())
+        .into_iter()
+        .flatten()
+        .filter(|(_, server)| convert_mcp_transport(server).is_ok())
+        .map(|(name, server)| (name.clone(), server.allow.clone()))
+        .collect()
+}
+
+/// Namespaced OpenFang name for an MCP tool (matches the runtime's `mcp_{server}_{tool}`).
+fn mcp_tool_name(server: &str, tool: &str) -> String {
+    let norm = |s: &str| s.to_lowercase().replace('-', "_");
+    format!("mcp_{}_{}", norm(server), norm(tool))
+}
+
+// ---------------------------------------------------------------------------
+// Channel migration from JSON5
+// ---------------------------------------------------------------------------
+
+/// The `default_agent` for Discord, from the agents its `guilds` map routes guilds
+/// (`{ "123": { agent: "coder" } }`) or their channels (`channels: { "456": {...} }`)
+/// to. OpenFang routes a whole channel adapter to one agent, so when routes name
+/// different agents the first is used and the rest are reported. Routes to agents
+/// that are not migrated (unknown or filtered out) are ignored with a warning.
+fn discord_default_agent(
+    guilds: Option<&serde_json::Value>,
+    root: &OpenClawRoot,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Option<String> {
+    let route_agent = |cfg: &serde_json::Value| {
+        cfg.get("agent")
+            .or_else(|| cfg.get("agentId"))
+            .and_then(|a| a.as_str())
+            .map(str::to_string)
+    };
+    let mut routes: Vec<(String, String)> = Vec::new();
+    for (guild, cfg) in guilds?.as_object()? {
+        if let Some(agent) = route_agent(cfg) {
+            routes.push((format!("guild {guild}"), agent));
+        }
+        let channels = cfg.get("channels").and_then(|c| c.as_object());
+        for (channel, ch) in channels.into_iter().flatten() {
+            if let Some(agent) = route_agent(ch) {
+                routes.push((format!("guild {guild} channel {channel}"), agent));
+            }
+        }
     }
 
+    let agent_dirs = AgentDirs::from_root(root, options);
+    let agent_ids: Vec<String> = root
+        .agents
+        .as_ref()
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(e, _)| e.id)
+        .filter(|id| !agent_dirs.is_excluded(id))
+        .collect();
+    let (routes, dangling): (Vec<_>, Vec<_>) = routes
+        .into_iter()
+        .partition(|(_, agent)| agent_ids.contains(agent));
+    if !dangling.is_empty() {
+        report.warnings.push(format!(
+            "Discord routes {} name agents that are not migrated; ignored",
+            dangling
+                .iter()
+                .map(|(target, agent)| format!("{target} -> {agent}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    let (_, first) = routes.first()?;
+    let agent_name = |id: &str| {
+        root.agents
+            .as_ref()
+            .and_then(|a| a.list.iter().find(|e| e.id == id))
+            .and_then(|e| e.name.clone())
+            .unwrap_or_else(|| renamed_id(id, &options.agent_renames).to_string())
+    };
+    let others: Vec<String> = routes
+        .iter()
+        .filter(|(_, agent)| agent != first)
+        .map(|(target, agent)| format!("{target} -> {agent}"))
+        .collect();
+    if !others.is_empty() {
+        report.warnings.push(format!(
+            "Discord routes {} to other agents; OpenFang sends all Discord messages to \
+             default_agent '{}'",
+            others.join(", "),
+            agent_name(first)
+        ));
+    }
+    Some(agent_name(first))
+}
+
+fn migrate_channels_from_json(
+    root: &OpenClawRoot,
+    target: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Option<toml::Value> {
+    let oc_channels = root.channels.as_ref()?;
+
+    let dry_run = options.dry_run;
+    let mut channels_table = toml::map::Map::new();
+    let secrets_path = target.join(&options.output_paths.secrets);
+    report
+        .warnings
+        .extend(unknown_group_policy_warnings(oc_channels));
+
     // --- Telegram ---
     if let Some(ref tg) = oc_channels.telegram {
-        if tg.enabled.unwrap_or(true) {
+        let allowed_ids = tg
+            .enabled
+            .unwrap_or(true)
+            .then(|| telegram_allowed_ids(tg.allow_from.as_deref(), report))
+            .flatten();
+        if let Some(allowed_ids) = allowed_ids {
             if let Some(ref token) = tg.bot_token {
                 emit_secret(&secrets_path, dry_run, "TELEGRAM_BOT_TOKEN", token, report);
             }
@@ -1257,28 +3242,31 @@ fn migrate_channels_from_json(
                 "bot_token_env",
                 toml::Value::String("TELEGRAM_BOT_TOKEN".into()),
             )];
-            if let Some(ref users) = tg.allow_from {
-                if !users.is_empty() {
-                    let arr: Vec<toml::Value> = users
-                        .iter()
-                        .map(|u| toml::Value::String(u.clone()))
-                        .collect();
-                    fields.push(("allowed_users", toml::Value::Array(arr)));
-                }
+            if !allowed_ids.is_empty() {
+                let arr = allowed_ids.into_iter().map(toml::Value::Integer).collect();
+                fields.push(("allowed_users", toml::Value::Array(arr)));
             }
-            channels_table.insert(
-                "telegram".to_string(),
-                build_channel_table(
-                    fields,
-                    tg.dm_policy.as_deref(),
-                    tg.group_policy.as_deref(),
-                    tg.allow_from.as_deref(),
-                ),
+            block_list_skipped("telegram", tg.block_from.as_deref(), report);
+            let table = build_channel_table(
+                fields,
+                tg.dm_policy.as_deref(),
+                tg.group_policy.as_deref(),
+                tg.allow_from.as_deref(),
             );
+            if let Some(chats) = tg.allow_chats.as_ref().filter(|c| !c.is_empty()) {
+                report.warnings.push(format!(
+                    "Telegram allowChats ({}) was not migrated: OpenFang's Telegram adapter \
+                     cannot restrict which group chats it answers in; use group_policy to \
+                     limit group replies",
+                    chats.join(", ")
+                ));
+            }
+            channels_table.insert("telegram".to_string(), table);
             report.imported.push(MigrateItem {
                 kind: ItemKind::Channel,
                 name: "telegram".to_string(),
                 destination: "config.toml [channels.telegram]".to_string(),
+                details: None,
             });
         }
     }
@@ -1289,10 +3277,14 @@ fn migrate_channels_from_json(
             if let Some(ref token) = dc.token {
                 emit_secret(&secrets_path, dry_run, "DISCORD_BOT_TOKEN", token, report);
             }
-            let fields: Vec<(&str, toml::Value)> = vec![(
+            let mut fields: Vec<(&str, toml::Value)> = vec![(
                 "bot_token_env",
                 toml::Value::String("DISCORD_BOT_TOKEN".into()),
             )];
+            if let Some(agent) = discord_default_agent(dc.guilds.as_ref(), root, options, report) {
+                fields.push(("default_agent", toml::Value::String(agent)));
+            }
+            block_list_skipped("discord", dc.block_from.as_deref(), report);
             channels_table.insert(
                 "discord".to_string(),
                 build_channel_table(
@@ -1306,6 +3298,7 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "discord".to_string(),
                 destination: "config.toml [channels.discord]".to_string(),
+                details: None,
             });
         }
     }
@@ -1329,6 +3322,7 @@ fn migrate_channels_from_json(
                     toml::Value::String("SLACK_APP_TOKEN".into()),
                 ),
             ];
+            block_list_skipped("slack", sl.block_from.as_deref(), report);
             channels_table.insert(
                 "slack".to_string(),
                 build_channel_table(
@@ -1342,6 +3336,7 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "slack".to_string(),
                 destination: "config.toml [channels.slack]".to_string(),
+                details: None,
             });
         }
     }
@@ -1351,31 +3346,49 @@ fn migrate_channels_from_json(
         if wa.enabled.unwrap_or(true) {
             // WhatsApp uses Baileys credential dir — copy it, warn user
             if let Some(ref auth_dir) = wa.auth_dir {
-                let src_path = PathBuf::from(auth_dir);
+                // Relative dirs (`wa-auth`) usually live in the OpenClaw home
+                let mut src_path = PathBuf::from(auth_dir);
+                if !src_path.exists() && src_path.is_relative() {
+                    if let Some(resolved) = expand_config_path(auth_dir, &options.source_dir) {
+                        src_path = resolved;
+                    }
+                }
                 if src_path.exists() {
                     let dest_creds = target.join("credentials").join("whatsapp");
-                    if !dry_run {
-                        if let Err(e) = copy_dir_recursive(&src_path, &dest_creds) {
-                            report
-                                .warnings
-                                .push(format!("Failed to copy WhatsApp credentials: {e}"));
+                    let copied = if dry_run {
+                        Ok(count_files(&src_path))
+                    } else {
+                        copy_credentials_dir(&src_path, &dest_creds).map(|(bytes, files)| {
+                            report.bytes_copied += bytes;
+                            files
+                        })
+                    };
+                    match copied {
+                        Ok(files) => {
+                            report.imported.push(MigrateItem {
+                                kind: ItemKind::Secret,
+                                name: format!("whatsapp/credentials ({files} files)"),
+                                destination: dest_creds.display().to_string(),
+                                details: None,
+                            });
+                            report.warnings.push(
+                                "WhatsApp Baileys credentials copied — you may need to re-authenticate"
+                                    .to_string(),
+                            );
                         }
+                        Err(e) => report
+                            .warnings
+                            .push(format!("Failed to copy WhatsApp credentials: {e}")),
                     }
-                    report.imported.push(MigrateItem {
-                        kind: ItemKind::Secret,
-                        name: "whatsapp/credentials".to_string(),
-                        destination: dest_creds.display().to_string(),
-                    });
-                    report.warnings.push(
-                        "WhatsApp Baileys credentials copied — you may need to re-authenticate"
-                            .to_string(),
-                    );
                 }
             }
             let mut fields: Vec<(&str, toml::Value)> = vec![(
                 "access_token_env",
                 toml::Value::String("WHATSAPP_ACCESS_TOKEN".into()),
             )];
+            if let Some(ref phone) = wa.phone_number {
+                report.warnings.push(whatsapp_phone_warning(phone));
+            }
             if let Some(ref users) = wa.allow_from {
                 if !users.is_empty() {
                     let arr: Vec<toml::Value> = users
@@ -1385,6 +3398,7 @@ fn migrate_channels_from_json(
                     fields.push(("allowed_users", toml::Value::Array(arr)));
                 }
             }
+            block_list_skipped("whatsapp", wa.block_from.as_deref(), report);
             channels_table.insert(
                 "whatsapp".to_string(),
                 build_channel_table(
@@ -1398,13 +3412,16 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "whatsapp".to_string(),
                 destination: "config.toml [channels.whatsapp]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- Signal ---
     if let Some(ref sig) = oc_channels.signal {
-        if sig.enabled.unwrap_or(true) {
+        if sig.enabled.unwrap_or(true)
+            && !skip_incomplete_channel("signal", &[("account", filled(&sig.account))], report)
+        {
             // Construct API URL from host+port or use http_url directly
             let api_url = sig.http_url.clone().unwrap_or_else(|| {
                 let host = sig.http_host.as_deref().unwrap_or("localhost");
@@ -1416,6 +3433,7 @@ fn migrate_channels_from_json(
             if let Some(ref account) = sig.account {
                 fields.push(("phone_number", toml::Value::String(account.clone())));
             }
+            block_list_skipped("signal", sig.block_from.as_deref(), report);
             channels_table.insert(
                 "signal".to_string(),
                 build_channel_table(
@@ -1429,13 +3447,16 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "signal".to_string(),
                 destination: "config.toml [channels.signal]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- Matrix ---
     if let Some(ref mx) = oc_channels.matrix {
-        if mx.enabled.unwrap_or(true) {
+        if mx.enabled.unwrap_or(true)
+            && !skip_incomplete_channel("matrix", &[("homeserver", filled(&mx.homeserver))], report)
+        {
             if let Some(ref token) = mx.access_token {
                 emit_secret(&secrets_path, dry_run, "MATRIX_ACCESS_TOKEN", token, report);
             }
@@ -1450,14 +3471,23 @@ fn migrate_channels_from_json(
                 fields.push(("user_id", toml::Value::String(uid.clone())));
             }
             if let Some(ref rooms) = mx.rooms {
+                let rooms: Vec<&OpenClawMatrixRoom> =
+                    rooms.iter().filter(|r| !r.id().is_empty()).collect();
                 if !rooms.is_empty() {
                     let arr: Vec<toml::Value> = rooms
                         .iter()
-                        .map(|r| toml::Value::String(r.clone()))
+                        .map(|r| toml::Value::String(r.id().to_string()))
                         .collect();
-                    fields.push(("rooms", toml::Value::Array(arr)));
+                    fields.push(("allowed_rooms", toml::Value::Array(arr)));
+                }
+                // OpenFang has no per-room settings; the channel-wide policy applies
+                for room in &rooms {
+                    if let OpenClawMatrixRoom::Detailed(d) = room {
+                        matrix_room_policies_skipped(d, report);
+                    }
                 }
             }
+            block_list_skipped("matrix", mx.block_from.as_deref(), report);
             channels_table.insert(
                 "matrix".to_string(),
                 build_channel_table(
@@ -1471,6 +3501,7 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "matrix".to_string(),
                 destination: "config.toml [channels.matrix]".to_string(),
+                details: None,
             });
         }
     }
@@ -1487,16 +3518,18 @@ fn migrate_channels_from_json(
                         if let Some(parent) = dest_sa.parent() {
                             let _ = std::fs::create_dir_all(parent);
                         }
-                        if let Err(e) = std::fs::copy(&src_sa, &dest_sa) {
-                            report
+                        match std::fs::copy(&src_sa, &dest_sa) {
+                            Ok(bytes) => report.bytes_copied += bytes,
+                            Err(e) => report
                                 .warnings
-                                .push(format!("Failed to copy Google Chat SA file: {e}"));
+                                .push(format!("Failed to copy Google Chat SA file: {e}")),
                         }
                     }
                     report.imported.push(MigrateItem {
                         kind: ItemKind::Secret,
                         name: "google_chat/service_account".to_string(),
                         destination: dest_sa.display().to_string(),
+                        details: None,
                     });
                 }
             }
@@ -1504,6 +3537,17 @@ fn migrate_channels_from_json(
                 "service_account_env",
                 toml::Value::String("GOOGLE_CHAT_SA_FILE".into()),
             )];
+            for (key, value) in [("webhookPath", &gc.webhook_path), ("botUser", &gc.bot_user)] {
+                if let Some(value) = value {
+                    report.skipped.push(SkippedItem {
+                        kind: ItemKind::Channel,
+                        name: format!("google_chat/{key}"),
+                        reason: format!(
+                            "'{value}' not migrated: OpenFang's Google Chat adapter has no such setting"
+                        ),
+                    });
+                }
+            }
             channels_table.insert(
                 "google_chat".to_string(),
                 build_channel_table(fields, gc.dm_policy.as_deref(), None, None),
@@ -1512,13 +3556,16 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "google_chat".to_string(),
                 destination: "config.toml [channels.google_chat]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- Teams ---
     if let Some(ref tm) = oc_channels.teams {
-        if tm.enabled.unwrap_or(true) {
+        if tm.enabled.unwrap_or(true)
+            && !skip_incomplete_channel("teams", &[("appId", filled(&tm.app_id))], report)
+        {
             if let Some(ref pw) = tm.app_password {
                 emit_secret(&secrets_path, dry_run, "TEAMS_APP_PASSWORD", pw, report);
             }
@@ -1532,6 +3579,7 @@ fn migrate_channels_from_json(
             if let Some(ref tenant) = tm.tenant_id {
                 fields.push(("tenant_id", toml::Value::String(tenant.clone())));
             }
+            block_list_skipped("teams", tm.block_from.as_deref(), report);
             channels_table.insert(
                 "teams".to_string(),
                 build_channel_table(
@@ -1545,13 +3593,20 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "teams".to_string(),
                 destination: "config.toml [channels.teams]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- IRC ---
     if let Some(ref irc) = oc_channels.irc {
-        if irc.enabled.unwrap_or(true) {
+        if irc.enabled.unwrap_or(true)
+            && !skip_incomplete_channel(
+                "irc",
+                &[("host", filled(&irc.host)), ("nick", filled(&irc.nick))],
+                report,
+            )
+        {
             if let Some(ref pw) = irc.password {
                 emit_secret(&secrets_path, dry_run, "IRC_PASSWORD", pw, report);
             }
@@ -1571,6 +3626,18 @@ fn migrate_channels_from_json(
             if irc.password.is_some() {
                 fields.push(("password_env", toml::Value::String("IRC_PASSWORD".into())));
             }
+            // The adapter only sends the password as a server PASS; it has no
+            // SASL or NickServ login, so the mechanism can't be carried over.
+            match irc_auth_mode(irc) {
+                Ok(Some(mode)) => report.warnings.push(format!(
+                    "IRC {mode} authentication is not supported by OpenFang's IRC adapter \
+                     (the password is only sent as a server PASS); not migrated"
+                )),
+                Ok(None) => {}
+                Err(other) => report.warnings.push(format!(
+                    "IRC auth mode '{other}' is not recognized (expected sasl or nickserv); not migrated"
+                )),
+            }
             if let Some(ref chans) = irc.channels {
                 if !chans.is_empty() {
                     let arr: Vec<toml::Value> = chans
@@ -1580,6 +3647,7 @@ fn migrate_channels_from_json(
                     fields.push(("channels", toml::Value::Array(arr)));
                 }
             }
+            block_list_skipped("irc", irc.block_from.as_deref(), report);
             channels_table.insert(
                 "irc".to_string(),
                 build_channel_table(
@@ -1593,13 +3661,16 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "irc".to_string(),
                 destination: "config.toml [channels.irc]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- Mattermost ---
     if let Some(ref mm) = oc_channels.mattermost {
-        if mm.enabled.unwrap_or(true) {
+        if mm.enabled.unwrap_or(true)
+            && !skip_incomplete_channel("mattermost", &[("baseUrl", filled(&mm.base_url))], report)
+        {
             if let Some(ref token) = mm.bot_token {
                 emit_secret(&secrets_path, dry_run, "MATTERMOST_TOKEN", token, report);
             }
@@ -1610,6 +3681,7 @@ fn migrate_channels_from_json(
             if let Some(ref url) = mm.base_url {
                 fields.push(("server_url", toml::Value::String(url.clone())));
             }
+            block_list_skipped("mattermost", mm.block_from.as_deref(), report);
             channels_table.insert(
                 "mattermost".to_string(),
                 build_channel_table(
@@ -1623,13 +3695,16 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "mattermost".to_string(),
                 destination: "config.toml [channels.mattermost]".to_string(),
+                details: None,
             });
         }
     }
 
     // --- Feishu ---
     if let Some(ref fs) = oc_channels.feishu {
-        if fs.enabled.unwrap_or(true) {
+        if fs.enabled.unwrap_or(true)
+            && !skip_incomplete_channel("feishu", &[("appId", filled(&fs.app_id))], report)
+        {
             if let Some(ref secret) = fs.app_secret {
                 emit_secret(&secrets_path, dry_run, "FEISHU_APP_SECRET", secret, report);
             }
@@ -1651,28 +3726,30 @@ fn migrate_channels_from_json(
                 kind: ItemKind::Channel,
                 name: "feishu".to_string(),
                 destination: "config.toml [channels.feishu]".to_string(),
+                details: None,
             });
         }
     }
 
-    // --- iMessage (skip — macOS-only, manual setup) ---
-    if oc_channels.imessage.is_some() {
-        report.skipped.push(SkippedItem {
-            kind: ItemKind::Channel,
-            name: "imessage".to_string(),
-            reason: "macOS-only channel — requires manual setup on the target Mac".to_string(),
-        });
-    }
-
-    // --- BlueBubbles (skip — no OpenFang adapter) ---
-    if oc_channels.bluebubbles.is_some() {
+    // --- Typed channels without an OpenFang adapter (iMessage, BlueBubbles) ---
+    for name in oc_channels.configured() {
+        if channel_is_supported(name) {
+            continue;
+        }
+        let reason = match name {
+            "imessage" => "macOS-only channel — requires manual setup on the target Mac",
+            "bluebubbles" => {
+                "No OpenFang adapter available — consider using the iMessage channel instead"
+            }
+            _ => "No OpenFang adapter available",
+        };
         report.skipped.push(SkippedItem {
             kind: ItemKind::Channel,
-            name: "bluebubbles".to_string(),
-            reason: "No OpenFang adapter available — consider using the iMessage channel instead"
-                .to_string(),
+            name: name.to_string(),
+            reason: reason.to_string(),
         });
     }
+    debug_assert!(channels_table.keys().all(|k| channel_is_supported(k)));
 
     // --- Unknown channels from the catch-all ---
     for key in oc_channels.other.keys() {
@@ -1697,9 +3774,10 @@ fn migrate_channels_from_json(
 fn migrate_agents_from_json(
     root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let agents = match root.agents.as_ref() {
         Some(a) => a,
         None => {
@@ -1711,34 +3789,76 @@ fn migrate_agents_from_json(
     };
 
     let defaults = agents.defaults.as_ref();
-
-    for entry in &agents.list {
+    let mut tool_map = ToolMap::load(options, &mut report.warnings)?;
+    tool_map.mcp = mcp_tool_allowlists(root);
+    let mut agent_dirs = AgentDirs::from_root(root, options);
+    agent_dirs.report_collisions(report);
+    let mut seen_ids = std::collections::HashSet::new();
+
+    for (entry, parent) in expand_subagents(&agents.list, None) {
+        let entry = &entry;
         let id = &entry.id;
-        if id.is_empty() {
+        if id.is_empty() || agent_dirs.skip_excluded(id, report) {
             continue;
         }
+        let agent_dir = if seen_ids.insert(id.clone()) {
+            agent_dirs.dir_for(id)
+        } else {
+            agent_dirs.dir_for_duplicate(id, report)
+        };
+        let Some(agent_dir) = agent_dir else {
+            continue;
+        };
 
-        match convert_agent_from_json(entry, defaults) {
-            Ok((toml_str, unmapped_tools)) => {
-                let dest_dir = target.join("agents").join(id);
+        match convert_agent_from_json(entry, defaults, Some(root), options, &tool_map) {
+            Ok(mut converted) => {
+                if reject_invalid_manifest(id, &agent_dir, &converted.toml, target, dry_run, report)
+                {
+                    continue;
+                }
+                let dest_dir = target.join("agents").join(&agent_dir);
                 let dest_file = dest_dir.join("agent.toml");
-
-                if !dry_run {
-                    std::fs::create_dir_all(&dest_dir)?;
-                    std::fs::write(&dest_file, &toml_str)?;
+                let changed = write_manifest(&dest_file, &converted.toml, dry_run, report)?;
+                record_env_refs(&converted.toml, report);
+                converted.notes.data_sources = agent_data_sources(&options.source_dir, id);
+                write_agent_notes(&converted.notes, &dest_dir, options, report);
+                if let Some((ref env, ref key)) = converted.secret {
+                    emit_secret(
+                        &target.join(&options.output_paths.secrets),
+                        dry_run,
+                        env,
+                        key,
+                        report,
+                    );
                 }
 
-                report.imported.push(MigrateItem {
+                let item = MigrateItem {
                     kind: ItemKind::Agent,
-                    name: id.clone(),
+                    name: match parent {
+                        Some(ref p) => format!("{id} (subagent of {p})"),
+                        None => id.clone(),
+                    },
                     destination: dest_file.display().to_string(),
-                });
+                    details: converted.memory_scope.clone().map(|scope| ItemDetails {
+                        memory_scope: Some(scope),
+                        ..Default::default()
+                    }),
+                };
+                if changed {
+                    report.imported.push(item);
+                } else {
+                    report.unchanged.push(item);
+                }
 
-                for tool in &unmapped_tools {
-                    report.warnings.push(format!(
-                        "Agent '{id}': tool '{tool}' has no OpenFang equivalent and was skipped"
-                    ));
+                for tool in &converted.unmapped_tools {
+                    report
+                        .warnings
+                        .push(format!("Agent '{id}': {}", unmapped_tool_warning(tool)));
+                }
+                for w in &converted.warnings {
+                    report.warnings.push(format!("Agent '{id}': {w}"));
                 }
+                report.skipped.append(&mut converted.skipped);
 
                 info!("Migrated agent: {id}");
             }
@@ -1756,177 +3876,632 @@ fn migrate_agents_from_json(
     Ok(())
 }
 
+/// Result of converting a single agent into an OpenFang manifest.
+struct ConvertedAgent {
+    /// Generated agent.toml content.
+    toml: String,
+    /// Tools with no OpenFang equivalent.
+    unmapped_tools: Vec<String>,
+    /// Agent-specific notes, surfaced as report warnings.
+    warnings: Vec<String>,
+    /// Agent settings OpenFang has no field for.
+    skipped: Vec<SkippedItem>,
+    /// `(env var, value)` of a literal API key to move into secrets.env.
+    secret: Option<(String, String)>,
+    /// Memory scope taken from the source config, if it set one.
+    memory_scope: Option<String>,
+    /// How the agent was converted, written out as `MIGRATION_NOTES.md`.
+    notes: AgentNotes,
+}
+
+/// Per-agent record of the conversion decisions, rendered as `MIGRATION_NOTES.md`.
+#[derive(Default)]
+struct AgentNotes {
+    source_id: String,
+    primary_model: String,
+    primary_origin: &'static str,
+    fallback_models: Vec<String>,
+    fallback_origin: &'static str,
+    /// Model id remappings applied on the way in.
+    model_notes: Vec<String>,
+    tools: Vec<String>,
+    unmapped_tools: Vec<String>,
+    capabilities: Vec<String>,
+    /// Source paths the agent's memory and workspace were taken from.
+    data_sources: Vec<String>,
+}
+
+impl AgentNotes {
+    fn to_markdown(&self) -> String {
+        let list = |items: &[String], empty: &str| {
+            if items.is_empty() {
+                format!("- {empty}\n")
+            } else {
+                items.iter().map(|i| format!("- {i}\n")).collect()
+            }
+        };
+        let mut md = format!(
+            "# Migration notes\n\nMigrated from OpenClaw agent `{}`.\n\n## Models\n\n",
+            self.source_id
+        );
+        md.push_str(&format!(
+            "- Primary: `{}` ({})\n",
+            self.primary_model, self.primary_origin
+        ));
+        if self.fallback_models.is_empty() {
+            md.push_str("- Fallbacks: none\n");
+        } else {
+            md.push_str(&format!(
+                "- Fallbacks: {} ({})\n",
+                self.fallback_models
+                    .iter()
+                    .map(|m| format!("`{m}`"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                self.fallback_origin
+            ));
+        }
+        for note in &self.model_notes {
+            md.push_str(&format!("- {note}\n"));
+        }
+        md.push_str("\n## Tools\n\n");
+        md.push_str(&list(&self.tools, "none"));
+        if !self.unmapped_tools.is_empty() {
+            md.push_str("\nDropped (no OpenFang equivalent):\n\n");
+            md.push_str(&list(&self.unmapped_tools, "none"));
+        }
+        md.push_str("\n## Capabilities\n\n");
+        md.push_str(&list(&self.capabilities, "none"));
+        md.push_str("\n## Memory and workspace\n\n");
+        md.push_str(&list(
+            &self.data_sources,
+            "nothing found in the source workspace",
+        ));
+        md
+    }
+}
+
+/// Memory and workspace locations in the source tree that belong to `id`.
+fn agent_data_sources(source: &Path, id: &str) -> Vec<String> {
+    [
+        format!("memory/{id}/MEMORY.md"),
+        format!("agents/{id}/MEMORY.md"),
+        format!("workspaces/{id}/"),
+        format!("agents/{id}/workspace/"),
+    ]
+    .into_iter()
+    .filter(|rel| source.join(rel).exists())
+    .collect()
+}
+
+/// Write an agent's `MIGRATION_NOTES.md` next to its manifest, if enabled.
+fn write_agent_notes(
+    notes: &AgentNotes,
+    dest_dir: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) {
+    if options.dry_run || !options.write_agent_notes {
+        return;
+    }
+    let path = dest_dir.join("MIGRATION_NOTES.md");
+    let content = notes.to_markdown();
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == content) {
+        return;
+    }
+    if let Err(e) = std::fs::write(&path, content) {
+        report.warnings.push(format!(
+            "Failed to write migration notes for '{}': {e}",
+            notes.source_id
+        ));
+    }
+}
+
+/// Flatten agents and their subagents into `(entry, parent_id)` pairs.
+///
+/// Subagents become `<parent>__<child>` agents and inherit the parent's model
+/// when they don't specify one.
+fn expand_subagents(
+    list: &[OpenClawAgentEntry],
+    parent: Option<&OpenClawAgentEntry>,
+) -> Vec<(OpenClawAgentEntry, Option<String>)> {
+    let mut out = Vec::new();
+    for entry in list {
+        let mut entry = entry.clone();
+        if let Some(p) = parent {
+            entry.name = Some(subagent_name(p, &entry));
+            entry.id = format!("{}__{}", p.id, entry.id);
+            if entry.model.is_none() {
+                entry.model = p.model.clone();
+            }
+        }
+        let children = expand_subagents(&entry.subagents, Some(&entry));
+        out.push((entry, parent.map(|p| p.id.clone())));
+        out.extend(children);
+    }
+    out
+}
+
+/// Agent name a subagent is migrated under (what the parent messages).
+fn subagent_name(parent: &OpenClawAgentEntry, child: &OpenClawAgentEntry) -> String {
+    child
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("{}__{}", parent.id, child.id))
+}
+
 fn convert_agent_from_json(
     entry: &OpenClawAgentEntry,
     defaults: Option<&OpenClawAgentDefaults>,
-) -> Result<(String, Vec<String>), MigrateError> {
+    root: Option<&OpenClawRoot>,
+    options: &MigrateOptions,
+    tool_map: &ToolMap,
+) -> Result<ConvertedAgent, MigrateError> {
+    let root_tools = root.and_then(|r| r.tools.as_ref());
     let id = &entry.id;
-    let display_name = entry.name.clone().unwrap_or_else(|| id.clone());
+    let display_name = entry
+        .name
+        .clone()
+        .unwrap_or_else(|| renamed_id(id, &options.agent_renames).to_string());
+    let mut warnings = Vec::new();
 
     // Resolve model
-    let primary_ref = extract_primary_model(entry, defaults)
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".to_string());
-    let (provider, model) = split_model_ref(&primary_ref);
+    let primary_ref = extract_primary_model(entry, defaults).unwrap_or_else(|| {
+        let (provider, model) = fallback_model(options);
+        format!("{provider}/{model}")
+    });
+    let primary = parse_model_ref(&primary_ref);
+    let base_url = provider_base_url(root, &primary.provider);
+    warnings.extend(cloud_model_warnings(&primary));
+    let ModelRef {
+        provider, model, ..
+    } = primary;
+    warnings.extend(bare_model_warning(&primary_ref));
+    let model = resolve_model_id(&provider, &model, options, &mut warnings);
+    // Remapping notes so far all concern the primary model
+    let model_notes = warnings.clone();
+    let primary_origin = if extract_primary_model(entry, None).is_some() {
+        "set on the agent"
+    } else if defaults.and_then(|d| d.model.as_ref()).is_some() {
+        "inherited from agents.defaults"
+    } else {
+        "nothing configured; migration default"
+    };
 
     // Resolve fallback models
-    let fallbacks = extract_fallback_models(entry, defaults);
+    let auth_order = root
+        .and_then(|r| r.auth.as_ref())
+        .and_then(|a| a.order.as_ref());
+    let fallbacks = extract_fallback_models(entry, defaults, auth_order, &primary_ref);
+    let has_fallbacks = |model: Option<&OpenClawAgentModel>| matches!(model, Some(OpenClawAgentModel::Detailed(d)) if !d.fallbacks.is_empty());
+    let fallback_origin = if has_fallbacks(entry.model.as_ref()) {
+        "set on the agent"
+    } else if has_fallbacks(defaults.and_then(|d| d.model.as_ref())) {
+        "inherited from agents.defaults"
+    } else {
+        "taken from auth.order"
+    };
 
     // Resolve tools
     let mut unmapped_tools = Vec::new();
-    let tools: Vec<String> = if let Some(ref agent_tools) = entry.tools {
+    let mut tools: Vec<String> = if let Some(ref agent_tools) = entry.tools {
         if let Some(ref allow) = agent_tools.allow {
             let mut mapped = Vec::new();
-            for t in allow {
-                if is_known_openfang_tool(t) {
-                    mapped.push(t.clone());
-                } else if let Some(of_name) = map_tool_name(t) {
-                    mapped.push(of_name.to_string());
-                } else {
-                    unmapped_tools.push(t.clone());
-                }
-            }
+            map_tool_list(
+                allow,
+                tool_map,
+                &mut mapped,
+                &mut unmapped_tools,
+                &mut warnings,
+            );
             // also_allow
             if let Some(ref also) = agent_tools.also_allow {
-                for t in also {
-                    if is_known_openfang_tool(t) {
-                        mapped.push(t.clone());
-                    } else if let Some(of_name) = map_tool_name(t) {
-                        mapped.push(of_name.to_string());
-                    } else {
-                        unmapped_tools.push(t.clone());
-                    }
-                }
+                map_tool_list(
+                    also,
+                    tool_map,
+                    &mut mapped,
+                    &mut unmapped_tools,
+                    &mut warnings,
+                );
             }
             mapped
         } else if let Some(ref profile) = agent_tools.profile {
+            warnings.extend(unknown_profile_warning(profile));
             tools_for_profile(profile)
         } else {
-            resolve_default_tools(defaults)
+            resolve_default_tools(defaults, root_tools, tool_map, &mut warnings)
         }
     } else {
-        resolve_default_tools(defaults)
+        resolve_default_tools(defaults, root_tools, tool_map, &mut warnings)
     };
 
+    // MCP servers referenced from the tool lists
+    let mut mcp_servers: Vec<&str> = entry
+        .tools
+        .iter()
+        .flat_map(|t| t.allow.iter().chain(t.also_allow.iter()).flatten())
+        .filter_map(|t| tool_map.mcp_server_ref(t))
+        .collect();
+    mcp_servers.sort_unstable();
+    mcp_servers.dedup();
+
     // Derive capabilities
-    let caps = derive_capabilities(&tools);
+    let mut caps = derive_capabilities(&tools);
 
-    let api_key_env = {
-        let env = default_api_key_env(&provider);
-        if env.is_empty() {
-            None
-        } else {
-            Some(env)
+    // Parents spawn and message their subagents
+    if !entry.subagents.is_empty() {
+        for t in ["agent_spawn", "agent_send"] {
+            if !tools.iter().any(|x| x == t || x == "*") {
+                tools.push(t.to_string());
+            }
         }
-    };
+        caps.agent_spawn = true;
+        if caps.agent_message != ["*"] {
+            for child in &entry.subagents {
+                let name = subagent_name(entry, child);
+                if !caps.agent_message.contains(&name) {
+                    caps.agent_message.push(name);
+                }
+            }
+        }
+    }
+    let tool_options = entry
+        .tools
+        .as_ref()
+        .or_else(|| defaults.and_then(|d| d.tools.as_ref()));
+    scope_capabilities(&mut caps, &tools, tool_options, &mut warnings);
+    let memory_scope = apply_memory_scope(
+        &mut caps,
+        &OpenClawMemoryScope::from_value(entry.memory.as_ref()),
+        &OpenClawMemoryScope::from_value(root.and_then(|r| r.memory.as_ref())),
+    );
 
-    // System prompt from identity
-    let system_prompt = entry
-        .identity
+    // An explicit env var wins over the provider default; a literal key is moved
+    // into secrets.env under that name, or under an agent-specific one.
+    let explicit_env = entry
+        .api_key_env
         .clone()
-        .or_else(|| defaults.and_then(|d| d.identity.clone()))
-        .unwrap_or_else(|| {
-            format!(
-                "You are {display_name}, an AI agent running on the OpenFang Agent OS. You are helpful, concise, and accurate."
-            )
-        });
+        .or_else(|| defaults.and_then(|d| d.api_key_env.clone()))
+        .filter(|e| !e.is_empty());
+    let literal_key = entry
+        .api_key
+        .clone()
+        .or_else(|| defaults.and_then(|d| d.api_key.clone()))
+        .filter(|k| !k.is_empty());
+    let mut secret = None;
+    let api_key_env = match (explicit_env, literal_key) {
+        (Some(env), key) => {
+            secret = key.map(|k| (env.clone(), k));
+            Some(env)
+        }
+        (None, Some(key)) => {
+            let env = agent_api_key_env(id, &provider);
+            secret = Some((env.clone(), key));
+            Some(env)
+        }
+        (None, None) => {
+            let env = default_api_key_env(&provider);
+            if env.is_empty() {
+                None
+            } else {
+                Some(env)
+            }
+        }
+    };
 
-    // Build agent TOML
-    let mut toml_str = String::new();
-    toml_str.push_str(&format!(
-        "# OpenFang agent manifest\n# Migrated from OpenClaw agent '{id}'\n\n"
-    ));
-    toml_str.push_str(&format!(
-        "name = \"{}\"\n",
-        display_name.replace('"', "\\\"")
-    ));
-    toml_str.push_str("version = \"0.1.0\"\n");
-    toml_str.push_str(&format!(
-        "description = \"Migrated from OpenClaw agent '{id}'\"\n"
-    ));
-    toml_str.push_str("author = \"openfang\"\n");
-    toml_str.push_str("module = \"builtin:chat\"\n");
-
-    toml_str.push_str("\n[model]\n");
-    toml_str.push_str(&format!("provider = \"{provider}\"\n"));
-    toml_str.push_str(&format!("model = \"{model}\"\n"));
-    toml_str.push_str(&format!(
-        "system_prompt = \"\"\"\n{system_prompt}\n\"\"\"\n"
-    ));
+    // System prompt from identity
+    // The defaults identity is a shared preamble the agent identity is appended to;
+    // blank identities (inline or from a file) count as unset
+    let default_identity = defaults
+        .and_then(|d| d.identity.as_deref())
+        .map(|identity| resolve_identity(identity, &options.source_dir, &mut warnings))
+        .filter(|identity| !identity.trim().is_empty());
+    let agent_identity = entry
+        .identity
+        .as_deref()
+        .map(|identity| resolve_identity(identity, &options.source_dir, &mut warnings))
+        .filter(|identity| !identity.trim().is_empty());
+    let system_prompt = match (default_identity, agent_identity) {
+        (Some(shared), Some(own)) if !options.replace_default_identity => {
+            format!("{shared}\n\n{own}")
+        }
+        (_, Some(own)) => own,
+        (Some(shared), None) => shared,
+        (None, None) => format!(
+            "You are {display_name}, an AI agent running on the OpenFang Agent OS. You are helpful, concise, and accurate."
+        ),
+    };
 
-    if let Some(ref api_key) = api_key_env {
-        toml_str.push_str(&format!("api_key_env = \"{api_key}\"\n"));
-    }
+    let mut notes = AgentNotes {
+        source_id: id.clone(),
+        primary_model: format!("{provider}/{model}"),
+        primary_origin,
+        fallback_origin,
+        tools: tools.clone(),
+        unmapped_tools: unmapped_tools.clone(),
+        capabilities: caps.summary(),
+        model_notes,
+        ..Default::default()
+    };
 
-    // Fallback models
+    let mut providers_used = vec![provider.clone()];
+    let mut fallback_models = Vec::new();
     for fb in &fallbacks {
-        let (fb_provider, fb_model) = split_model_ref(fb);
-        let fb_api_key = default_api_key_env(&fb_provider);
-        toml_str.push_str("\n[[fallback_models]]\n");
-        toml_str.push_str(&format!("provider = \"{fb_provider}\"\n"));
-        toml_str.push_str(&format!("model = \"{fb_model}\"\n"));
-        if !fb_api_key.is_empty() {
-            toml_str.push_str(&format!("api_key_env = \"{fb_api_key}\"\n"));
+        let fb_ref = parse_model_ref(fb);
+        let before = warnings.len();
+        warnings.extend(bare_model_warning(fb));
+        let fb_model = resolve_model_id(&fb_ref.provider, &fb_ref.model, options, &mut warnings);
+        notes.model_notes.extend_from_slice(&warnings[before..]);
+        notes
+            .fallback_models
+            .push(format!("{}/{fb_model}", fb_ref.provider));
+        let fb_base_url = provider_base_url(root, &fb_ref.provider);
+        warnings.extend(cloud_model_warnings(&fb_ref));
+        let fb_api_key = default_api_key_env(&fb_ref.provider);
+        fallback_models.push(ManifestModel {
+            provider: fb_ref.provider.clone(),
+            model: fb_model,
+            system_prompt: None,
+            api_key_env: Some(fb_api_key).filter(|k| !k.is_empty()),
+            base_url: fb_base_url,
+            params: ModelParams::default(),
+        });
+        providers_used.push(fb_ref.provider);
+    }
+
+    providers_used.sort();
+    providers_used.dedup();
+    for p in &providers_used {
+        if let Some(note) = provider_credential_note(p) {
+            warnings.push(note.to_string());
+        }
+        // Still written, so the rest of the manifest can be kept once fixed
+        if !is_openfang_provider(p, provider_base_url(root, p).as_deref()) {
+            warnings.push(format!(
+                "unrecognized provider '{p}': OpenFang will not load this agent until the \
+                 provider is changed to a supported one, or a base_url is set for an \
+                 OpenAI-compatible endpoint"
+            ));
         }
     }
 
-    // Capabilities section
-    toml_str.push_str("\n[capabilities]\n");
-    let tools_str: Vec<String> = tools.iter().map(|t| format!("\"{t}\"")).collect();
-    toml_str.push_str(&format!("tools = [{}]\n", tools_str.join(", ")));
-    toml_str.push_str("memory_read = [\"*\"]\n");
-    toml_str.push_str("memory_write = [\"self.*\"]\n");
+    caps.tools = tools;
 
-    if !caps.network.is_empty() {
-        let net_str: Vec<String> = caps.network.iter().map(|n| format!("\"{n}\"")).collect();
-        toml_str.push_str(&format!("network = [{}]\n", net_str.join(", ")));
+    let mut params =
+        ModelParams::from_openclaw(extract_model_params(entry, defaults), &mut warnings);
+    let skipped = resolve_limits(id, entry, defaults, &mut params, &mut warnings);
+
+    let mut manifest = AgentManifest::new(
+        &display_name,
+        entry
+            .description
+            .clone()
+            .filter(|d| !d.trim().is_empty())
+            .unwrap_or_else(|| format!("Migrated from OpenClaw agent '{id}'")),
+        ManifestModel {
+            provider,
+            model,
+            system_prompt: Some(system_prompt),
+            api_key_env,
+            base_url,
+            params,
+        },
+    );
+    manifest.tags = entry.tags.clone();
+    manifest.mcp_servers = mcp_servers.iter().map(|s| s.to_string()).collect();
+    manifest.fallback_models = fallback_models;
+    manifest.set_tool_profile(entry.tools.as_ref().and_then(|t| t.profile.clone()));
+    manifest.capabilities = caps;
+    manifest.metadata = source_id_metadata(id, entry.name.as_deref());
+    if let Some((schedule, prompt)) = convert_schedule(entry, &mut warnings) {
+        manifest.schedule = Some(schedule);
+        if let Some(prompt) = prompt {
+            manifest
+                .metadata
+                .insert("schedule_prompt".to_string(), prompt);
+        }
     }
-    if !caps.shell.is_empty() {
-        let shell_str: Vec<String> = caps.shell.iter().map(|s| format!("\"{s}\"")).collect();
-        toml_str.push_str(&format!("shell = [{}]\n", shell_str.join(", ")));
+
+    let mut toml_str = manifest.to_toml(id)?;
+    push_unmapped_tools_comment(&mut toml_str, &unmapped_tools);
+
+    Ok(ConvertedAgent {
+        toml: toml_str,
+        unmapped_tools,
+        warnings,
+        skipped,
+        secret,
+        memory_scope,
+        notes,
+    })
+}
+
+/// Apply the retired-model alias table unless the caller asked to keep IDs verbatim.
+fn resolve_model_id(
+    provider: &str,
+    model: &str,
+    options: &MigrateOptions,
+    warnings: &mut Vec<String>,
+) -> String {
+    if options.preserve_model_ids {
+        return model.to_string();
+    }
+    let (normalized, note) = normalize_model_id(provider, model);
+    if let Some(note) = note {
+        warnings.push(note);
+    }
+    normalized
+}
+
+/// Resolve an OpenClaw tool name to an OpenFang tool.
+///
+/// User mappings win over everything else. Exact names and known aliases resolve silently. Names that only differ
+/// from a known tool or alias by case, `_` or `-` are accepted too, with a
+/// note describing the match.
+fn resolve_tool_name(name: &str, tool_map: &ToolMap) -> Option<(String, Option<String>)> {
+    if let Some(of_name) = tool_map.get(name) {
+        return Some((of_name.to_string(), None));
+    }
+    if is_known_openfang_tool(name) {
+        return Some((name.to_string(), None));
+    }
+    if let Some(of_name) = map_tool_name(name) {
+        return Some((of_name.to_string(), None));
+    }
+    match suggest_tool_name(name) {
+        Some((of_name, true)) => Some((
+            of_name.to_string(),
+            Some(format!(
+                "tool '{name}' mapped to '{of_name}' (case/underscore-insensitive match)"
+            )),
+        )),
+        _ => None,
     }
-    if !caps.agent_message.is_empty() {
-        let msg_str: Vec<String> = caps
-            .agent_message
-            .iter()
-            .map(|m| format!("\"{m}\""))
-            .collect();
-        toml_str.push_str(&format!("agent_message = [{}]\n", msg_str.join(", ")));
+}
+
+/// Map a list of OpenClaw tool names, collecting the ones with no equivalent.
+fn map_tool_list(
+    names: &[String],
+    tool_map: &ToolMap,
+    mapped: &mut Vec<String>,
+    unmapped: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) {
+    for t in names {
+        if let Some(server) = tool_map.mcp_server_ref(t) {
+            let allow = &tool_map.mcp[server];
+            if allow.is_empty() {
+                warnings.push(format!(
+                    "MCP server '{server}' declares no tool allowlist; grant its tools manually"
+                ));
+            }
+            mapped.extend(allow.iter().map(|tool| mcp_tool_name(server, tool)));
+            continue;
+        }
+        match resolve_tool_name(t, tool_map) {
+            Some((of_name, note)) => {
+                mapped.push(of_name);
+                warnings.extend(note);
+            }
+            None => unmapped.push(t.clone()),
+        }
     }
-    if caps.agent_spawn {
-        toml_str.push_str("agent_spawn = true\n");
+}
+
+/// Lowercase a tool name and drop `_`/`-` separators for fuzzy comparison.
+fn normalize_tool_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find the closest OpenFang tool for an unrecognised name.
+///
+/// Compares against the known OpenFang tools and the OpenClaw alias keys.
+/// Returns the OpenFang tool and whether the match was exact after
+/// normalisation; otherwise only close edits (distance <= 2, and at most a
+/// quarter of the name) qualify.
+fn suggest_tool_name(name: &str) -> Option<(&'static str, bool)> {
+    let needle = normalize_tool_name(name);
+    if needle.is_empty() {
+        return None;
+    }
+    let candidates = KNOWN_OPENFANG_TOOLS
+        .iter()
+        .map(|t| (*t, *t))
+        .chain(OPENCLAW_TOOL_ALIASES.iter().copied());
+
+    let mut best: Option<(&'static str, usize)> = None;
+    for (key, of_name) in candidates {
+        let dist = edit_distance(&needle, &normalize_tool_name(key));
+        if dist == 0 {
+            return Some((of_name, true));
+        }
+        if best.is_none_or(|(_, d)| dist < d) {
+            best = Some((of_name, dist));
+        }
     }
+    best.filter(|(_, d)| *d <= 2 && d * 4 <= needle.chars().count())
+        .map(|(of_name, _)| (of_name, false))
+}
 
-    // Tool profile hint
-    if let Some(ref agent_tools) = entry.tools {
-        if let Some(ref profile) = agent_tools.profile {
-            toml_str.push_str(&format!("\nprofile = \"{profile}\"\n"));
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
         }
+        prev = cur;
     }
+    prev[b.len()]
+}
 
-    Ok((toml_str, unmapped_tools))
+/// Report line for a tool that could not be mapped, with a suggestion if any.
+fn unmapped_tool_warning(tool: &str) -> String {
+    match suggest_tool_name(tool) {
+        Some((of_name, _)) => format!(
+            "tool '{tool}' has no OpenFang equivalent and was skipped (did you mean '{of_name}'?)"
+        ),
+        None => format!("tool '{tool}' has no OpenFang equivalent and was skipped"),
+    }
 }
 
-fn resolve_default_tools(defaults: Option<&OpenClawAgentDefaults>) -> Vec<String> {
+/// Tools for an agent without its own tool config.
+///
+/// `agents.defaults.tools` wins; otherwise the root `tools` policy applies
+/// (profile or allow list, minus deny); otherwise a small read-only set.
+fn resolve_default_tools(
+    defaults: Option<&OpenClawAgentDefaults>,
+    root_tools: Option<&OpenClawRootTools>,
+    tool_map: &ToolMap,
+    warnings: &mut Vec<String>,
+) -> Vec<String> {
     if let Some(defs) = defaults {
         if let Some(ref tools) = defs.tools {
             if let Some(ref profile) = tools.profile {
+                warnings.extend(unknown_profile_warning(profile));
                 return tools_for_profile(profile);
             }
             if let Some(ref allow) = tools.allow {
-                let mut mapped = Vec::new();
-                for t in allow {
-                    if is_known_openfang_tool(t) {
-                        mapped.push(t.clone());
-                    } else if let Some(of_name) = map_tool_name(t) {
-                        mapped.push(of_name.to_string());
-                    }
-                }
+                let mapped: Vec<String> = allow
+                    .iter()
+                    .filter_map(|t| resolve_tool_name(t, tool_map).map(|(name, _)| name))
+                    .collect();
                 if !mapped.is_empty() {
                     return mapped;
                 }
             }
         }
     }
+    let defaults_have_tools = defaults.is_some_and(|d| d.tools.is_some());
+    if let (false, Some(root)) = (defaults_have_tools, root_tools) {
+        let map = |names: &[String]| -> Vec<String> {
+            names
+                .iter()
+                .filter_map(|t| resolve_tool_name(t, tool_map).map(|(name, _)| name))
+                .collect()
+        };
+        let base = match (&root.profile, &root.allow) {
+            (Some(profile), _) => {
+                warnings.extend(unknown_profile_warning(profile));
+                Some(tools_for_profile(profile))
+            }
+            (None, Some(allow)) => Some(map(allow)),
+            (None, None) => None,
+        };
+        if let Some(mut tools) = base {
+            let deny = map(root.deny.as_deref().unwrap_or_default());
+            tools.retain(|t| !deny.contains(t));
+            if !tools.is_empty() {
+                return tools;
+            }
+        }
+    }
     vec!["file_read".into(), "file_list".into(), "web_fetch".into()]
 }
 
@@ -1938,175 +4513,634 @@ fn migrate_memory_files(
     source: &Path,
     root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
-    // Collect agent IDs from the config
+    let agent_dirs = AgentDirs::from_root(root, options);
     let agent_ids: Vec<String> = root
         .agents
         .as_ref()
-        .map(|a| a.list.iter().map(|e| e.id.clone()).collect())
-        .unwrap_or_default();
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(e, _)| e.id)
+        .collect();
+    // The main session's memory goes to a real agent when none is called that
+    let main_target = main_agent_target(options, &agent_ids, &agent_dirs);
+
+    let mut found: std::collections::BTreeMap<String, Vec<(String, String)>> =
+        std::collections::BTreeMap::new();
+
+    // Per-agent `memory.path` overrides come first
+    for (entry, _) in root
+        .agents
+        .as_ref()
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+    {
+        let Some(value) = OpenClawMemoryLocation::from_value(entry.memory.as_ref()).path else {
+            continue;
+        };
+        let file = expand_config_path(&value, source).map(|p| {
+            if p.is_dir() {
+                p.join("MEMORY.md")
+            } else {
+                p
+            }
+        });
+        match file.filter(|f| f.is_file()) {
+            Some(f) => {
+                if let Some(content) = read_memory_text(&f, report) {
+                    if !content.trim().is_empty() {
+                        found.entry(entry.id).or_default().push((value, content));
+                    }
+                }
+            }
+            None => report.warnings.push(format!(
+                "Agent '{}': memory path '{value}' does not exist; its memory was not migrated",
+                entry.id
+            )),
+        }
+    }
+
+    // `memory.dir` moves layout 1 out of the workspace
+    let configured_dir = OpenClawMemoryLocation::from_value(root.memory.as_ref()).dir;
+    let memory_root = match configured_dir
+        .as_deref()
+        .and_then(|dir| Some((dir, expand_config_path(dir, source)?)))
+    {
+        Some((_, path)) if path.is_dir() => path,
+        Some((dir, _)) => {
+            report.warnings.push(format!(
+                "memory.dir '{dir}' does not exist; looked for MEMORY.md files under memory/ instead"
+            ));
+            source.join("memory")
+        }
+        None => source.join("memory"),
+    };
+    let memory_label = if memory_root == source.join("memory") {
+        "memory".to_string()
+    } else {
+        memory_root.display().to_string()
+    };
+    let shared_dir_is_agent = agent_ids.iter().any(|id| id == "shared");
+    let shared_memory_md = memory_root.join("shared").join("MEMORY.md");
+    migrate_shared_memory(
+        &memory_root,
+        &memory_label,
+        !shared_dir_is_agent,
+        target,
+        options,
+        report,
+    )?;
 
     // Check both memory layouts:
     // Layout 1: memory/<agent>/MEMORY.md
     // Layout 2: agents/<agent>/MEMORY.md (legacy)
-    let mut migrated = std::collections::HashSet::new();
-
-    let memory_dir = source.join("memory");
-    if memory_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&memory_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
-                let memory_md = path.join("MEMORY.md");
-                if !memory_md.exists() {
-                    continue;
-                }
-
-                let agent_name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-
-                let content = std::fs::read_to_string(&memory_md)?;
-                if content.trim().is_empty() {
-                    continue;
-                }
-
-                let dest_dir = target.join("agents").join(&agent_name);
-                let dest_file = dest_dir.join("imported_memory.md");
-
-                if !dry_run {
-                    std::fs::create_dir_all(&dest_dir)?;
-                    std::fs::write(&dest_file, &content)?;
-                }
-
-                report.imported.push(MigrateItem {
-                    kind: ItemKind::Memory,
-                    name: format!("{agent_name}/MEMORY.md"),
-                    destination: dest_file.display().to_string(),
-                });
+    // An agent with both gets them merged, newer layout first. Dated journal
+    // files next to MEMORY.md are collected from either layout.
+    let mut journals: std::collections::BTreeMap<String, Vec<PathBuf>> =
+        std::collections::BTreeMap::new();
+    let mut orphaned = Vec::new();
+    for (layout_dir, layout) in [
+        (memory_root, memory_label),
+        (source.join("agents"), "agents".to_string()),
+    ] {
+        let Ok(entries) = std::fs::read_dir(&layout_dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let memory_md = path.join("MEMORY.md");
+            if !path.is_dir() || (memory_md == shared_memory_md && !shared_dir_is_agent) {
+                continue;
+            }
+            let journal_files = dated_journal_files(&path);
+            let content = if memory_md.exists() {
+                read_memory_text(&memory_md, report).filter(|c| !c.trim().is_empty())
+            } else {
+                None
+            };
+            if content.is_none() && journal_files.is_empty() {
+                continue;
+            }
 
-                migrated.insert(agent_name);
+            let agent_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let rel = match content {
+                Some(_) => format!("{layout}/{agent_name}/MEMORY.md"),
+                None => format!("{layout}/{agent_name}/ (journal files)"),
+            };
+            let is_main = matches!(agent_name.as_str(), "default" | "main");
+            let owner = if agent_ids.contains(&agent_name) {
+                agent_name
+            } else if let Some(main) = main_target.filter(|_| is_main) {
+                main.clone()
+            } else {
+                orphaned.push(rel);
+                continue;
+            };
+            journals
+                .entry(owner.clone())
+                .or_default()
+                .extend(journal_files);
+            if let Some(content) = content {
+                found.entry(owner).or_default().push((rel, content));
             }
         }
     }
+    if !orphaned.is_empty() {
+        report.warnings.push(format!(
+            "No agent matches these memory files; they were not migrated: {}",
+            orphaned.join(", ")
+        ));
+    }
 
-    // Layout 2: agents/<agent>/MEMORY.md (legacy layout)
-    let agents_dir = source.join("agents");
-    if agents_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&agents_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
-
-                let agent_name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
+    let owners: std::collections::BTreeSet<String> =
+        found.keys().chain(journals.keys()).cloned().collect();
+    for agent_name in owners {
+        let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+            continue;
+        };
+        let dest_dir = target.join("agents").join(agent_dir);
+        let mut sources = found.remove(&agent_name).unwrap_or_default();
+        let mut agent_journals = journals.remove(&agent_name).unwrap_or_default();
+        agent_journals.sort_by_key(|p| p.file_name().map(|n| n.to_os_string()));
+        // A day's journal in both layouts is imported once if identical; differing
+        // copies are both kept
+        agent_journals.dedup_by(|b, a| {
+            a.file_name() == b.file_name()
+                && matches!(
+                    (std::fs::read(&*a), std::fs::read(&*b)),
+                    (Ok(a), Ok(b)) if a == b
+                )
+        });
 
-                if migrated.contains(&agent_name) {
-                    continue;
+        // Journals are copied as-is; the newest can also be appended to memory
+        let journal_dir = dest_dir.join("imported_journal");
+        let mut recent = String::new();
+        let recent_from = agent_journals
+            .len()
+            .saturating_sub(options.recent_journals_in_memory);
+        let mut taken = std::collections::HashSet::new();
+        for (i, journal) in agent_journals.iter().enumerate() {
+            let Some(file_name) = journal.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let mut dest_name = file_name.to_string();
+            if !taken.insert(dest_name.clone()) {
+                let stem = file_name.trim_end_matches(".md");
+                dest_name = format!("{stem}-2.md");
+                report.warnings.push(format!(
+                    "{agent_name}: journal {file_name} differs between memory layouts; \
+                     {} was imported as {dest_name}",
+                    journal.display()
+                ));
+            }
+            if !options.dry_run {
+                std::fs::create_dir_all(&journal_dir)?;
+                report.bytes_copied += std::fs::copy(journal, journal_dir.join(&dest_name))?;
+            }
+            if i >= recent_from {
+                if let Some(text) = read_memory_text(journal, report) {
+                    let date = Path::new(&dest_name).with_extension("");
+                    recent.push_str(&format!(
+                        "\n# Journal {}\n\n{}\n",
+                        date.display(),
+                        text.trim()
+                    ));
                 }
+            }
+        }
 
-                let memory_md = path.join("MEMORY.md");
-                if !memory_md.exists() {
-                    continue;
-                }
+        // Identical copies in both layouts are imported once
+        sources.dedup_by(|b, a| a.1.trim() == b.1.trim());
+        let (memory_name, mut content) = match sources.as_slice() {
+            [] => (None, String::new()),
+            [(_, content)] => (Some("MEMORY.md".to_string()), content.clone()),
+            _ => (
+                Some(format!(
+                    "MEMORY.md (merged from {})",
+                    sources
+                        .iter()
+                        .map(|(rel, _)| rel.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" and ")
+                )),
+                sources
+                    .iter()
+                    .map(|(rel, content)| format!("# Imported from {rel}\n\n{}\n", content.trim()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+        };
+        let journal_count = match agent_journals.len() {
+            1 => "1 journal file".to_string(),
+            n => format!("{n} journal files"),
+        };
+        let name = match (&memory_name, agent_journals.is_empty()) {
+            (Some(memory_name), true) => format!("{agent_name}/{memory_name}"),
+            (Some(memory_name), false) => format!("{agent_name}: {memory_name} + {journal_count}"),
+            (None, _) => format!("{agent_name}: {journal_count}"),
+        };
 
-                let content = std::fs::read_to_string(&memory_md)?;
-                if content.trim().is_empty() {
-                    continue;
-                }
+        if memory_name.is_none() && recent.is_empty() {
+            report.imported.push(MigrateItem {
+                kind: ItemKind::Memory,
+                name,
+                destination: journal_dir.display().to_string(),
+                details: None,
+            });
+            continue;
+        }
+        if content.is_empty() {
+            content = recent.trim_start().to_string();
+        } else if !recent.is_empty() {
+            if !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&recent);
+        }
+        let (dest_file, details) =
+            write_imported_memory(&dest_dir, &content, &agent_name, options, report)?;
 
-                let dest_dir = target.join("agents").join(&agent_name);
-                let dest_file = dest_dir.join("imported_memory.md");
+        report.imported.push(MigrateItem {
+            kind: ItemKind::Memory,
+            name,
+            destination: dest_file.display().to_string(),
+            details: Some(details),
+        });
+    }
 
-                if !dry_run {
-                    std::fs::create_dir_all(&dest_dir)?;
-                    std::fs::write(&dest_file, &content)?;
-                }
+    Ok(())
+}
 
-                report.imported.push(MigrateItem {
-                    kind: ItemKind::Memory,
-                    name: format!("{agent_name}/MEMORY.md"),
-                    destination: dest_file.display().to_string(),
-                });
-            }
-        }
+/// Dated journal files (`YYYY-MM-DD.md`) directly inside an agent memory directory.
+fn dated_journal_files(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension().is_some_and(|e| e == "md")
+                && p.file_stem().and_then(|s| s.to_str()).is_some_and(|stem| {
+                    stem.len() == 10 && chrono::NaiveDate::parse_from_str(stem, "%Y-%m-%d").is_ok()
+                })
+        })
+        .collect()
+}
+
+/// Copy notes every agent can read — markdown files at the top of the memory root
+/// (`GLOBAL.md`) and, unless an agent owns it, the `shared/` directory — into
+/// `shared_memory/`, keeping their paths relative to the memory root.
+fn migrate_shared_memory(
+    memory_root: &Path,
+    label: &str,
+    include_shared_dir: bool,
+    target: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(), MigrateError> {
+    let is_markdown = |p: &Path| p.is_file() && p.extension().is_some_and(|e| e == "md");
+    let mut files: Vec<PathBuf> = std::fs::read_dir(memory_root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_markdown(p))
+        .collect();
+    let shared = memory_root.join("shared");
+    if include_shared_dir && shared.is_dir() {
+        files.extend(
+            walkdir::WalkDir::new(&shared)
+                .into_iter()
+                .flatten()
+                .map(|e| e.into_path())
+                .filter(|p| is_markdown(p)),
+        );
     }
+    files.sort();
 
-    // Warn about agents with no memory found
-    for id in &agent_ids {
-        if !migrated.contains(id) {
-            let has_in_agents = source.join("agents").join(id).join("MEMORY.md").exists();
-            if !has_in_agents {
-                // not an error, just informational
+    let dest_root = target.join("shared_memory");
+    for file in files {
+        let Ok(rel) = file.strip_prefix(memory_root) else {
+            continue;
+        };
+        let dest = dest_root.join(rel);
+        if !options.dry_run {
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            report.bytes_copied += std::fs::copy(&file, &dest)?;
         }
+        report.imported.push(MigrateItem {
+            kind: ItemKind::Memory,
+            name: format!("{label}/{}", rel.to_string_lossy().replace('\\', "/")),
+            destination: dest.display().to_string(),
+            details: None,
+        });
     }
-
     Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Workspace directory migration
-// ---------------------------------------------------------------------------
+/// Schema versions of `memory-search/index.db` whose `chunks` table we understand.
+const MEMORY_INDEX_SCHEMA_VERSIONS: &[&str] = &["1"];
 
-fn migrate_workspace_dirs(
+/// One text chunk recovered from OpenClaw's memory search index.
+#[derive(Debug, Serialize)]
+struct IndexChunk {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_line: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_line: Option<i64>,
+}
+
+/// Copy `memory-search/index.db` when `copy_vector_index` is set, and import its
+/// text chunks when `import_memory_index` is set. An index that is neither copied
+/// nor readable is reported as skipped.
+fn migrate_memory_index(
     source: &Path,
     root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
-    // OpenClaw stores workspaces in workspaces/<agent>/
-    let workspaces_dir = source.join("workspaces");
-    if workspaces_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&workspaces_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
-
-                let agent_name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default();
-
-                let file_count = walkdir::WalkDir::new(&path)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                    .filter(|e| e.file_type().is_file())
-                    .count();
-
-                if file_count == 0 {
-                    continue;
-                }
-
-                let dest_dir = target.join("agents").join(&agent_name).join("workspace");
-
-                if !dry_run {
-                    copy_dir_recursive(&path, &dest_dir)?;
-                }
+    let index_path = source.join("memory-search/index.db");
+    if !index_path.exists() {
+        return Ok(());
+    }
+    let skip = |report: &mut MigrationReport, reason: String| {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Memory,
+            name: "memory-search/index.db".to_string(),
+            reason,
+        });
+    };
 
-                report.imported.push(MigrateItem {
-                    kind: ItemKind::Session, // reuse for workspace
-                    name: format!("{agent_name}/workspace ({file_count} files)"),
-                    destination: dest_dir.display().to_string(),
-                });
-            }
+    let copied = options.copy_vector_index;
+    if copied {
+        let dest = target.join("memory").join("index.db");
+        if !options.dry_run {
+            std::fs::create_dir_all(target.join("memory"))?;
+            report.bytes_copied += std::fs::copy(&index_path, &dest)?;
+        }
+        report.imported.push(MigrateItem {
+            kind: ItemKind::Memory,
+            name: "memory-search/index.db".to_string(),
+            destination: dest.display().to_string(),
+            details: None,
+        });
+        report.warnings.push(
+            "memory-search/index.db was copied as-is; it is only valid if OpenFang's embedding \
+             model produces vectors of the same dimensions as OpenClaw's"
+                .to_string(),
+        );
+    }
+
+    if !options.import_memory_index {
+        if !copied {
+            skip(
+                report,
+                "SQLite vector index not portable — OpenFang will rebuild embeddings".to_string(),
+            );
+        }
+        return Ok(());
+    }
+    let rows = match read_memory_index(&index_path) {
+        Ok(rows) => rows,
+        Err(e) if copied => {
+            report.warnings.push(format!(
+                "memory-search/index.db: could not read text chunks ({e})"
+            ));
+            return Ok(());
+        }
+        Err(e) => {
+            skip(
+                report,
+                format!(
+                    "Could not read SQLite vector index ({e}) — OpenFang will rebuild embeddings"
+                ),
+            );
+            return Ok(());
+        }
+    };
+
+    let agent_dirs = AgentDirs::from_root(root, options);
+    let agent_ids: Vec<String> = root
+        .agents
+        .as_ref()
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(e, _)| e.id)
+        .collect();
+    let main_target = main_agent_target(options, &agent_ids, &agent_dirs);
+
+    let mut by_agent: std::collections::BTreeMap<String, Vec<IndexChunk>> =
+        std::collections::BTreeMap::new();
+    let mut orphaned = 0;
+    for (agent, chunk) in rows {
+        // Chunks name their agent directly or through the memory file they came from
+        let agent = agent.or_else(|| {
+            let path = chunk.path.as_deref()?.replace('\\', "/");
+            let mut parts = path.split('/');
+            while let Some(part) = parts.next() {
+                if matches!(part, "memory" | "agents") {
+                    return parts.next().map(str::to_string);
+                }
+            }
+            None
+        });
+        let owner = match agent {
+            Some(a) if agent_ids.contains(&a) => Some(a),
+            Some(a) if !matches!(a.as_str(), "default" | "main") => None,
+            _ => main_target.cloned(),
+        };
+        match owner {
+            Some(owner) => by_agent.entry(owner).or_default().push(chunk),
+            None => orphaned += 1,
+        }
+    }
+    if orphaned > 0 {
+        report.warnings.push(format!(
+            "memory-search/index.db: {orphaned} chunks belong to no migrated agent and were not imported"
+        ));
+    }
+
+    for (agent, chunks) in by_agent {
+        let Some(agent_dir) = agent_dirs.dir_for(&agent) else {
+            continue;
+        };
+        let dest_dir = target.join("agents").join(agent_dir);
+        let dest_file = dest_dir.join("imported_memory_index.jsonl");
+        if !options.dry_run {
+            std::fs::create_dir_all(&dest_dir)?;
+            let mut jsonl = String::new();
+            for chunk in &chunks {
+                jsonl.push_str(&serde_json::to_string(chunk).unwrap_or_default());
+                jsonl.push('\n');
+            }
+            std::fs::write(&dest_file, jsonl)?;
+        }
+        report.imported.push(MigrateItem {
+            kind: ItemKind::Memory,
+            name: format!("{agent}/memory-search/index.db ({} chunks)", chunks.len()),
+            destination: dest_file.display().to_string(),
+            details: None,
+        });
+    }
+    report.notes.push(
+        "memory-search/index.db: text chunks imported; embeddings were not copied and will be rebuilt"
+            .to_string(),
+    );
+    Ok(())
+}
+
+/// Read `(agent, chunk)` rows from an OpenClaw memory index, opened read-only.
+/// Errors describe why the database isn't a usable index.
+fn read_memory_index(path: &Path) -> Result<Vec<(Option<String>, IndexChunk)>, String> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| e.to_string())?;
+    let table_columns = |table: &str| -> Result<Vec<String>, String> {
+        let mut stmt = conn
+            .prepare(&format!("PRAGMA table_info({table})"))
+            .map_err(|e| e.to_string())?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>(1))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        Ok(columns)
+    };
+
+    if !table_columns("meta")?.is_empty() {
+        let version: Option<String> = conn
+            .query_row(
+                "SELECT CAST(value AS TEXT) FROM meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .ok();
+        if let Some(v) = version.filter(|v| !MEMORY_INDEX_SCHEMA_VERSIONS.contains(&v.as_str())) {
+            return Err(format!("unrecognized schema version {v}"));
+        }
+    }
+
+    let columns = table_columns("chunks")?;
+    if !columns.iter().any(|c| c == "text") {
+        return Err("no chunks table with a text column".to_string());
+    }
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find(|n| columns.iter().any(|c| c == *n))
+            .map_or("NULL".to_string(), |n| n.to_string())
+    };
+    let sql = format!(
+        "SELECT text, {}, {}, {}, {}, {} FROM chunks ORDER BY rowid",
+        column(&["agent_id", "agent"]),
+        column(&["path"]),
+        column(&["source"]),
+        column(&["start_line"]),
+        column(&["end_line"]),
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, Option<String>>(1)?,
+                IndexChunk {
+                    text: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                    path: row.get(2)?,
+                    source: row.get(3)?,
+                    start_line: row.get(4)?,
+                    end_line: row.get(5)?,
+                },
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .filter(|(_, chunk)| !chunk.text.trim().is_empty())
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Workspace directory migration
+// ---------------------------------------------------------------------------
+
+fn migrate_workspace_dirs(
+    source: &Path,
+    root: &OpenClawRoot,
+    target: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
+    let agent_dirs = AgentDirs::from_root(root, options);
+
+    // OpenClaw stores workspaces in workspaces/<agent>/
+    let workspaces_dir = source.join("workspaces");
+    if workspaces_dir.exists() {
+        if let Ok(entries) = std::fs::read_dir(&workspaces_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_dir() {
+                    continue;
+                }
+
+                let agent_name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let file_count = walkdir::WalkDir::new(&path)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.file_type().is_file())
+                    .count();
+
+                if file_count == 0 {
+                    continue;
+                }
+
+                let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+                    continue;
+                };
+
+                let dest_dir = target.join("agents").join(agent_dir).join("workspace");
+
+                if !dry_run {
+                    copy_workspace(&path, &dest_dir, options, report)?;
+                }
+
+                report.imported.push(MigrateItem {
+                    kind: ItemKind::Session, // reuse for workspace
+                    name: format!("{agent_name}/workspace ({file_count} files)"),
+                    destination: dest_dir.display().to_string(),
+                    details: None,
+                });
+            }
         }
     }
 
     // Also check legacy agents/<agent>/workspace/ layout
-    let _ = root; // used for agent IDs if needed
     let agents_dir = source.join("agents");
     if agents_dir.exists() {
         if let Ok(entries) = std::fs::read_dir(&agents_dir) {
@@ -2127,7 +5161,10 @@ fn migrate_workspace_dirs(
                     .unwrap_or_default();
 
                 // Skip if already migrated from workspaces/ dir
-                let dest_dir = target.join("agents").join(&agent_name).join("workspace");
+                let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+                    continue;
+                };
+                let dest_dir = target.join("agents").join(agent_dir).join("workspace");
                 if dest_dir.exists() {
                     continue;
                 }
@@ -2143,13 +5180,14 @@ fn migrate_workspace_dirs(
                 }
 
                 if !dry_run {
-                    copy_dir_recursive(&workspace_dir, &dest_dir)?;
+                    copy_workspace(&workspace_dir, &dest_dir, options, report)?;
                 }
 
                 report.imported.push(MigrateItem {
                     kind: ItemKind::Session,
                     name: format!("{agent_name}/workspace ({file_count} files)"),
                     destination: dest_dir.display().to_string(),
+                    details: None,
                 });
             }
         }
@@ -2164,181 +5202,936 @@ fn migrate_workspace_dirs(
 
 fn migrate_sessions(
     source: &Path,
+    root: &OpenClawRoot,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let sessions_dir = source.join("sessions");
     if !sessions_dir.exists() {
         return Ok(());
     }
 
-    let dest_dir = target.join("imported_sessions");
-    let mut count = 0;
-
-    if let Ok(entries) = std::fs::read_dir(&sessions_dir) {
-        for entry in entries.flatten() {
+    let agent_dirs = AgentDirs::from_root(root, options);
+    // Converted tool calls are renamed like agent tool lists; load warnings were
+    // already reported with the agents
+    let tool_map = ToolMap::load(options, &mut Vec::new())?;
+    let agent_ids: Vec<String> = root
+        .agents
+        .as_ref()
+        .map(|a| expand_subagents(&a.list, None))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(e, _)| e.id)
+        .collect();
+    // Main-session and unmatched logs go to the default agent
+    let main_target = main_agent_target(options, &agent_ids, &agent_dirs);
+
+    let flat_dir = target.join("imported_sessions");
+    // Counts per destination directory (`None` = flat) and whether converted
+    let mut counts: std::collections::BTreeMap<(Option<String>, bool), SessionCounts> =
+        std::collections::BTreeMap::new();
+    let cutoff = [
+        options.sessions_since,
+        options
+            .sessions_max_age_days
+            .map(|days| chrono::Utc::now() - chrono::Duration::days(days.into())),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+    let (mut too_old, mut too_old_bytes) = (0, 0);
+    let mut excluded = 0;
+    let mut taken = std::collections::HashSet::new();
+    // Files left behind, by extension
+    let mut unsupported: std::collections::BTreeMap<String, usize> =
+        std::collections::BTreeMap::new();
+
+    let archive_limit = options
+        .sessions_size_limit
+        .unwrap_or(MAX_SESSION_ARCHIVE_BYTES);
+
+    // Logs that pass the filters, in name order
+    let mut candidates: Vec<SessionFile> = Vec::new();
+    {
+        let files = walkdir::WalkDir::new(&sessions_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file());
+        for entry in files {
             let path = entry.path();
-            if !path.is_file() {
-                continue;
-            }
-            // Only copy .jsonl files
-            let ext = path.extension().and_then(|e| e.to_str());
-            if ext != Some("jsonl") {
-                continue;
-            }
-
             let file_name = path
                 .file_name()
                 .map(|n| n.to_string_lossy().to_string())
                 .unwrap_or_default();
 
-            if !dry_run {
-                std::fs::create_dir_all(&dest_dir)?;
-                std::fs::copy(&path, dest_dir.join(&file_name))?;
+            // Nested logs keep their folders; a channel-named first folder is noted
+            let rel = path.strip_prefix(&sessions_dir).unwrap_or(path);
+            let rel_name = rel.to_string_lossy().replace('\\', "/");
+
+            // Only plain .jsonl logs, and gzipped ones if asked, are migrated;
+            // archives are streamed for their size and time here, and only
+            // decompressed into memory once picked
+            let wants_time = cutoff.is_some() || options.sessions_size_limit.is_some();
+            let (file_name, archive, size, newest) = match file_name.strip_suffix(".gz") {
+                Some(plain) if options.decompress_sessions && plain.ends_with(".jsonl") => {
+                    match scan_gzip(path, archive_limit, wants_time) {
+                        Ok((size, newest)) => (plain.to_string(), true, size, newest),
+                        Err(e) => {
+                            report.warnings.push(format!(
+                                "sessions/{rel_name}: could not decompress ({e}); it was not migrated"
+                            ));
+                            continue;
+                        }
+                    }
+                }
+                _ if file_name.ends_with(".jsonl") => {
+                    let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                    let newest = if wants_time {
+                        newest_message_time(path)
+                    } else {
+                        None
+                    };
+                    (file_name, false, size, newest)
+                }
+                _ => {
+                    *unsupported
+                        .entry(session_extension(&file_name))
+                        .or_default() += 1;
+                    continue;
+                }
+            };
+            let time = if wants_time {
+                session_time(path, newest)
+            } else {
+                None
+            };
+            if let (Some(cutoff), Some(time)) = (cutoff, time) {
+                if time < cutoff {
+                    too_old += 1;
+                    too_old_bytes += size;
+                    continue;
+                }
             }
 
-            count += 1;
-        }
-    }
-
-    if count > 0 {
-        report.imported.push(MigrateItem {
-            kind: ItemKind::Session,
-            name: format!("{count} session files"),
-            destination: dest_dir.display().to_string(),
-        });
-        info!("Migrated {count} session files");
-    }
-
-    Ok(())
-}
-
-// ---------------------------------------------------------------------------
-// Report non-migratable features
-// ---------------------------------------------------------------------------
-
-fn report_skipped_features(root: &OpenClawRoot, source: &Path, report: &mut MigrationReport) {
-    // Cron jobs
-    if root.cron.is_some() {
-        report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "cron".to_string(),
-            reason: "Cron job scheduling not yet supported — use OpenFang's ScheduleMode::Periodic instead".to_string(),
-        });
-    }
-
-    // Hooks
-    if root.hooks.is_some() {
-        report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "hooks".to_string(),
-            reason: "Webhook hooks not supported — use OpenFang's event system instead".to_string(),
-        });
-    }
+            let (owner, name) = split_session_name(&file_name, &agent_ids);
+            let agent = owner.or(main_target.map(String::as_str));
+            let dir = match agent {
+                Some(id) => match agent_dirs.dir_for(id) {
+                    Some(dir) => Some(dir),
+                    None => {
+                        excluded += 1;
+                        continue;
+                    }
+                },
+                None => None,
+            };
 
-    // Auth profiles
-    if let Some(ref auth) = root.auth {
-        if auth.profiles.is_some() {
-            report.skipped.push(SkippedItem {
-                kind: ItemKind::Config,
-                name: "auth-profiles".to_string(),
-                reason: "Auth profiles (API keys, OAuth tokens) not migrated for security — set env vars manually".to_string(),
+            candidates.push(SessionFile {
+                path: path.to_path_buf(),
+                rel_name,
+                name,
+                agent: agent.map(str::to_string),
+                dir,
+                archive,
+                size,
+                time,
             });
         }
     }
 
-    // Skills entries
-    if let Some(ref skills) = root.skills {
-        if let Some(ref entries) = skills.entries {
-            if !entries.is_empty() {
+    // Over the size budget, the newest logs are kept; the same files are picked
+    // on a dry run
+    let (mut over_limit, mut over_limit_bytes) = (0, 0);
+    if let Some(limit) = options.sessions_size_limit {
+        let mut newest_first: Vec<usize> = (0..candidates.len()).collect();
+        newest_first.sort_by(|&a, &b| candidates[b].time.cmp(&candidates[a].time));
+        let mut used = 0u64;
+        let mut keep = vec![false; candidates.len()];
+        for i in newest_first {
+            if over_limit == 0 && used + candidates[i].size <= limit {
+                used += candidates[i].size;
+                keep[i] = true;
+            } else {
+                over_limit += 1;
+                over_limit_bytes += candidates[i].size;
+            }
+        }
+        let mut keep = keep.into_iter();
+        candidates.retain(|_| keep.next().unwrap_or(false));
+    }
+
+    for file in candidates {
+        let SessionFile {
+            path,
+            rel_name,
+            mut name,
+            agent,
+            dir,
+            archive,
+            ..
+        } = file;
+        let data = if archive {
+            match read_gzip(&path, archive_limit) {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    report.warnings.push(format!(
+                        "sessions/{rel_name}: could not decompress ({e}); it was not migrated"
+                    ));
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        let rel_dir = Path::new(&rel_name).parent().unwrap_or(Path::new(""));
+        let channel = rel_dir
+            .components()
+            .next()
+            .and_then(|c| session_channel(&c.as_os_str().to_string_lossy()));
+
+        if let (true, Some(agent), Some(dir)) = (options.convert_sessions, &agent, &dir) {
+            let session = match &data {
+                Some(data) => convert_session_text(&String::from_utf8_lossy(data), &tool_map),
+                None => convert_session_file(&path, &tool_map)?,
+            };
+            let bad_share = session.bad_lines as f64 / session.lines.max(1) as f64;
+            if bad_share > options.session_bad_line_limit {
                 report.skipped.push(SkippedItem {
-                    kind: ItemKind::Skill,
-                    name: format!("{} skill entries", entries.len()),
-                    reason: "Skills must be reinstalled via `openfang skill install`".to_string(),
+                    kind: ItemKind::Session,
+                    name: format!("sessions/{rel_name}"),
+                    reason: format!(
+                        "{} of {} lines unparseable (limit {:.0}%)",
+                        session.bad_lines,
+                        session.lines,
+                        options.session_bad_line_limit * 100.0
+                    ),
                 });
+                continue;
+            }
+            if session.bad_lines > 0 {
+                report.warnings.push(format!(
+                    "sessions/{rel_name}: skipped {} of {} lines as unparseable",
+                    session.bad_lines, session.lines
+                ));
+            }
+            if session.messages > 0 {
+                let dest = target
+                    .join("agents")
+                    .join(dir)
+                    .join("imported_sessions")
+                    .join(format!("{}.jsonl", session_uuid(agent, &rel_name)));
+                if !dry_run {
+                    std::fs::create_dir_all(dest.parent().unwrap_or(target))?;
+                    std::fs::write(&dest, session.jsonl)?;
+                }
+                counts
+                    .entry((Some(dir.clone()), true))
+                    .or_default()
+                    .add(channel);
+                continue;
             }
+            report.warnings.push(format!(
+                "sessions/{rel_name}: no messages could be converted; copied as-is"
+            ));
+        }
+
+        let dest_dir = match &dir {
+            Some(dir) => target.join("agents").join(dir).join("imported_sessions"),
+            None => flat_dir.clone(),
+        }
+        .join(rel_dir);
+        // `main.jsonl` and `agent:<default>:main.jsonl` would share a name
+        let base = name.clone();
+        let mut n = 1;
+        while !taken.insert(dest_dir.join(&name)) {
+            n += 1;
+            name = format!("{base}-{n}");
+        }
+        if !dry_run {
+            std::fs::create_dir_all(&dest_dir)?;
+            let dest = dest_dir.join(format!("{name}.jsonl"));
+            report.bytes_copied += match &data {
+                Some(data) => {
+                    std::fs::write(&dest, data)?;
+                    data.len() as u64
+                }
+                None => std::fs::copy(&path, &dest)?,
+            };
         }
+        counts.entry((dir, false)).or_default().add(channel);
     }
 
-    // Cron state file
-    if source.join("cron").join("cron-store.json").exists() {
-        report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "cron-store.json".to_string(),
-            reason: "Cron run state not portable".to_string(),
+    let converted_any = counts.keys().any(|(_, converted)| *converted);
+    for ((dir, converted), counts) in counts {
+        let (label, agent_dir) = match &dir {
+            Some(dir) => (format!("{dir}: "), target.join("agents").join(dir)),
+            None => (String::new(), target.to_path_buf()),
+        };
+        let (what, dest) = if converted {
+            ("converted sessions", agent_dir.join("imported_sessions"))
+        } else {
+            ("session files", agent_dir.join("imported_sessions"))
+        };
+        let mut name = format!("{label}{} {what}", counts.files);
+        if !counts.channels.is_empty() {
+            let channels: Vec<String> = counts
+                .channels
+                .iter()
+                .map(|(channel, n)| format!("{n} from {channel}"))
+                .collect();
+            name.push_str(&format!(" ({})", channels.join(", ")));
+        }
+        info!("Migrated {name}");
+        report.imported.push(MigrateItem {
+            kind: ItemKind::Session,
+            name,
+            destination: dest.display().to_string(),
+            details: None,
         });
     }
-
-    // Vector index
-    if source.join("memory-search").join("index.db").exists() {
-        report.skipped.push(SkippedItem {
-            kind: ItemKind::Memory,
-            name: "memory-search/index.db".to_string(),
-            reason: "SQLite vector index not portable — OpenFang will rebuild embeddings"
+    if converted_any {
+        report.warnings.push(
+            "Converted sessions are a readable archive under imported_sessions/; OpenFang \
+             keeps live sessions in its database and will not resume them"
                 .to_string(),
-        });
+        );
     }
-
-    // Auth profiles file
-    if source.join("auth-profiles.json").exists() {
+    if let (Some(cutoff), true) = (cutoff, too_old > 0) {
         report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "auth-profiles.json".to_string(),
-            reason: "Credential file not migrated for security — set API keys as env vars"
-                .to_string(),
+            kind: ItemKind::Session,
+            name: format!("{too_old} session files ({})", format_bytes(too_old_bytes)),
+            reason: format!(
+                "Last message before {}",
+                cutoff.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
         });
     }
-
-    // Session config
-    if root.session.is_some() {
+    if over_limit > 0 {
         report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "session".to_string(),
-            reason: "Session scope config differs — OpenFang uses per-agent sessions by default"
-                .to_string(),
+            kind: ItemKind::Session,
+            name: format!(
+                "{over_limit} session files ({})",
+                format_bytes(over_limit_bytes)
+            ),
+            reason: format!(
+                "Older than the newest {} of logs; raise sessions_size_limit to include them",
+                format_bytes(options.sessions_size_limit.unwrap_or(0))
+            ),
         });
     }
-
-    // Memory backend config
-    if root.memory.is_some() {
+    if !unsupported.is_empty() {
+        let counts: Vec<String> = unsupported
+            .iter()
+            .map(|(ext, n)| format!("{n} {ext}"))
+            .collect();
+        let mut warning = format!(
+            "sessions/: skipped {} \u{2014} only plain .jsonl is migrated",
+            counts.join(", ")
+        );
+        if unsupported.contains_key(".jsonl.gz") {
+            warning.push_str("; set decompress_sessions to include .jsonl.gz archives");
+        }
+        report.warnings.push(warning);
+    }
+    if excluded > 0 {
         report.skipped.push(SkippedItem {
-            kind: ItemKind::Config,
-            name: "memory".to_string(),
-            reason:
-                "Memory backend config not migrated — OpenFang uses SQLite with vector embeddings"
-                    .to_string(),
+            kind: ItemKind::Session,
+            name: format!("{excluded} session files"),
+            reason: "Their agent is not migrated".to_string(),
         });
     }
+
+    Ok(())
 }
 
-// ---------------------------------------------------------------------------
-// Legacy YAML migration (backward compat)
-// ---------------------------------------------------------------------------
+/// A session log picked for migration, before it is converted or copied.
+struct SessionFile {
+    path: PathBuf,
+    /// Path under `sessions/`, with `/` separators.
+    rel_name: String,
+    /// Session name without the agent prefix or extension.
+    name: String,
+    agent: Option<String>,
+    /// Destination agent directory; `None` for the flat `imported_sessions/`.
+    dir: Option<String>,
+    /// A `.jsonl.gz` archive, decompressed only when converted or copied.
+    archive: bool,
+    /// Bytes to migrate (decompressed for archives).
+    size: u64,
+    /// Newest message time, or last modification; only read when a cutoff or
+    /// size limit needs it.
+    time: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Session files migrated into one destination, and how many came from each channel.
+#[derive(Default)]
+struct SessionCounts {
+    files: usize,
+    channels: std::collections::BTreeMap<&'static str, usize>,
+}
+
+impl SessionCounts {
+    fn add(&mut self, channel: Option<&'static str>) {
+        self.files += 1;
+        if let Some(channel) = channel {
+            *self.channels.entry(channel).or_default() += 1;
+        }
+    }
+}
+
+/// The channel a `sessions/<folder>/` subfolder is named after, if any.
+fn session_channel(folder: &str) -> Option<&'static str> {
+    const OPENCLAW_ONLY: &[&str] = &["googlechat", "msteams", "imessage", "bluebubbles"];
+    SUPPORTED_CHANNELS
+        .iter()
+        .chain(OPENCLAW_ONLY)
+        .find(|c| c.eq_ignore_ascii_case(folder))
+        .copied()
+}
+
+/// Split a session log name into its owning agent and the session name.
+///
+/// `agent:<id>:<key>.jsonl` and `agent_<id>_<key>.jsonl` name an agent from
+/// `agent_ids`; anything else (e.g. `main.jsonl`) has no owner and keeps its stem.
+fn split_session_name<'a>(file_name: &str, agent_ids: &'a [String]) -> (Option<&'a str>, String) {
+    let stem = file_name.strip_suffix(".jsonl").unwrap_or(file_name);
+    // Longest ids first, so `agent_web_dev_main` prefers `web_dev` over `web`
+    let mut ids: Vec<&String> = agent_ids.iter().collect();
+    ids.sort_by_key(|id| std::cmp::Reverse(id.len()));
+    for (prefix, sep) in [("agent:", ':'), ("agent_", '_')] {
+        let Some(rest) = stem.strip_prefix(prefix) else {
+            continue;
+        };
+        for id in &ids {
+            let key = rest
+                .strip_prefix(id.as_str())
+                .and_then(|r| r.strip_prefix(sep))
+                .filter(|k| !k.is_empty());
+            if let Some(key) = key {
+                return (Some(id.as_str()), key.replace(':', "_"));
+            }
+        }
+    }
+    (None, stem.replace(':', "_"))
+}
+
+/// A stable session id for a converted log, so re-running overwrites it.
+fn session_uuid(agent: &str, file_name: &str) -> uuid::Uuid {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(format!("{agent}\0{file_name}").as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Builder::from_random_bytes(bytes).into_uuid()
+}
+
+/// A session log rewritten in the JSONL layout of the kernel's session mirror, for
+/// reading back as an archive. The kernel keeps live sessions in SQLite and never
+/// loads these files.
+struct ConvertedSession {
+    jsonl: String,
+    messages: usize,
+    /// Non-empty lines in the source log.
+    lines: usize,
+    bad_lines: usize,
+}
+
+/// One line of OpenFang's session JSONL, as written by the kernel's session mirror.
+#[derive(Serialize)]
+struct SessionLine {
+    timestamp: String,
+    role: &'static str,
+    content: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_use: Option<serde_json::Value>,
+}
+
+impl ConvertedSession {
+    fn push(
+        &mut self,
+        time: chrono::DateTime<chrono::Utc>,
+        role: &'static str,
+        text: String,
+        tool_parts: Vec<serde_json::Value>,
+    ) {
+        let line = SessionLine {
+            timestamp: time.to_rfc3339(),
+            role,
+            content: serde_json::Value::String(text),
+            tool_use: (!tool_parts.is_empty()).then_some(serde_json::Value::Array(tool_parts)),
+        };
+        self.jsonl
+            .push_str(&serde_json::to_string(&line).unwrap_or_default());
+        self.jsonl.push('\n');
+        self.messages += 1;
+    }
+}
+
+/// Convert an OpenClaw session log line by line into an archive [`ConvertedSession`].
+/// Lines that are not valid JSON or carry an unknown role are counted in
+/// `bad_lines`; records that are not messages (session headers, model changes, ...)
+/// are ignored. Tool calls and results are paired up by [`ToolPairing`], so the
+/// archive reads like the kernel's own session mirror.
+fn convert_session_file(path: &Path, tool_map: &ToolMap) -> Result<ConvertedSession, MigrateError> {
+    let text = String::from_utf8_lossy(&std::fs::read(path)?).into_owned();
+    Ok(convert_session_text(&text, tool_map))
+}
+
+/// [`convert_session_file`] for a log already in memory.
+fn convert_session_text(text: &str, tool_map: &ToolMap) -> ConvertedSession {
+    let mut session = ConvertedSession {
+        jsonl: String::new(),
+        messages: 0,
+        lines: 0,
+        bad_lines: 0,
+    };
+    let mut pairing = ToolPairing::default();
+    let mut last_time = None;
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        session.lines += 1;
+        let Ok(record) = serde_json::from_str::<serde_json::Value>(line) else {
+            session.bad_lines += 1;
+            continue;
+        };
+        let converted = match convert_session_record(&record) {
+            Ok(Some(converted)) => converted,
+            Ok(None) => continue,
+            Err(()) => {
+                session.bad_lines += 1;
+                continue;
+            }
+        };
+        let SessionMessage {
+            role,
+            text,
+            tool_parts,
+        } = converted;
+        let time = record_time(&record)
+            .or_else(|| record.get("message").and_then(record_time))
+            .or(last_time)
+            .unwrap_or_else(chrono::Utc::now);
+        last_time = Some(time);
+        let had_tools = !tool_parts.is_empty();
+        let (tool_parts, notes) = pairing.apply(tool_parts, tool_map);
+        // A message left with nothing once its tool parts became notes is dropped
+        if !(had_tools && text.is_empty() && tool_parts.is_empty()) {
+            session.push(time, role, text.join("\n"), tool_parts);
+        }
+        for note in notes {
+            session.push(time, "system", note, Vec::new());
+        }
+    }
+    session
+}
+
+/// Matches tool results to the calls before them while a session log is converted.
+///
+/// Calls are renamed to their OpenFang tool; calls without an id get a generated
+/// one, and results without an id answer the oldest open call. Calls to tools
+/// OpenFang lacks, their results, and results answering no call cannot stay tool
+/// blocks (a result must follow its call), so they become system notes.
+#[derive(Default)]
+struct ToolPairing {
+    /// Calls awaiting a result: id, OpenClaw name, and OpenFang name if mapped.
+    open: Vec<(String, String, Option<String>)>,
+    generated: usize,
+}
+
+impl ToolPairing {
+    /// Returns the tool parts to keep and the notes to add after the message.
+    fn apply(
+        &mut self,
+        parts: Vec<serde_json::Value>,
+        tool_map: &ToolMap,
+    ) -> (Vec<serde_json::Value>, Vec<String>) {
+        let str_of = |part: &serde_json::Value, key: &str| {
+            part.get(key)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let mut kept = Vec::new();
+        let mut notes = Vec::new();
+        for mut part in parts {
+            if part["type"] == "tool_use" {
+                let id = str_of(&part, "id").unwrap_or_else(|| {
+                    self.generated += 1;
+                    format!("migrated_call_{}", self.generated)
+                });
+                let name = str_of(&part, "name").unwrap_or_default();
+                let mapped = resolve_tool_name(&name, tool_map).map(|(of_name, _)| of_name);
+                match &mapped {
+                    Some(of_name) => {
+                        part["id"] = id.clone().into();
+                        part["name"] = of_name.clone().into();
+                        kept.push(part);
+                    }
+                    None => notes.push(format!(
+                        "[OpenClaw tool call '{name}' ({id}), which has no OpenFang equivalent]\ninput: {}",
+                        part["input"]
+                    )),
+                }
+                self.open.push((id, name, mapped));
+                continue;
+            }
+
+            let id = str_of(&part, "tool_use_id");
+            let call = match &id {
+                Some(id) => self.open.iter().position(|(open, ..)| open == id),
+                None => (!self.open.is_empty()).then_some(0),
+            }
+            .map(|i| self.open.remove(i));
+            let content = part["content"].as_str().unwrap_or_default();
+            let failed = if part["is_error"] == true {
+                ", failed"
+            } else {
+                ""
+            };
+            match call {
+                Some((id, _, Some(_))) => {
+                    part["tool_use_id"] = id.into();
+                    kept.push(part);
+                }
+                Some((id, name, None)) => notes.push(format!(
+                    "[OpenClaw tool result for '{name}' ({id}){failed}]\n{content}"
+                )),
+                None => notes.push(format!(
+                    "[OpenClaw tool result ({}){failed} with no matching call]\n{content}",
+                    id.as_deref().unwrap_or("no id")
+                )),
+            }
+        }
+        (kept, notes)
+    }
+}
+
+/// The parts of one session record that make up a [`SessionLine`].
+struct SessionMessage {
+    role: &'static str,
+    text: Vec<String>,
+    tool_parts: Vec<serde_json::Value>,
+}
+
+impl SessionMessage {
+    fn new(role: &'static str, text: Vec<String>, tool_parts: Vec<serde_json::Value>) -> Self {
+        Self {
+            role,
+            text,
+            tool_parts,
+        }
+    }
+}
+
+/// Convert one session record: `Ok(None)` for records that are not messages, or
+/// `Err` for a message with a role OpenFang lacks.
+fn convert_session_record(record: &serde_json::Value) -> Result<Option<SessionMessage>, ()> {
+    let str_field = |v: &serde_json::Value, keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|x| x.as_str()))
+            .map(str::to_string)
+    };
+    let tool_result = |v: &serde_json::Value, content: Option<&serde_json::Value>| {
+        serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": str_field(v, &["tool_use_id", "toolCallId", "tool_call_id", "id"]).unwrap_or_default(),
+            "content": content.map(session_text).unwrap_or_default(),
+            "is_error": v.get("is_error").or_else(|| v.get("isError")).and_then(|e| e.as_bool()).unwrap_or(false),
+        })
+    };
+
+    // Standalone tool records
+    match record.get("type").and_then(|t| t.as_str()) {
+        Some("tool_use") => {
+            let part = serde_json::json!({
+                "type": "tool_use",
+                "id": str_field(record, &["id", "tool_use_id"]).unwrap_or_default(),
+                "name": str_field(record, &["name", "tool"]).unwrap_or_default(),
+                "input": record.get("input").or_else(|| record.get("arguments")).cloned().unwrap_or_default(),
+            });
+            return Ok(Some(SessionMessage::new(
+                "assistant",
+                Vec::new(),
+                vec![part],
+            )));
+        }
+        Some("tool_result") => {
+            let content = record.get("output").or_else(|| record.get("content"));
+            return Ok(Some(SessionMessage::new(
+                "user",
+                Vec::new(),
+                vec![tool_result(record, content)],
+            )));
+        }
+        _ => {}
+    }
+
+    // `{"type":"message","message":{...}}` wraps the message; older logs are flat
+    let message = record
+        .get("message")
+        .filter(|m| m.is_object())
+        .unwrap_or(record);
+    let Some(role) = message.get("role").and_then(|r| r.as_str()) else {
+        return Ok(None);
+    };
+    let content = message.get("content");
+    let role = match role {
+        "user" | "human" => "user",
+        "assistant" | "ai" => "assistant",
+        "system" => "system",
+        "tool" | "toolResult" | "tool_result" => {
+            return Ok(Some(SessionMessage::new(
+                "user",
+                Vec::new(),
+                vec![tool_result(message, content)],
+            )));
+        }
+        _ => return Err(()),
+    };
+
+    let mut text = Vec::new();
+    let mut tool_parts = Vec::new();
+    match content {
+        Some(serde_json::Value::String(s)) => text.push(s.clone()),
+        Some(serde_json::Value::Array(blocks)) => {
+            for block in blocks {
+                match block.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => text.extend(str_field(block, &["text"])),
+                    Some("tool_use" | "toolCall") => tool_parts.push(serde_json::json!({
+                        "type": "tool_use",
+                        "id": str_field(block, &["id"]).unwrap_or_default(),
+                        "name": str_field(block, &["name"]).unwrap_or_default(),
+                        "input": block.get("input").or_else(|| block.get("arguments")).cloned().unwrap_or_default(),
+                    })),
+                    Some("tool_result") => {
+                        tool_parts.push(tool_result(block, block.get("content")))
+                    }
+                    Some("image") => text.push(format!(
+                        "[image: {}]",
+                        str_field(block, &["media_type", "mimeType"]).unwrap_or_default()
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(Some(SessionMessage::new(role, text, tool_parts)))
+}
+
+/// Plain text of a session content value: a string, or the text blocks of an array.
+fn session_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| {
+                b.get("text")
+                    .and_then(|t| t.as_str())
+                    .or_else(|| b.as_str())
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Report non-migratable features
+// ---------------------------------------------------------------------------
+
+/// Warn about agents whose models use a provider missing from `models.providers`,
+/// and about configured providers no agent uses. Skipped when the map is absent.
+fn check_provider_references(root: &OpenClawRoot, report: &mut MigrationReport) {
+    let Some(configured) = root.models.as_ref().and_then(|m| m.providers.as_ref()) else {
+        return;
+    };
+    let configured: std::collections::BTreeMap<String, &String> =
+        configured.keys().map(|k| (map_provider(k), k)).collect();
+
+    let (list, defaults) = match root.agents.as_ref() {
+        Some(a) => (expand_subagents(&a.list, None), a.defaults.as_ref()),
+        None => (Vec::new(), None),
+    };
+    let auth_order = root.auth.as_ref().and_then(|a| a.order.as_ref());
+    let mut used = std::collections::BTreeSet::new();
+    for (entry, _) in &list {
+        let Some(primary) = extract_primary_model(entry, defaults) else {
+            continue;
+        };
+        let mut refs = vec![primary.clone()];
+        refs.extend(extract_fallback_models(
+            entry, defaults, auth_order, &primary,
+        ));
+        let mut missing = std::collections::BTreeSet::new();
+        for model_ref in &refs {
+            let provider = parse_model_ref(model_ref).provider;
+            if !configured.contains_key(&provider) {
+                missing.insert(provider.clone());
+            }
+            used.insert(provider);
+        }
+        for provider in missing {
+            report.warnings.push(format!(
+                "Agent '{}' uses provider '{provider}', which has no entry in models.providers",
+                entry.id
+            ));
+        }
+    }
+
+    for (provider, key) in &configured {
+        if !used.contains(provider) {
+            report.warnings.push(format!(
+                "models.providers defines '{key}', but no migrated agent uses it"
+            ));
+        }
+    }
+}
+
+/// Record features with no OpenFang counterpart. Files are only checked when
+/// there is a `source` workspace to look in.
+fn report_skipped_features(
+    root: &OpenClawRoot,
+    source: Option<&Path>,
+    report: &mut MigrationReport,
+) {
+    let exists = |rel: &str| source.is_some_and(|s| s.join(rel).exists());
+
+    // Cron jobs
+    if root.cron.is_some() {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "cron".to_string(),
+            reason: "Cron job scheduling not yet supported — use OpenFang's ScheduleMode::Periodic instead".to_string(),
+        });
+    }
+
+    // Hooks
+    if root.hooks.is_some() {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "hooks".to_string(),
+            reason: "Webhook hooks not supported — use OpenFang's event system instead".to_string(),
+        });
+    }
+
+    // Auth profiles
+    if let Some(ref auth) = root.auth {
+        if auth.profiles.is_some() {
+            report.skipped.push(SkippedItem {
+                kind: ItemKind::Config,
+                name: "auth-profiles".to_string(),
+                reason: "Auth profiles (API keys, OAuth tokens) not migrated for security — set env vars manually".to_string(),
+            });
+        }
+    }
+
+    // Skills entries not already copied as file-only skills
+    if let Some(ref skills) = root.skills {
+        if let Some(ref entries) = skills.entries {
+            let remaining = entries
+                .keys()
+                .filter(|name| {
+                    !report
+                        .imported
+                        .iter()
+                        .any(|i| i.kind == ItemKind::Skill && &i.name == *name)
+                })
+                .count();
+            if remaining > 0 {
+                report.skipped.push(SkippedItem {
+                    kind: ItemKind::Skill,
+                    name: format!("{remaining} skill entries"),
+                    reason: "Skills must be reinstalled via `openfang skill install`".to_string(),
+                });
+            }
+        }
+    }
+
+    // Cron state file
+    if exists("cron/cron-store.json") {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "cron-store.json".to_string(),
+            reason: "Cron run state not portable".to_string(),
+        });
+    }
+
+    // Auth profiles file
+    if exists("auth-profiles.json") {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "auth-profiles.json".to_string(),
+            reason: "Credential file not migrated for security — set API keys as env vars"
+                .to_string(),
+        });
+    }
+
+    // Session config
+    if root.session.is_some() {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "session".to_string(),
+            reason: "Session scope config differs — OpenFang uses per-agent sessions by default"
+                .to_string(),
+        });
+    }
+
+    // Memory backend config (location, scope and tunables are migrated)
+    let unmigrated_memory = match root.memory.as_ref() {
+        Some(serde_json::Value::Object(block)) => block.keys().any(|k| {
+            !MIGRATED_MEMORY_KEYS.contains(&k.as_str())
+                && !matches!(k.as_str(), "ttlDays" | "ttl_days")
+        }),
+        Some(_) => true,
+        None => false,
+    };
+    if unmigrated_memory {
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Config,
+            name: "memory".to_string(),
+            reason:
+                "Memory backend config not migrated — OpenFang uses SQLite with vector embeddings"
+                    .to_string(),
+        });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Legacy YAML migration (backward compat)
+// ---------------------------------------------------------------------------
 
 fn migrate_from_legacy_yaml(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
+    check_agent_renames(&legacy_agent_ids(source), options, report)?;
+    if options.strict {
+        check_strict_legacy(source, options)?;
+    }
+
     // Channel parsing
     let channels = parse_legacy_channels(source, target, dry_run, report)?;
 
     // Config migration
-    migrate_legacy_config(source, target, dry_run, channels, report)?;
+    migrate_legacy_config(source, target, options, channels, report)?;
 
     // Agent migration
-    migrate_legacy_agents(source, target, dry_run, report)?;
+    migrate_legacy_agents(source, target, options, report)?;
 
     // Memory migration
-    migrate_legacy_memory(source, target, dry_run, report)?;
+    migrate_legacy_memory(source, target, options, report)?;
 
     // Workspace migration
-    migrate_legacy_workspaces(source, target, dry_run, report)?;
+    migrate_legacy_workspaces(source, target, options, report)?;
 
     // Skill scanning
-    scan_legacy_skills(source, report);
+    scan_legacy_skills(source, target, options.dry_run, report);
 
     info!("Legacy YAML migration complete");
     Ok(())
@@ -2347,10 +6140,11 @@ fn migrate_from_legacy_yaml(
 fn migrate_legacy_config(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     channels: Option<toml::Value>,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let config_path = source.join("config.yaml");
     if !config_path.exists() {
         report
@@ -2363,15 +6157,26 @@ fn migrate_legacy_config(
     let oc_config: LegacyYamlConfig = serde_yaml::from_str(&yaml_str)
         .map_err(|e| MigrateError::ConfigParse(format!("config.yaml: {e}")))?;
 
-    let provider = map_provider(&oc_config.provider);
+    let (default_provider, default_model) = fallback_model(options);
+    let provider = oc_config
+        .provider
+        .map(|p| map_provider(&p))
+        .unwrap_or(default_provider);
     let api_key_env = oc_config
         .api_key_env
         .unwrap_or_else(|| default_api_key_env(&provider));
 
+    let mut model_notes = Vec::new();
+    let model = oc_config.model.unwrap_or(default_model);
+    let model = resolve_model_id(&provider, &model, options, &mut model_notes);
+    for note in model_notes {
+        report.warnings.push(format!("Default model: {note}"));
+    }
+
     let of_config = OpenFangConfig {
         default_model: OpenFangModelConfig {
             provider,
-            model: oc_config.model,
+            model,
             api_key_env,
             base_url: oc_config.base_url,
         },
@@ -2380,15 +6185,18 @@ fn migrate_legacy_config(
                 .memory
                 .as_ref()
                 .and_then(|m| m.decay_rate)
-                .unwrap_or(0.05),
+                .unwrap_or(DEFAULT_DECAY_RATE),
+            consolidation_threshold: None,
         },
         network: OpenFangNetworkSection {
             listen_addr: "127.0.0.1:4200".to_string(),
         },
         channels,
+        mcp_servers: Vec::new(),
     };
 
     let toml_str = toml::to_string_pretty(&of_config)?;
+    record_env_refs(&toml_str, report);
 
     let config_content = format!(
         "# OpenFang Agent OS configuration\n\
@@ -2397,7 +6205,8 @@ fn migrate_legacy_config(
         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
     );
 
-    let dest = target.join("config.toml");
+    let dest = target.join(&options.output_paths.config);
+    note_conflict(dry_run, &dest, report);
 
     if !dry_run {
         std::fs::create_dir_all(target)?;
@@ -2408,12 +6217,30 @@ fn migrate_legacy_config(
         kind: ItemKind::Config,
         name: "config.yaml".to_string(),
         destination: dest.display().to_string(),
+        details: None,
     });
 
-    info!("Migrated config.yaml -> config.toml");
+    info!("Migrated config.yaml -> {}", dest.display());
     Ok(())
 }
 
+/// Channel files (`messaging/<name>.yaml`) a legacy workspace may contain.
+const LEGACY_CHANNELS: &[&str] = &[
+    "telegram",
+    "discord",
+    "slack",
+    "whatsapp",
+    "signal",
+    "matrix",
+    "irc",
+    "mattermost",
+    "feishu",
+    "googlechat",
+    "msteams",
+    "imessage",
+    "bluebubbles",
+];
+
 fn parse_legacy_channels(
     source: &Path,
     target: &Path,
@@ -2430,21 +6257,7 @@ fn parse_legacy_channels(
     // so no secrets extraction needed. target/dry_run reserved for future use.
     let _ = (target, dry_run);
 
-    for name in &[
-        "telegram",
-        "discord",
-        "slack",
-        "whatsapp",
-        "signal",
-        "matrix",
-        "irc",
-        "mattermost",
-        "feishu",
-        "googlechat",
-        "msteams",
-        "imessage",
-        "bluebubbles",
-    ] {
+    for name in LEGACY_CHANNELS {
         let yaml_path = messaging_dir.join(format!("{name}.yaml"));
         if !yaml_path.exists() {
             continue;
@@ -2479,6 +6292,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "telegram".to_string(),
                     destination: "config.toml [channels.telegram]".to_string(),
+                    details: None,
                 });
             }
             "discord" => {
@@ -2498,6 +6312,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "discord".to_string(),
                     destination: "config.toml [channels.discord]".to_string(),
+                    details: None,
                 });
             }
             "slack" => {
@@ -2520,6 +6335,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "slack".to_string(),
                     destination: "config.toml [channels.slack]".to_string(),
+                    details: None,
                 });
             }
             "whatsapp" => {
@@ -2529,6 +6345,9 @@ fn parse_legacy_channels(
                     .unwrap_or_else(|| "WHATSAPP_ACCESS_TOKEN".to_string());
                 let fields: Vec<(&str, toml::Value)> =
                     vec![("access_token_env", toml::Value::String(token_env))];
+                if let Some(ref phone) = ch.phone_number {
+                    report.warnings.push(whatsapp_phone_warning(phone));
+                }
                 channels_table.insert(
                     "whatsapp".to_string(),
                     build_channel_table(fields, None, None, None),
@@ -2537,13 +6356,17 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "whatsapp".to_string(),
                     destination: "config.toml [channels.whatsapp]".to_string(),
+                    details: None,
                 });
             }
             "signal" => {
-                let fields: Vec<(&str, toml::Value)> = vec![(
+                let mut fields: Vec<(&str, toml::Value)> = vec![(
                     "api_url",
                     toml::Value::String("http://localhost:8080".into()),
                 )];
+                if let Some(ref phone) = ch.phone_number {
+                    fields.push(("phone_number", toml::Value::String(phone.clone())));
+                }
                 channels_table.insert(
                     "signal".to_string(),
                     build_channel_table(fields, None, None, None),
@@ -2552,6 +6375,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "signal".to_string(),
                     destination: "config.toml [channels.signal]".to_string(),
+                    details: None,
                 });
             }
             "matrix" => {
@@ -2569,6 +6393,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "matrix".to_string(),
                     destination: "config.toml [channels.matrix]".to_string(),
+                    details: None,
                 });
             }
             "irc" => {
@@ -2584,6 +6409,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "irc".to_string(),
                     destination: "config.toml [channels.irc]".to_string(),
+                    details: None,
                 });
             }
             "mattermost" => {
@@ -2600,6 +6426,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "mattermost".to_string(),
                     destination: "config.toml [channels.mattermost]".to_string(),
+                    details: None,
                 });
             }
             "feishu" => {
@@ -2615,6 +6442,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "feishu".to_string(),
                     destination: "config.toml [channels.feishu]".to_string(),
+                    details: None,
                 });
             }
             "googlechat" => {
@@ -2630,6 +6458,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "google_chat".to_string(),
                     destination: "config.toml [channels.google_chat]".to_string(),
+                    details: None,
                 });
             }
             "msteams" => {
@@ -2645,6 +6474,7 @@ fn parse_legacy_channels(
                     kind: ItemKind::Channel,
                     name: "teams".to_string(),
                     destination: "config.toml [channels.teams]".to_string(),
+                    details: None,
                 });
             }
             "imessage" => {
@@ -2676,9 +6506,10 @@ fn parse_legacy_channels(
 fn migrate_legacy_agents(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let agents_dir = source.join("agents");
     if !agents_dir.exists() {
         report
@@ -2686,6 +6517,9 @@ fn migrate_legacy_agents(
             .push("No agents/ directory found".to_string());
         return Ok(());
     }
+    let tool_map = ToolMap::load(options, &mut report.warnings)?;
+    let agent_dirs = AgentDirs::from_legacy(source, options);
+    agent_dirs.report_collisions(report);
 
     let entries = std::fs::read_dir(&agents_dir)?;
     for entry in entries {
@@ -2704,28 +6538,53 @@ fn migrate_legacy_agents(
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
+        if agent_dirs.skip_excluded(&agent_name, report) {
+            continue;
+        }
 
-        match convert_legacy_agent(&agent_yaml, &agent_name) {
-            Ok((toml_str, unmapped_tools)) => {
-                let dest_dir = target.join("agents").join(&agent_name);
-                let dest_file = dest_dir.join("agent.toml");
-
-                if !dry_run {
-                    std::fs::create_dir_all(&dest_dir)?;
-                    std::fs::write(&dest_file, &toml_str)?;
+        match convert_legacy_agent(&agent_yaml, &agent_name, options, &tool_map) {
+            Ok(mut converted) => {
+                let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+                    continue;
+                };
+                if reject_invalid_manifest(
+                    &agent_name,
+                    &agent_dir,
+                    &converted.toml,
+                    target,
+                    dry_run,
+                    report,
+                ) {
+                    continue;
                 }
+                let dest_dir = target.join("agents").join(agent_dir);
+                let dest_file = dest_dir.join("agent.toml");
+                let changed = write_manifest(&dest_file, &converted.toml, dry_run, report)?;
+                record_env_refs(&converted.toml, report);
+                converted.notes.data_sources = agent_data_sources(source, &agent_name);
+                write_agent_notes(&converted.notes, &dest_dir, options, report);
 
-                report.imported.push(MigrateItem {
+                let item = MigrateItem {
                     kind: ItemKind::Agent,
                     name: agent_name.clone(),
                     destination: dest_file.display().to_string(),
-                });
+                    details: None,
+                };
+                if changed {
+                    report.imported.push(item);
+                } else {
+                    report.unchanged.push(item);
+                }
 
-                for tool in &unmapped_tools {
+                for tool in &converted.unmapped_tools {
                     report.warnings.push(format!(
-                        "Agent '{agent_name}': tool '{tool}' has no OpenFang equivalent and was skipped"
+                        "Agent '{agent_name}': {}",
+                        unmapped_tool_warning(tool)
                     ));
                 }
+                for w in &converted.warnings {
+                    report.warnings.push(format!("Agent '{agent_name}': {w}"));
+                }
 
                 info!("Migrated agent: {agent_name}");
             }
@@ -2746,41 +6605,63 @@ fn migrate_legacy_agents(
 fn convert_legacy_agent(
     yaml_path: &Path,
     name: &str,
-) -> Result<(String, Vec<String>), MigrateError> {
+    options: &MigrateOptions,
+    tool_map: &ToolMap,
+) -> Result<ConvertedAgent, MigrateError> {
     let yaml_str = std::fs::read_to_string(yaml_path)?;
     let oc: LegacyYamlAgent = serde_yaml::from_str(&yaml_str)
         .map_err(|e| MigrateError::AgentParse(format!("{name}: {e}")))?;
 
+    let mut warnings = Vec::new();
+
     // Map tools
     let mut unmapped_tools = Vec::new();
     let tools: Vec<String> = if !oc.tools.is_empty() {
         let mut mapped = Vec::new();
-        for t in &oc.tools {
-            if is_known_openfang_tool(t) {
-                mapped.push(t.clone());
-            } else if let Some(of_name) = map_tool_name(t) {
-                mapped.push(of_name.to_string());
-            } else {
-                unmapped_tools.push(t.clone());
-            }
-        }
+        map_tool_list(
+            &oc.tools,
+            tool_map,
+            &mut mapped,
+            &mut unmapped_tools,
+            &mut warnings,
+        );
         mapped
     } else if let Some(ref profile) = oc.tool_profile {
+        warnings.extend(unknown_profile_warning(profile));
         tools_for_profile(profile)
     } else {
         vec!["file_read".into(), "file_list".into(), "web_fetch".into()]
     };
 
-    let caps = derive_capabilities(&tools);
+    let mut caps = derive_capabilities(&tools);
 
+    let (default_provider, default_model) = fallback_model(options);
     let provider = oc
         .provider
         .map(|p| map_provider(&p))
-        .unwrap_or_else(|| "anthropic".to_string());
+        .unwrap_or(default_provider);
+    if let Some(note) = provider_credential_note(&provider) {
+        warnings.push(note.to_string());
+    }
 
-    let model = oc
-        .model
-        .unwrap_or_else(|| "claude-sonnet-4-20250514".to_string());
+    let primary_origin = if oc.model.is_some() {
+        "set in agent.yaml"
+    } else {
+        "nothing configured; migration default"
+    };
+    let model = oc.model.unwrap_or(default_model);
+    let before = warnings.len();
+    let model = resolve_model_id(&provider, &model, options, &mut warnings);
+    let notes = AgentNotes {
+        source_id: name.to_string(),
+        primary_model: format!("{provider}/{model}"),
+        primary_origin,
+        model_notes: warnings[before..].to_vec(),
+        tools: tools.clone(),
+        unmapped_tools: unmapped_tools.clone(),
+        capabilities: caps.summary(),
+        ..Default::default()
+    };
 
     let system_prompt = oc.system_prompt.unwrap_or_else(|| {
         format!(
@@ -2803,72 +6684,50 @@ fn convert_legacy_agent(
         }
     });
 
-    let mut toml_str = String::new();
-    toml_str.push_str(&format!(
-        "# OpenFang agent manifest\n# Migrated from OpenClaw agent '{}'\n\n",
-        oc.name
-    ));
-    toml_str.push_str(&format!("name = \"{}\"\n", oc.name));
-    toml_str.push_str("version = \"0.1.0\"\n");
-    toml_str.push_str(&format!(
-        "description = \"{}\"\n",
-        oc.description.replace('"', "\\\"")
-    ));
-    toml_str.push_str("author = \"openfang\"\n");
-    toml_str.push_str("module = \"builtin:chat\"\n");
-
-    if !oc.tags.is_empty() {
-        let tags_str: Vec<String> = oc.tags.iter().map(|t| format!("\"{t}\"")).collect();
-        toml_str.push_str(&format!("tags = [{}]\n", tags_str.join(", ")));
-    }
-
-    toml_str.push_str("\n[model]\n");
-    toml_str.push_str(&format!("provider = \"{provider}\"\n"));
-    toml_str.push_str(&format!("model = \"{model}\"\n"));
-    toml_str.push_str(&format!(
-        "system_prompt = \"\"\"\n{system_prompt}\n\"\"\"\n"
-    ));
+    // A name that just repeats the agent's directory follows any configured rename
+    let display_name = if oc.name == name {
+        renamed_id(name, &options.agent_renames)
+    } else {
+        &oc.name
+    };
 
-    if let Some(ref api_key) = api_key_env {
-        toml_str.push_str(&format!("api_key_env = \"{api_key}\"\n"));
-    }
-    if let Some(base_url) = oc.base_url {
-        toml_str.push_str(&format!("base_url = \"{base_url}\"\n"));
-    }
+    caps.tools = tools;
 
-    toml_str.push_str("\n[capabilities]\n");
-    let tools_str: Vec<String> = tools.iter().map(|t| format!("\"{t}\"")).collect();
-    toml_str.push_str(&format!("tools = [{}]\n", tools_str.join(", ")));
-    toml_str.push_str("memory_read = [\"*\"]\n");
-    toml_str.push_str("memory_write = [\"self.*\"]\n");
-
-    if !caps.network.is_empty() {
-        let net_str: Vec<String> = caps.network.iter().map(|n| format!("\"{n}\"")).collect();
-        toml_str.push_str(&format!("network = [{}]\n", net_str.join(", ")));
-    }
-    if !caps.shell.is_empty() {
-        let shell_str: Vec<String> = caps.shell.iter().map(|s| format!("\"{s}\"")).collect();
-        toml_str.push_str(&format!("shell = [{}]\n", shell_str.join(", ")));
-    }
-    if !caps.agent_message.is_empty() {
-        let msg_str: Vec<String> = caps
-            .agent_message
-            .iter()
-            .map(|m| format!("\"{m}\""))
-            .collect();
-        toml_str.push_str(&format!("agent_message = [{}]\n", msg_str.join(", ")));
-    }
-    if caps.agent_spawn {
-        toml_str.push_str("agent_spawn = true\n");
-    }
-
-    Ok((toml_str, unmapped_tools))
-}
+    let mut manifest = AgentManifest::new(
+        display_name,
+        oc.description,
+        ManifestModel {
+            provider,
+            model,
+            system_prompt: Some(system_prompt),
+            api_key_env,
+            base_url: oc.base_url,
+            params: ModelParams::default(),
+        },
+    );
+    manifest.tags = oc.tags;
+    manifest.set_tool_profile(oc.tool_profile);
+    manifest.capabilities = caps;
+    manifest.metadata = source_id_metadata(name, Some(&oc.name));
+
+    let mut toml_str = manifest.to_toml(&oc.name)?;
+    push_unmapped_tools_comment(&mut toml_str, &unmapped_tools);
+
+    Ok(ConvertedAgent {
+        toml: toml_str,
+        unmapped_tools,
+        warnings,
+        skipped: Vec::new(),
+        secret: None,
+        memory_scope: None,
+        notes,
+    })
+}
 
 fn migrate_legacy_memory(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
     let agents_dir = source.join("agents");
@@ -2876,6 +6735,7 @@ fn migrate_legacy_memory(
         return Ok(());
     }
 
+    let agent_dirs = AgentDirs::from_legacy(source, options);
     let entries = std::fs::read_dir(&agents_dir)?;
     for entry in entries {
         let entry = entry?;
@@ -2894,40 +6754,646 @@ fn migrate_legacy_memory(
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
-        let content = std::fs::read_to_string(&memory_md)?;
+        let Some(content) = read_memory_text(&memory_md, report) else {
+            continue;
+        };
         if content.trim().is_empty() {
             continue;
         }
 
-        let dest_dir = target.join("agents").join(&agent_name);
-        let dest_file = dest_dir.join("imported_memory.md");
+        let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+            continue;
+        };
 
-        if !dry_run {
-            std::fs::create_dir_all(&dest_dir)?;
-            std::fs::write(&dest_file, &content)?;
-        }
+        let dest_dir = target.join("agents").join(agent_dir);
+        let (dest_file, details) =
+            write_imported_memory(&dest_dir, &content, &agent_name, options, report)?;
 
         report.imported.push(MigrateItem {
             kind: ItemKind::Memory,
             name: format!("{agent_name}/MEMORY.md"),
             destination: dest_file.display().to_string(),
+            details: Some(details),
         });
     }
 
     Ok(())
 }
 
+/// When a session log was last active: its `newest` message timestamp, or the
+/// file's modification time when no line carries one.
+fn session_time(
+    path: &Path,
+    newest: Option<chrono::DateTime<chrono::Utc>>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    newest.or_else(|| {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        Some(modified.into())
+    })
+}
+
+/// Timestamp of the newest message in a JSONL session log.
+///
+/// Reads the `ts` or `timestamp` field of each line, as RFC 3339 text or Unix
+/// seconds/milliseconds. Only the tail of the file is read.
+fn newest_message_time(path: &Path) -> Option<chrono::DateTime<chrono::Utc>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    const TAIL_BYTES: u64 = 64 * 1024;
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    newest_line_time(&String::from_utf8_lossy(&tail))
+}
+
+/// The newest timestamp on any line of session log text. Lines that are not JSON,
+/// such as one cut in half by the tail read, are ignored.
+fn newest_line_time(text: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    text.lines()
+        .filter_map(|l| serde_json::from_str::<serde_json::Value>(l).ok())
+        .filter_map(|record| record_time(&record))
+        .max()
+}
+
+/// Decompressed size allowed for one session archive when no `sessions_size_limit`
+/// budget caps it.
+const MAX_SESSION_ARCHIVE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Decompress a gzipped session archive, giving up once it inflates past `limit`
+/// bytes so a gzip bomb cannot exhaust memory.
+fn read_gzip(path: &Path, limit: u64) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut data = Vec::new();
+    flate2::read::GzDecoder::new(std::fs::File::open(path)?)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(archive_too_large(limit));
+    }
+    Ok(data)
+}
+
+/// Stream a gzipped session archive for its decompressed size and, if `with_time`,
+/// its newest message time, without holding it in memory. Fails past `limit`
+/// bytes like [`read_gzip`].
+fn scan_gzip(
+    path: &Path,
+    limit: u64,
+    with_time: bool,
+) -> std::io::Result<(u64, Option<chrono::DateTime<chrono::Utc>>)> {
+    use std::io::{BufRead, Read};
+    let mut reader = std::io::BufReader::new(
+        flate2::read::GzDecoder::new(std::fs::File::open(path)?).take(limit.saturating_add(1)),
+    );
+    let (mut size, mut newest) = (0u64, None);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        size += n as u64;
+        if with_time {
+            newest = newest.max(newest_line_time(&String::from_utf8_lossy(&line)));
+        }
+    }
+    if size > limit {
+        return Err(archive_too_large(limit));
+    }
+    Ok((size, newest))
+}
+
+fn archive_too_large(limit: u64) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("decompresses to more than {}", format_bytes(limit)),
+    )
+}
+
+/// How a skipped session file is counted: `.jsonl.gz`, `.json`, `.jsonl~`, ...
+fn session_extension(file_name: &str) -> String {
+    if file_name.ends_with(".jsonl.gz") {
+        return ".jsonl.gz".to_string();
+    }
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!(".{ext}"),
+        _ => "(no extension)".to_string(),
+    }
+}
+
+/// The `ts`/`timestamp` of a session record, as RFC 3339 or epoch seconds/milliseconds.
+fn record_time(record: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    let ts = record.get("ts").or_else(|| record.get("timestamp"))?;
+    match ts {
+        serde_json::Value::String(text) => chrono::DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc)),
+        serde_json::Value::Number(n) => {
+            let n = n.as_i64()?;
+            // Values this large are milliseconds (seconds would be past year 5000)
+            if n > 100_000_000_000 {
+                chrono::DateTime::from_timestamp_millis(n)
+            } else {
+                chrono::DateTime::from_timestamp(n, 0)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Read a memory file as text. A UTF-8 BOM is stripped, UTF-16 with a BOM is
+/// converted, and invalid bytes become U+FFFD, with a warning giving the count.
+/// An unreadable file is warned about and returns `None` rather than failing the run.
+fn read_memory_text(path: &Path, report: &mut MigrationReport) -> Option<String> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            report.warnings.push(format!(
+                "Could not read {}: {e}; it was not migrated",
+                path.display()
+            ));
+            return None;
+        }
+    };
+
+    let utf16 = |body: &[u8], unit: fn([u8; 2]) -> u16| {
+        let mut bad = body.len() % 2;
+        let text: String =
+            char::decode_utf16(body.chunks_exact(2).map(|pair| unit([pair[0], pair[1]])))
+                .map(|c| {
+                    c.unwrap_or_else(|_| {
+                        bad += 1;
+                        char::REPLACEMENT_CHARACTER
+                    })
+                })
+                .collect();
+        (text, bad)
+    };
+    let (text, substituted, encoding) = match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            let (text, bad) = decode_utf8_lossy(rest);
+            (text, bad, "UTF-8")
+        }
+        [0xFF, 0xFE, rest @ ..] => {
+            let (text, bad) = utf16(rest, u16::from_le_bytes);
+            (text, bad, "UTF-16LE")
+        }
+        [0xFE, 0xFF, rest @ ..] => {
+            let (text, bad) = utf16(rest, u16::from_be_bytes);
+            (text, bad, "UTF-16BE")
+        }
+        rest => {
+            let (text, bad) = decode_utf8_lossy(rest);
+            (text, bad, "UTF-8")
+        }
+    };
+    if encoding != "UTF-8" {
+        report.warnings.push(format!(
+            "{} was converted from {encoding} ({substituted} invalid characters replaced)",
+            path.display()
+        ));
+    } else if substituted > 0 {
+        report.warnings.push(format!(
+            "{} is not valid UTF-8; replaced {substituted} invalid byte sequences with U+FFFD",
+            path.display()
+        ));
+    }
+    Some(text)
+}
+
+/// Decode UTF-8, replacing each invalid sequence with U+FFFD. Returns the text and
+/// the number of replacements.
+fn decode_utf8_lossy(mut bytes: &[u8]) -> (String, usize) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut bad = 0;
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                text.push_str(valid);
+                return (text, bad);
+            }
+            Err(e) => {
+                let (valid, after) = bytes.split_at(e.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                text.push(char::REPLACEMENT_CHARACTER);
+                bad += 1;
+                bytes = &after[e.error_len().unwrap_or(after.len())..];
+            }
+        }
+    }
+}
+
+/// Soft size limit for one imported memory entry, in bytes.
+const MEMORY_ENTRY_MAX_BYTES: usize = 2048;
+
+/// One recall-sized piece of an OpenClaw `MEMORY.md`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct MemoryEntry {
+    /// Heading of the section the entry came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tag: Option<String>,
+    content: String,
+}
+
+/// Size of one `imported_memory_NNN.md` chunk of an oversized MEMORY.md.
+const MEMORY_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Write an agent's `MEMORY.md` as `imported_memory.md`, plus `imported_memory.jsonl`
+/// holding its [`MemoryEntry`] split. Returns the (first) markdown destination and
+/// the size of what was carried over, which is computed in dry-run too.
+///
+/// Files over `options.memory_size_limit` are cut at headings into
+/// `imported_memory_001.md`, ... and only the most recent chunks that fit the
+/// limit are written, with a warning. Markdown files are written through
+/// [`write_generated_markdown`], so local edits survive a re-run; when one is
+/// kept, the new entries go to `imported_memory.new.jsonl` instead.
+///
+/// YAML front-matter is stripped: recognized keys are added to every structured
+/// entry and the rest is kept in an HTML comment at the top of the markdown.
+fn write_imported_memory(
+    dest_dir: &Path,
+    content: &str,
+    agent: &str,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(PathBuf, ItemDetails), MigrateError> {
+    let (front_matter, content) = match split_front_matter(content) {
+        Some((front_matter, body)) => (Some(front_matter), body),
+        None => (None, content),
+    };
+    let files: Vec<(String, String)> = match options.memory_size_limit {
+        Some(limit) if content.len() as u64 > limit => {
+            let chunk_size = usize::try_from(limit)
+                .unwrap_or(usize::MAX)
+                .clamp(1, MEMORY_CHUNK_BYTES);
+            let chunks = chunk_memory(content, chunk_size);
+            // Appended logs put the newest notes at the end
+            let mut budget = limit;
+            let mut start = chunks.len();
+            while start > 0 && chunks[start - 1].len() as u64 <= budget {
+                start -= 1;
+                budget -= chunks[start].len() as u64;
+            }
+            let kept = &chunks[start..];
+            let kept_bytes: usize = kept.iter().map(String::len).sum();
+            report.warnings.push(format!(
+                "Memory for '{agent}' is {}, over the {} limit; kept the most recent {} of {} chunks ({})",
+                format_bytes(content.len() as u64),
+                format_bytes(limit),
+                kept.len(),
+                chunks.len(),
+                format_bytes(kept_bytes as u64)
+            ));
+            kept.iter()
+                .enumerate()
+                .map(|(i, chunk)| (format!("imported_memory_{:03}.md", i + 1), chunk.clone()))
+                .collect()
+        }
+        _ => vec![("imported_memory.md".to_string(), content.to_string())],
+    };
+
+    let mut details = ItemDetails::default();
+    let mut jsonl = String::new();
+    for (_, text) in &files {
+        details.bytes += text.len() as u64;
+        details.lines += text.lines().count();
+        for entry in split_memory_markdown(text) {
+            details.entries += 1;
+            let mut value = serde_json::to_value(&entry).unwrap_or_default();
+            if let (Some(front_matter), Some(map)) = (&front_matter, value.as_object_mut()) {
+                map.extend(front_matter.fields.clone());
+            }
+            jsonl.push_str(&value.to_string());
+            jsonl.push('\n');
+        }
+    }
+
+    let mut written = Vec::new();
+    if !options.dry_run {
+        std::fs::create_dir_all(dest_dir)?;
+        for (i, (name, text)) in files.iter().enumerate() {
+            let markdown = match front_matter.as_ref().and_then(|f| f.extra.as_ref()) {
+                Some(extra) if i == 0 => format!("<!-- front-matter\n{extra}-->\n\n{text}"),
+                _ => text.clone(),
+            };
+            written.push(write_generated_markdown(
+                &dest_dir.join(name),
+                &markdown,
+                report,
+            )?);
+        }
+        // The entries follow the markdown: if an edited file was kept, so is its split
+        let kept_edits = written
+            .iter()
+            .zip(&files)
+            .any(|(path, (name, _))| *path != dest_dir.join(name));
+        let jsonl_path = dest_dir.join("imported_memory.jsonl");
+        if kept_edits && jsonl_path.exists() {
+            let new_path = dest_dir.join("imported_memory.new.jsonl");
+            std::fs::write(&new_path, jsonl)?;
+            report.warnings.push(format!(
+                "Kept {} to match the edited memory; wrote the new entries to {}",
+                jsonl_path.display(),
+                new_path.display()
+            ));
+        } else {
+            std::fs::write(jsonl_path, jsonl)?;
+        }
+    }
+    let dest_file = written
+        .into_iter()
+        .next()
+        .or_else(|| files.first().map(|(name, _)| dest_dir.join(name)))
+        .unwrap_or_else(|| dest_dir.join("imported_memory.md"));
+    Ok((dest_file, details))
+}
+
+/// Front-matter keys copied onto structured memory entries.
+const FRONT_MATTER_KEYS: &[&str] = &["updated", "priority", "tags"];
+
+/// YAML front-matter read off the top of a MEMORY.md.
+#[derive(Debug, Default, PartialEq)]
+struct FrontMatter {
+    /// Recognized keys ([`FRONT_MATTER_KEYS`]) as JSON; `tags` is always a list.
+    fields: serde_json::Map<String, serde_json::Value>,
+    /// Any other keys, re-serialized as YAML.
+    extra: Option<String>,
+}
+
+/// Split a leading `---` YAML front-matter block off `content`. Returns `None`, so the
+/// content is kept as-is, unless the block is closed and parses as a YAML mapping.
+fn split_front_matter(content: &str) -> Option<(FrontMatter, &str)> {
+    let rest = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    let (yaml, body) = loop {
+        let line_end = rest[offset..].find('\n').map(|i| offset + i + 1);
+        let line = &rest[offset..line_end.unwrap_or(rest.len())];
+        if matches!(line.trim_end(), "---" | "...") {
+            break (&rest[..offset], &rest[line_end.unwrap_or(rest.len())..]);
+        }
+        offset = line_end?;
+    };
+    let mapping: serde_yaml::Mapping = serde_yaml::from_str(yaml).ok()?;
+
+    let mut front_matter = FrontMatter::default();
+    let mut extra = serde_yaml::Mapping::new();
+    for (key, value) in mapping {
+        let name = key.as_str().filter(|k| FRONT_MATTER_KEYS.contains(k));
+        let json = serde_json::to_value(&value).ok();
+        match (name, json) {
+            (Some("tags"), Some(serde_json::Value::String(tags))) => {
+                let tags = tags
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(|t| serde_json::Value::String(t.to_string()))
+                    .collect();
+                front_matter
+                    .fields
+                    .insert("tags".to_string(), serde_json::Value::Array(tags));
+            }
+            (Some(name), Some(json)) => {
+                front_matter.fields.insert(name.to_string(), json);
+            }
+            _ => {
+                extra.insert(key, value);
+            }
+        }
+    }
+    if !extra.is_empty() {
+        front_matter.extra = serde_yaml::to_string(&extra).ok();
+    }
+    Some((front_matter, body.trim_start_matches(['\r', '\n'])))
+}
+
+/// First line of generated markdown, recording the SHA-256 of the body below it.
+const PROVENANCE_PREFIX: &str = "<!-- openfang-migrate sha256:";
+
+fn sha256_hex(text: &str) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// Split generated markdown into the hash from its provenance line (if any) and its body.
+fn split_provenance(text: &str) -> (Option<&str>, &str) {
+    text.strip_prefix(PROVENANCE_PREFIX)
+        .and_then(|rest| rest.split_once(" -->\n"))
+        .map_or((None, text), |(hash, body)| (Some(hash), body))
+}
+
+/// Write migration output `body` to `path` behind a provenance line. An existing file
+/// that matches is left alone; one still matching its own provenance hash is replaced.
+/// A file edited since the last run is kept, and the new version goes to
+/// `<stem>.new.md` with a warning. Returns the path written (or left in place).
+fn write_generated_markdown(
+    path: &Path,
+    body: &str,
+    report: &mut MigrationReport,
+) -> Result<PathBuf, MigrateError> {
+    let generated = format!("{PROVENANCE_PREFIX}{} -->\n{body}", sha256_hex(body));
+    let Ok(existing) = std::fs::read_to_string(path) else {
+        std::fs::write(path, generated)?;
+        return Ok(path.to_path_buf());
+    };
+    let (hash, current) = split_provenance(&existing);
+    if current == body {
+        if hash.is_none() {
+            std::fs::write(path, generated)?;
+        }
+        return Ok(path.to_path_buf());
+    }
+    if hash.is_some_and(|h| h == sha256_hex(current)) {
+        std::fs::write(path, generated)?;
+        return Ok(path.to_path_buf());
+    }
+
+    let new_path = path.with_extension("new.md");
+    std::fs::write(&new_path, generated)?;
+    report.warnings.push(format!(
+        "{} was edited since it was migrated; kept it and wrote the new import to {}",
+        path.display(),
+        new_path.display()
+    ));
+    Ok(new_path)
+}
+
+/// Cut markdown into chunks of at most `max` bytes, breaking before headings where
+/// possible (never inside a code fence) and on line boundaries otherwise.
+fn chunk_memory(content: &str, max: usize) -> Vec<String> {
+    let mut sections = Vec::new();
+    let mut current = String::new();
+    let mut in_fence = false;
+    for line in content.lines() {
+        let fence = is_fence_line(line);
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        let heading = !in_fence
+            && (1..=6).contains(&hashes)
+            && (line.len() == hashes || line[hashes..].starts_with(' '));
+        if heading && !current.is_empty() {
+            sections.push(std::mem::take(&mut current));
+        }
+        in_fence ^= fence;
+        current.push_str(line);
+        current.push('\n');
+    }
+    sections.push(current);
+
+    let mut chunks = Vec::new();
+    let mut chunk = String::new();
+    for section in sections {
+        if !chunk.is_empty() && chunk.len() + section.len() > max {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if section.len() > max {
+            chunks.extend(chunk_text(&section, max));
+        } else {
+            chunk.push_str(&section);
+        }
+    }
+    chunks.push(chunk);
+    chunks.retain(|c| !c.trim().is_empty());
+    chunks
+}
+
+/// Split a `MEMORY.md` into entries, one per `#`/`##` section tagged with its heading.
+///
+/// Sections over [`MEMORY_ENTRY_MAX_BYTES`] are split per top-level bullet, and
+/// anything still too long is chunked on line boundaries. Lines inside code
+/// fences never start a section or a bullet. Text before the first heading (or
+/// a file without headings) becomes an untagged entry.
+fn split_memory_markdown(content: &str) -> Vec<MemoryEntry> {
+    let mut sections: Vec<(Option<String>, Vec<&str>)> = vec![(None, Vec::new())];
+    let mut in_fence = false;
+    for line in content.lines() {
+        let fence = is_fence_line(line);
+        if !in_fence && !fence {
+            if let Some(heading) = heading_text(line) {
+                sections.push((Some(heading), Vec::new()));
+                continue;
+            }
+        }
+        in_fence ^= fence;
+        if let Some((_, lines)) = sections.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    let mut entries = Vec::new();
+    for (tag, lines) in sections {
+        let text = lines.join("\n");
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let pieces = if text.len() <= MEMORY_ENTRY_MAX_BYTES {
+            vec![text.to_string()]
+        } else {
+            split_bullets(&lines)
+                .iter()
+                .flat_map(|item| chunk_text(item, MEMORY_ENTRY_MAX_BYTES))
+                .collect()
+        };
+        entries.extend(pieces.into_iter().map(|content| MemoryEntry {
+            tag: tag.clone(),
+            content,
+        }));
+    }
+    entries
+}
+
+/// Text of a level 1 or 2 ATX heading (`# Title`, `## Title ##`).
+fn heading_text(line: &str) -> Option<String> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[hashes..];
+    if !(1..=2).contains(&hashes) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// Whether a line opens or closes a fenced code block.
+fn is_fence_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Group lines into top-level bullet items; lines before the first bullet form their own item.
+fn split_bullets(lines: &[&str]) -> Vec<String> {
+    let is_bullet = |line: &str| {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        ["- ", "* ", "+ "].iter().any(|b| line.starts_with(b))
+            || (digits > 0
+                && (line[digits..].starts_with(". ") || line[digits..].starts_with(") ")))
+    };
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_fence = false;
+    for line in lines {
+        let fence = is_fence_line(line);
+        if !in_fence && !fence && is_bullet(line) && !current.trim().is_empty() {
+            items.push(std::mem::take(&mut current));
+        }
+        in_fence ^= fence;
+        current.push_str(line);
+        current.push('\n');
+    }
+    items.push(current);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Cut text into pieces of at most `max` bytes, preferring line, then word, boundaries.
+fn chunk_text(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if !current.is_empty() && current.len() + 1 + line.len() > max {
+            chunks.push(std::mem::take(&mut current));
+        }
+        let mut line = line;
+        while line.len() > max {
+            let mut cut = max;
+            while !line.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            if let Some(space) = line[..cut].rfind(' ').filter(|&i| i > 0) {
+                cut = space + 1;
+            }
+            chunks.push(line[..cut].trim_end().to_string());
+            line = &line[cut..];
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    chunks.push(current);
+    chunks.retain(|c| !c.trim().is_empty());
+    chunks
+}
+
 fn migrate_legacy_workspaces(
     source: &Path,
     target: &Path,
-    dry_run: bool,
+    options: &MigrateOptions,
     report: &mut MigrationReport,
 ) -> Result<(), MigrateError> {
+    let dry_run = options.dry_run;
     let agents_dir = source.join("agents");
     if !agents_dir.exists() {
         return Ok(());
     }
 
+    let agent_dirs = AgentDirs::from_legacy(source, options);
     let entries = std::fs::read_dir(&agents_dir)?;
     for entry in entries {
         let entry = entry?;
@@ -2956,23 +7422,28 @@ fn migrate_legacy_workspaces(
             continue;
         }
 
-        let dest_dir = target.join("agents").join(&agent_name).join("workspace");
+        let Some(agent_dir) = agent_dirs.dir_for(&agent_name) else {
+            continue;
+        };
+
+        let dest_dir = target.join("agents").join(agent_dir).join("workspace");
 
         if !dry_run {
-            copy_dir_recursive(&workspace_dir, &dest_dir)?;
+            copy_workspace(&workspace_dir, &dest_dir, options, report)?;
         }
 
         report.imported.push(MigrateItem {
             kind: ItemKind::Session,
             name: format!("{agent_name}/workspace ({file_count} files)"),
             destination: dest_dir.display().to_string(),
+            details: None,
         });
     }
 
     Ok(())
 }
 
-fn scan_legacy_skills(source: &Path, report: &mut MigrationReport) {
+fn scan_legacy_skills(source: &Path, target: &Path, dry_run: bool, report: &mut MigrationReport) {
     let skills_dir = source.join("skills");
     if !skills_dir.exists() {
         return;
@@ -3000,6 +7471,8 @@ fn scan_legacy_skills(source: &Path, report: &mut MigrationReport) {
                         reason: "Node.js skill — run with `openfang skill install` after migration"
                             .to_string(),
                     });
+                } else if is_file_skill(&path) {
+                    import_file_skill(&path, &name, target, dry_run, report);
                 } else {
                     report.skipped.push(SkippedItem {
                         kind: ItemKind::Skill,
@@ -3015,64 +7488,880 @@ fn scan_legacy_skills(source: &Path, report: &mut MigrationReport) {
     scan_subdir(&skills_dir.join("custom"));
 }
 
+/// Copy the file-only skills listed in `skills.entries` (see [`is_file_skill`]).
+///
+/// Other entries stay in the skipped summary written by `report_skipped_features`.
+fn migrate_file_skills(
+    source: &Path,
+    root: &OpenClawRoot,
+    target: &Path,
+    dry_run: bool,
+    report: &mut MigrationReport,
+) {
+    let Some(entries) = root.skills.as_ref().and_then(|s| s.entries.as_ref()) else {
+        return;
+    };
+    let skills_dir = source.join("skills");
+    for name in entries.keys() {
+        // Keys become paths on both sides; `..` or an absolute path would escape them
+        let mut components = Path::new(name).components();
+        let plain = matches!(components.next(), Some(std::path::Component::Normal(_)))
+            && components.next().is_none();
+        if !plain {
+            report.skipped.push(SkippedItem {
+                kind: ItemKind::Skill,
+                name: name.clone(),
+                reason: "Skill name is not a plain directory name".to_string(),
+            });
+            continue;
+        }
+        let dir = ["", "community", "custom"]
+            .iter()
+            .map(|sub| skills_dir.join(sub).join(name))
+            .find(|dir| dir.is_dir());
+        if let Some(dir) = dir.filter(|d| is_file_skill(d)) {
+            import_file_skill(&dir, name, target, dry_run, report);
+        }
+    }
+}
+
+/// Whether a skill is plain markdown/prompt files (no `package.json`) and can be copied as-is.
+fn is_file_skill(dir: &Path) -> bool {
+    !dir.join("package.json").exists()
+        && walkdir::WalkDir::new(dir).into_iter().flatten().any(|e| {
+            e.file_type().is_file()
+                && e.path().extension().is_some_and(|ext| {
+                    ["md", "txt", "prompt"]
+                        .iter()
+                        .any(|known| ext.eq_ignore_ascii_case(known))
+                })
+        })
+}
+
+/// Copy a file-only skill into `target/skills/<name>/` and record it as imported.
+fn import_file_skill(
+    dir: &Path,
+    name: &str,
+    target: &Path,
+    dry_run: bool,
+    report: &mut MigrationReport,
+) {
+    let dest = target.join("skills").join(name);
+    if !dry_run {
+        match copy_dir_recursive(dir, &dest) {
+            Ok(bytes) => report.bytes_copied += bytes,
+            Err(e) => {
+                report.skipped.push(SkippedItem {
+                    kind: ItemKind::Skill,
+                    name: name.to_string(),
+                    reason: format!("Failed to copy skill files: {e}"),
+                });
+                return;
+            }
+        }
+    }
+    report.imported.push(MigrateItem {
+        kind: ItemKind::Skill,
+        name: name.to_string(),
+        destination: dest.display().to_string(),
+        details: None,
+    });
+}
+
 // ---------------------------------------------------------------------------
 // Shared utilities
 // ---------------------------------------------------------------------------
 
-/// Recursively copy a directory.
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), std::io::Error> {
-    std::fs::create_dir_all(dst)?;
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
+/// Match `value` against a pattern where `*` matches any run of characters and
+/// `?` a single character.
+fn glob_matches(pattern: &str, value: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let v: Vec<char> = value.chars().collect();
+    let (mut pi, mut vi) = (0, 0);
+    // Position of the last `*` and the value index it was tried at
+    let mut star: Option<(usize, usize)> = None;
+    while vi < v.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == v[vi]) {
+            pi += 1;
+            vi += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, vi));
+            pi += 1;
+        } else if let Some((sp, sv)) = star {
+            pi = sp + 1;
+            vi = sv + 1;
+            star = Some((sp, sv + 1));
         } else {
-            std::fs::copy(&src_path, &dst_path)?;
+            return false;
         }
     }
-    Ok(())
+    p[pi..].iter().all(|&c| c == '*')
 }
 
-// ---------------------------------------------------------------------------
-// Tests
-// ---------------------------------------------------------------------------
+/// Sanitize an OpenClaw agent id into a directory name that is safe on every platform.
+///
+/// Lowercases, replaces path separators, whitespace and other reserved characters
+/// with `-`, collapses repeats and trims the ends. Never returns an empty string.
+fn sanitize_agent_id(id: &str) -> String {
+    let mut out = String::with_capacity(id.len());
+    for c in id.chars().flat_map(char::to_lowercase) {
+        let safe = c.is_alphanumeric() || matches!(c, '_' | '.' | '-');
+        let c = if safe { c } else { '-' };
+        if c == '-' && out.ends_with('-') {
+            continue;
+        }
+        out.push(c);
+    }
+    let trimmed = out.trim_matches(|c| c == '-' || c == '.');
+    if trimmed.is_empty() {
+        "agent".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+/// Mapping from OpenClaw agent ids to the directory names used under `agents/`.
+#[derive(Debug, Default)]
+struct AgentDirs {
+    policy: DuplicateAgentPolicy,
+    renames: std::collections::HashMap<String, String>,
+    dirs: std::collections::HashMap<String, String>,
+    taken: std::collections::HashMap<String, String>,
+    /// `(original id, assigned dir or None if skipped, id already owning the base dir)`.
+    collisions: Vec<(String, Option<String>, String)>,
+    include: Option<Vec<String>>,
+    exclude: Vec<String>,
+    selection: Option<Vec<String>>,
+}
 
-    // ===== Helper: create legacy YAML workspace =====
+impl AgentDirs {
+    /// Assign directories in order. An id that sanitizes to a directory already
+    /// taken gets a `-2`, `-3`, ... suffix, or no directory at all under
+    /// [`DuplicateAgentPolicy::Skip`].
+    fn from_ids<'a>(ids: impl IntoIterator<Item = &'a str>, options: &MigrateOptions) -> Self {
+        let mut map = Self {
+            policy: options.duplicate_agents,
+            renames: options.agent_renames.clone(),
+            include: options.include_agents.clone(),
+            selection: options.agent_ids.clone(),
+            exclude: options.exclude_agents.clone(),
+            ..Self::default()
+        };
+        for id in ids {
+            if map.dirs.contains_key(id)
+                || map.collisions.iter().any(|(c, _, _)| c == id)
+                || map.is_excluded(id)
+            {
+                continue;
+            }
+            let base = map.base_dir(id);
+            match map.taken.get(&base).cloned() {
+                None => {
+                    map.taken.insert(base.clone(), id.to_string());
+                    map.dirs.insert(id.to_string(), base);
+                }
+                Some(owner) => {
+                    let dir = match map.policy {
+                        DuplicateAgentPolicy::Rename => {
+                            let dir = map.claim_suffixed(&base, id);
+                            map.dirs.insert(id.to_string(), dir.clone());
+                            Some(dir)
+                        }
+                        DuplicateAgentPolicy::Skip => None,
+                    };
+                    map.collisions.push((id.to_string(), dir, owner));
+                }
+            }
+        }
+        map
+    }
 
-    fn create_legacy_yaml_workspace(dir: &Path) {
-        // config.yaml
-        std::fs::write(
-            dir.join("config.yaml"),
-            "provider: anthropic\nmodel: claude-sonnet-4-20250514\napi_key_env: ANTHROPIC_API_KEY\n",
-        )
-        .unwrap();
+    /// Directories for the agents (and subagents) listed in `openclaw.json`.
+    fn from_root(root: &OpenClawRoot, options: &MigrateOptions) -> Self {
+        let entries = root
+            .agents
+            .as_ref()
+            .map(|a| expand_subagents(&a.list, None))
+            .unwrap_or_default();
+        Self::from_ids(entries.iter().map(|(e, _)| e.id.as_str()), options)
+    }
 
-        // agents/coder/agent.yaml
-        let agent_dir = dir.join("agents").join("coder");
-        std::fs::create_dir_all(&agent_dir).unwrap();
-        std::fs::write(
-            agent_dir.join("agent.yaml"),
-            "name: coder\ndescription: A coding assistant\ntools:\n  - read_file\n  - write_file\n  - execute_command\ntags:\n  - coding\n  - dev\n",
-        ).unwrap();
+    /// Directories for the directory-based agents of a legacy workspace.
+    fn from_legacy(source: &Path, options: &MigrateOptions) -> Self {
+        let names = legacy_agent_ids(source);
+        Self::from_ids(names.iter().map(String::as_str), options)
+    }
 
-        // agents/coder/MEMORY.md
-        std::fs::write(
-            agent_dir.join("MEMORY.md"),
-            "## Project Context\n- Working on a Rust project\n- Uses async/await\n",
-        )
-        .unwrap();
+    /// Whether `id` is filtered out by `agent_ids`, `include_agents` or `exclude_agents`.
+    fn is_excluded(&self, id: &str) -> bool {
+        !self.is_selected(id) || self.is_filtered(id)
+    }
 
-        // messaging/telegram.yaml
-        let msg_dir = dir.join("messaging");
-        std::fs::create_dir_all(&msg_dir).unwrap();
-        std::fs::write(
+    /// Whether `id` is in the `agent_ids` selection, if there is one.
+    fn is_selected(&self, id: &str) -> bool {
+        self.selection
+            .as_ref()
+            .is_none_or(|ids| ids.iter().any(|s| s == id))
+    }
+
+    /// Whether `id` is filtered out by the `include_agents` / `exclude_agents` patterns.
+    fn is_filtered(&self, id: &str) -> bool {
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|patterns| patterns.iter().any(|p| glob_matches(p, id)));
+        !included || self.exclude.iter().any(|p| glob_matches(p, id))
+    }
+
+    /// Record `id` as skipped by the agent filters. Returns `true` if it was.
+    fn skip_excluded(&self, id: &str, report: &mut MigrationReport) -> bool {
+        if !self.is_excluded(id) {
+            return false;
+        }
+        let reason = if self.is_selected(id) {
+            "excluded by filter"
+        } else {
+            "not in selection"
+        };
+        report.skipped.push(SkippedItem {
+            kind: ItemKind::Agent,
+            name: id.to_string(),
+            reason: reason.to_string(),
+        });
+        true
+    }
+
+    /// Sanitized directory name for `id`, after applying any configured rename.
+    fn base_dir(&self, id: &str) -> String {
+        sanitize_agent_id(renamed_id(id, &self.renames))
+    }
+
+    /// Reserve the first free `<base>-N` directory for `id`.
+    fn claim_suffixed(&mut self, base: &str, id: &str) -> String {
+        let mut n = 2;
+        loop {
+            let dir = format!("{base}-{n}");
+            if !self.taken.contains_key(&dir) {
+                self.taken.insert(dir.clone(), id.to_string());
+                return dir;
+            }
+            n += 1;
+        }
+    }
+
+    /// Directory for `id`, or `None` if the agent was skipped as a duplicate or
+    /// filtered out.
+    fn dir_for(&self, id: &str) -> Option<String> {
+        if self.is_excluded(id) {
+            return None;
+        }
+        if let Some(dir) = self.dirs.get(id) {
+            return Some(dir.clone());
+        }
+        if self.collisions.iter().any(|(c, _, _)| c == id) {
+            return None;
+        }
+        Some(self.base_dir(id))
+    }
+
+    /// Directory for a repeated definition of an id that already has one.
+    ///
+    /// Returns `None` (after recording a skipped item) under [`DuplicateAgentPolicy::Skip`].
+    fn dir_for_duplicate(&mut self, id: &str, report: &mut MigrationReport) -> Option<String> {
+        let first = self.dir_for(id).unwrap_or_else(|| self.base_dir(id));
+        match self.policy {
+            DuplicateAgentPolicy::Rename => {
+                let dir = self.claim_suffixed(&self.base_dir(id), id);
+                report.warnings.push(format!(
+                    "Agent id '{id}' is defined more than once; the first definition stays in agents/{first}/ and the later one was migrated to agents/{dir}/"
+                ));
+                Some(dir)
+            }
+            DuplicateAgentPolicy::Skip => {
+                report.skipped.push(SkippedItem {
+                    kind: ItemKind::Agent,
+                    name: id.to_string(),
+                    reason: format!(
+                        "Duplicate agent id; agents/{first}/ already holds the first definition"
+                    ),
+                });
+                None
+            }
+        }
+    }
+
+    fn report_collisions(&self, report: &mut MigrationReport) {
+        for (id, dir, owner) in &self.collisions {
+            match dir {
+                Some(dir) => report.warnings.push(format!(
+                    "Agent '{id}' collides with '{owner}' after sanitizing its id; migrated to agents/{dir}/"
+                )),
+                None => report.skipped.push(SkippedItem {
+                    kind: ItemKind::Agent,
+                    name: id.clone(),
+                    reason: format!(
+                        "Id collides with '{owner}' after sanitization; agents/{}/ is already used",
+                        self.base_dir(id)
+                    ),
+                }),
+            }
+        }
+    }
+}
+
+/// Agent that receives the main session's memory and logs: `default_agent_target`
+/// when it names a migrated agent, otherwise the first agent.
+fn main_agent_target<'a>(
+    options: &'a MigrateOptions,
+    agent_ids: &'a [String],
+    agent_dirs: &AgentDirs,
+) -> Option<&'a String> {
+    options
+        .default_agent_target
+        .as_ref()
+        .filter(|t| agent_ids.contains(t) && !agent_dirs.is_excluded(t))
+        .or_else(|| agent_ids.first())
+}
+
+/// Sorted names of the directory-based agents of a legacy workspace.
+fn legacy_agent_ids(source: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(source.join("agents"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// The OpenFang name for an agent, applying `MigrateOptions::agent_renames`.
+fn renamed_id<'a>(id: &'a str, renames: &'a std::collections::HashMap<String, String>) -> &'a str {
+    renames.get(id).map(String::as_str).unwrap_or(id)
+}
+
+/// Refuse to migrate more agents than `options.max_agents`, naming the largest entries.
+fn check_agent_count(
+    root: &OpenClawRoot,
+    content: &str,
+    options: &MigrateOptions,
+) -> Result<(), MigrateError> {
+    let Some(agents) = root.agents.as_ref() else {
+        return Ok(());
+    };
+    let agent_dirs = AgentDirs::from_root(root, options);
+    let found = expand_subagents(&agents.list, None)
+        .iter()
+        .filter(|(e, _)| !e.id.is_empty() && !agent_dirs.is_excluded(&e.id))
+        .count();
+    if found <= options.max_agents {
+        return Ok(());
+    }
+    Err(MigrateError::TooManyAgents {
+        found,
+        limit: options.max_agents,
+        largest: largest_agent_entries(content, 3).join(", "),
+    })
+}
+
+/// The `n` largest agent entries of a config as `'id' (N bytes)`, largest first.
+fn largest_agent_entries(content: &str, n: usize) -> Vec<String> {
+    let body = content.trim_start_matches('\u{feff}');
+    let Ok(value) = json5::from_str::<serde_json::Value>(body) else {
+        return Vec::new();
+    };
+    let mut sizes: Vec<(usize, &str)> = match value.pointer("/agents/list") {
+        Some(list) => list
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|e| {
+                let id = e.get("id").and_then(|i| i.as_str()).unwrap_or("?");
+                (e.to_string().len(), id)
+            })
+            .collect(),
+        // Map form: every object except `defaults` is an agent
+        None => value
+            .get("agents")
+            .and_then(|a| a.as_object())
+            .into_iter()
+            .flatten()
+            .filter(|(id, e)| *id != "defaults" && e.is_object())
+            .map(|(id, e)| (e.to_string().len(), id.as_str()))
+            .collect(),
+    };
+    sizes.sort_by_key(|&(size, _)| std::cmp::Reverse(size));
+    sizes
+        .into_iter()
+        .take(n)
+        .map(|(size, id)| format!("'{id}' ({size} bytes)"))
+        .collect()
+}
+
+/// Fail on anything a normal run would only warn about or skip, before anything is written.
+///
+/// Covers unknown channels, channels with no OpenFang adapter, tools with no
+/// OpenFang equivalent and providers OpenFang cannot load, for every agent the
+/// filters keep.
+fn check_strict(root: &OpenClawRoot, options: &MigrateOptions) -> Result<(), MigrateError> {
+    if let Some(channels) = root.channels.as_ref() {
+        if let Some(key) = channels.other.keys().next() {
+            return Err(MigrateError::Strict(format!(
+                "channel '{key}' is not mapped to any OpenFang adapter"
+            )));
+        }
+        if let Some(name) = channels
+            .configured()
+            .into_iter()
+            .find(|name| !channel_is_supported(name))
+        {
+            return Err(MigrateError::Strict(format!(
+                "channel '{name}' has no OpenFang adapter"
+            )));
+        }
+    }
+    let Some(agents) = root.agents.as_ref() else {
+        return Ok(());
+    };
+    let mut tool_map = ToolMap::load(options, &mut Vec::new())?;
+    tool_map.mcp = mcp_tool_allowlists(root);
+    let agent_dirs = AgentDirs::from_root(root, options);
+    for (entry, _) in expand_subagents(&agents.list, None) {
+        let id = &entry.id;
+        if id.is_empty() || agent_dirs.is_excluded(id) {
+            continue;
+        }
+        // Conversion failures are reported per agent by the normal run.
+        if let Ok(converted) = convert_agent_from_json(
+            &entry,
+            agents.defaults.as_ref(),
+            Some(root),
+            options,
+            &tool_map,
+        ) {
+            check_strict_agent(id, &converted)?;
+        }
+    }
+    Ok(())
+}
+
+/// [`check_strict`] for a legacy YAML workspace.
+fn check_strict_legacy(source: &Path, options: &MigrateOptions) -> Result<(), MigrateError> {
+    if let Ok(entries) = std::fs::read_dir(source.join("messaging")) {
+        let mut names: Vec<String> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "yaml"))
+            .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+            .collect();
+        names.sort();
+        if let Some(name) = names
+            .iter()
+            .find(|name| !LEGACY_CHANNELS.contains(&name.as_str()))
+        {
+            return Err(MigrateError::Strict(format!(
+                "channel '{name}' is not mapped to any OpenFang adapter"
+            )));
+        }
+        if let Some(name) = names
+            .iter()
+            .find(|name| matches!(name.as_str(), "imessage" | "bluebubbles"))
+        {
+            return Err(MigrateError::Strict(format!(
+                "channel '{name}' has no OpenFang adapter"
+            )));
+        }
+    }
+    let tool_map = ToolMap::load(options, &mut Vec::new())?;
+    let agent_dirs = AgentDirs::from_legacy(source, options);
+    for name in legacy_agent_ids(source) {
+        if agent_dirs.is_excluded(&name) {
+            continue;
+        }
+        let agent_yaml = source.join("agents").join(&name).join("agent.yaml");
+        if let Ok(converted) = convert_legacy_agent(&agent_yaml, &name, options, &tool_map) {
+            check_strict_agent(&name, &converted)?;
+        }
+    }
+    Ok(())
+}
+
+/// Strict-mode checks on one converted agent: every tool mapped, every model loadable.
+fn check_strict_agent(id: &str, converted: &ConvertedAgent) -> Result<(), MigrateError> {
+    if let Some(tool) = converted.unmapped_tools.first() {
+        return Err(MigrateError::Strict(format!(
+            "agent '{id}': {}",
+            unmapped_tool_warning(tool)
+        )));
+    }
+    let manifest: toml::Value = toml::from_str(&converted.toml)
+        .map_err(|e| MigrateError::AgentParse(format!("{id}: {e}")))?;
+    let fallbacks = manifest
+        .get("fallback_models")
+        .and_then(|f| f.as_array())
+        .into_iter()
+        .flatten();
+    for model in std::iter::once(&manifest["model"]).chain(fallbacks) {
+        let Some(provider) = model.get("provider").and_then(|p| p.as_str()) else {
+            continue;
+        };
+        let base_url = model.get("base_url").and_then(|u| u.as_str());
+        if !is_openfang_provider(provider, base_url) {
+            return Err(MigrateError::Strict(format!(
+                "agent '{id}': provider '{provider}' is not mapped to any OpenFang provider"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Reject renames that would make two agents share a directory, before anything is written.
+fn check_agent_renames(
+    ids: &[String],
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(), MigrateError> {
+    let renames = &options.agent_renames;
+    let mut unknown: Vec<&String> = renames.keys().filter(|k| !ids.contains(k)).collect();
+    unknown.sort();
+    for from in unknown {
+        report
+            .warnings
+            .push(format!("Rename for unknown agent '{from}' ignored"));
+    }
+
+    let mut owners: std::collections::HashMap<String, &str> = std::collections::HashMap::new();
+    for id in ids {
+        let dir = sanitize_agent_id(renamed_id(id, renames));
+        match owners.get(&dir) {
+            Some(owner) if *owner != id.as_str() => {
+                if renames.contains_key(id) || renames.contains_key(*owner) {
+                    return Err(MigrateError::AgentRename(format!(
+                        "agents '{owner}' and '{id}' would both be migrated to agents/{dir}/"
+                    )));
+                }
+            }
+            Some(_) => {}
+            None => {
+                owners.insert(dir, id);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write a generated agent manifest unless the existing one already has the same
+/// content (manifests carry no timestamp, so a rerun produces identical text).
+/// Returns `false` (and leaves the file and its mtime alone) when nothing changed.
+fn write_manifest(
+    path: &Path,
+    content: &str,
+    dry_run: bool,
+    report: &mut MigrationReport,
+) -> Result<bool, std::io::Error> {
+    if std::fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    note_conflict(dry_run, path, report);
+    if !dry_run {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, content)?;
+    }
+    Ok(true)
+}
+
+/// Add every env var named by a `*_env` key in generated TOML to the report's checklist.
+fn record_env_refs(toml_text: &str, report: &mut MigrationReport) {
+    fn walk(value: &toml::Value, report: &mut MigrationReport) {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, v) in table {
+                    match v {
+                        toml::Value::String(name) if key.ends_with("_env") && !name.is_empty() => {
+                            report.require_env_var(name)
+                        }
+                        _ => walk(v, report),
+                    }
+                }
+            }
+            toml::Value::Array(items) => items.iter().for_each(|v| walk(v, report)),
+            _ => {}
+        }
+    }
+    if let Ok(value) = toml::from_str::<toml::Value>(toml_text) {
+        walk(&value, report);
+    }
+}
+
+/// Check a generated manifest the way the kernel will load it. An invalid one is
+/// reported as skipped and its content dumped to `rejected/<dir>.toml` for debugging.
+///
+/// Returns `true` if the manifest was rejected.
+fn reject_invalid_manifest(
+    id: &str,
+    agent_dir: &str,
+    manifest: &str,
+    target: &Path,
+    dry_run: bool,
+    report: &mut MigrationReport,
+) -> bool {
+    let Err(e) = toml::from_str::<openfang_types::agent::AgentManifest>(manifest) else {
+        return false;
+    };
+    let dump = target.join("rejected").join(format!("{agent_dir}.toml"));
+    let mut reason = format!("Generated agent.toml is invalid: {}", e.message());
+    if !dry_run {
+        let written = std::fs::create_dir_all(dump.parent().unwrap_or(target))
+            .and_then(|_| std::fs::write(&dump, manifest));
+        match written {
+            Ok(()) => reason.push_str(&format!(" (content saved to {})", dump.display())),
+            Err(err) => reason.push_str(&format!(" (could not save content: {err})")),
+        }
+    }
+    warn!("Rejected manifest for agent {id}");
+    report.skipped.push(SkippedItem {
+        kind: ItemKind::Agent,
+        name: id.to_string(),
+        reason,
+    });
+    true
+}
+
+/// Keep a record of dropped tools at the bottom of the manifest so equivalents can be wired up.
+fn push_unmapped_tools_comment(toml_str: &mut String, unmapped_tools: &[String]) {
+    if !unmapped_tools.is_empty() {
+        toml_str.push_str(&format!(
+            "\n# unmapped tools: {}\n",
+            unmapped_tools.join(", ")
+        ));
+    }
+}
+
+/// `[metadata]` entries tying a manifest back to its OpenClaw agent: `source_id`
+/// always, `source_name` when the OpenClaw display name differs from the id.
+fn source_id_metadata(id: &str, name: Option<&str>) -> std::collections::BTreeMap<String, String> {
+    let mut metadata = std::collections::BTreeMap::new();
+    metadata.insert("source_id".to_string(), id.to_string());
+    if let Some(name) = name.filter(|n| *n != id) {
+        metadata.insert("source_name".to_string(), name.to_string());
+    }
+    metadata
+}
+
+/// Copy a workspace tree, verifying every file afterwards when `verify_copies` is set.
+fn copy_workspace(
+    src: &Path,
+    dst: &Path,
+    options: &MigrateOptions,
+    report: &mut MigrationReport,
+) -> Result<(), std::io::Error> {
+    if options.incremental_copies {
+        let (bytes, unchanged) = copy_dir_incremental(src, dst, options.verify_copies)?;
+        report.bytes_copied += bytes;
+        if unchanged > 0 {
+            report.notes.push(format!(
+                "{}: {unchanged} files unchanged, skipped",
+                dst.display()
+            ));
+        }
+    } else {
+        report.bytes_copied += copy_dir_recursive(src, dst)?;
+    }
+    if !options.verify_copies {
+        return Ok(());
+    }
+
+    let mut verified = 0;
+    for entry in walkdir::WalkDir::new(src).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(src) else {
+            continue;
+        };
+        let copy = dst.join(rel);
+        let expected = file_fingerprint(entry.path())?;
+        if file_fingerprint(&copy).ok() == Some(expected) {
+            verified += 1;
+            continue;
+        }
+        // Retry once before giving up on the file
+        std::fs::copy(entry.path(), &copy)?;
+        if file_fingerprint(&copy).ok() == Some(expected) {
+            verified += 1;
+            report.warnings.push(format!(
+                "Checksum mismatch copying {}; re-copied successfully",
+                copy.display()
+            ));
+        } else {
+            report.warnings.push(format!(
+                "Checksum mismatch copying {} persists after retry",
+                copy.display()
+            ));
+        }
+    }
+    report
+        .notes
+        .push(format!("{}: verified {verified} files", dst.display()));
+    Ok(())
+}
+
+/// Cheap content fingerprint (length + 64-bit hash) for copy verification.
+fn file_fingerprint(path: &Path) -> Result<(u64, u64), std::io::Error> {
+    use std::hash::Hasher;
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut len = 0u64;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.write(&buf[..n]);
+        len += n as u64;
+    }
+    Ok((len, hasher.finish()))
+}
+
+/// Copy a directory, skipping files whose destination already matches: same size and
+/// mtime, or same fingerprint when `by_hash`. Copied files take the source mtime so the
+/// next run can recognize them. Returns `(bytes copied, files left unchanged)`.
+fn copy_dir_incremental(
+    src: &Path,
+    dst: &Path,
+    by_hash: bool,
+) -> Result<(u64, usize), std::io::Error> {
+    let mut bytes = 0;
+    let mut unchanged = 0;
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let Ok(rel) = entry.path().strip_prefix(src) else {
+            continue;
+        };
+        let dst_path = dst.join(rel);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dst_path)?;
+            continue;
+        }
+        let src_meta = entry.metadata().map_err(std::io::Error::other)?;
+        let same = match std::fs::metadata(&dst_path) {
+            Ok(_) if by_hash => file_fingerprint(entry.path())? == file_fingerprint(&dst_path)?,
+            Ok(dst_meta) => {
+                dst_meta.len() == src_meta.len()
+                    && dst_meta.modified().ok() == src_meta.modified().ok()
+            }
+            Err(_) => false,
+        };
+        if same {
+            unchanged += 1;
+            continue;
+        }
+        bytes += std::fs::copy(entry.path(), &dst_path)?;
+        if let Ok(mtime) = src_meta.modified() {
+            std::fs::File::options()
+                .write(true)
+                .open(&dst_path)?
+                .set_modified(mtime)?;
+        }
+    }
+    Ok((bytes, unchanged))
+}
+
+/// Recursively copy a directory, returning the number of bytes copied.
+fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<u64, std::io::Error> {
+    std::fs::create_dir_all(dst)?;
+    let mut bytes = 0;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let src_path = entry.path();
+        let dst_path = dst.join(entry.file_name());
+        if src_path.is_dir() {
+            bytes += copy_dir_recursive(&src_path, &dst_path)?;
+        } else {
+            bytes += std::fs::copy(&src_path, &dst_path)?;
+        }
+    }
+    Ok(bytes)
+}
+
+/// Number of files under `dir`, at any depth.
+fn count_files(dir: &Path) -> usize {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .count()
+}
+
+/// Copy a credentials directory as a whole, returning bytes and files copied.
+///
+/// The tree is copied next to `dst` first and only swapped in once complete, so a
+/// failure removes the partial copy and leaves any previous `dst` intact.
+fn copy_credentials_dir(src: &Path, dst: &Path) -> Result<(u64, usize), std::io::Error> {
+    let mut partial = dst.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    if partial.exists() {
+        std::fs::remove_dir_all(&partial)?;
+    }
+    let bytes = match copy_dir_recursive(src, &partial) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&partial);
+            return Err(e);
+        }
+    };
+    if dst.exists() {
+        std::fs::remove_dir_all(dst)?;
+    }
+    std::fs::rename(&partial, dst)?;
+    Ok((bytes, count_files(dst)))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // ===== Helper: create legacy YAML workspace =====
+
+    fn create_legacy_yaml_workspace(dir: &Path) {
+        // config.yaml
+        std::fs::write(
+            dir.join("config.yaml"),
+            "provider: anthropic\nmodel: claude-sonnet-4-20250514\napi_key_env: ANTHROPIC_API_KEY\n",
+        )
+        .unwrap();
+
+        // agents/coder/agent.yaml
+        let agent_dir = dir.join("agents").join("coder");
+        std::fs::create_dir_all(&agent_dir).unwrap();
+        std::fs::write(
+            agent_dir.join("agent.yaml"),
+            "name: coder\ndescription: A coding assistant\ntools:\n  - read_file\n  - write_file\n  - execute_command\ntags:\n  - coding\n  - dev\n",
+        ).unwrap();
+
+        // agents/coder/MEMORY.md
+        std::fs::write(
+            agent_dir.join("MEMORY.md"),
+            "## Project Context\n- Working on a Rust project\n- Uses async/await\n",
+        )
+        .unwrap();
+
+        // messaging/telegram.yaml
+        let msg_dir = dir.join("messaging");
+        std::fs::create_dir_all(&msg_dir).unwrap();
+        std::fs::write(
             msg_dir.join("telegram.yaml"),
             "type: telegram\nbot_token_env: TELEGRAM_BOT_TOKEN\ndefault_agent: coder\n",
         )
@@ -3081,8 +8370,8 @@ mod tests {
 
     // ===== Helper: create JSON5 workspace =====
 
-    fn create_json5_workspace(dir: &Path) {
-        let json5_content = r##"{
+    /// `openclaw.json` of the fixture workspace built by [`create_json5_workspace`].
+    const JSON5_FIXTURE: &str = r##"{
   agents: {
     defaults: {
       model: "anthropic/claude-sonnet-4-20250514",
@@ -3092,6 +8381,7 @@ mod tests {
       {
         id: "coder",
         name: "Coder",
+        description: "Writes and reviews \"production\" code",
         model: {
           primary: "deepseek/deepseek-chat",
           fallbacks: ["groq/llama-3.3-70b-versatile", "anthropic/claude-haiku-4-5-20251001"]
@@ -3109,7 +8399,7 @@ mod tests {
   channels: {
     telegram: {
       botToken: "123:ABC",
-      allowFrom: ["user1", "user2"],
+      allowFrom: ["123456789", "user2"],
       groupPolicy: "open",
       dmPolicy: "allowlist"
     },
@@ -3186,7 +8476,8 @@ mod tests {
   session: { scope: "per-sender" }
 }"##;
 
-        std::fs::write(dir.join("openclaw.json"), json5_content).unwrap();
+    fn create_json5_workspace(dir: &Path) {
+        std::fs::write(dir.join("openclaw.json"), JSON5_FIXTURE).unwrap();
 
         // Physical memory dirs
         let mem_coder = dir.join("memory").join("coder");
@@ -3230,24 +8521,174 @@ mod tests {
     // ================================================================
 
     #[test]
-    fn test_json5_full_migration() {
-        let source = TempDir::new().unwrap();
+    fn test_migrate_config_str() {
         let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            // Not used: everything comes from the string
+            source_dir: "/nonexistent/path".into(),
+            ..Default::default()
+        };
+        let report = migrate_config_str(JSON5_FIXTURE, target.path(), &options).unwrap();
 
-        create_json5_workspace(source.path());
+        let imported = |kind: ItemKind| report.imported.iter().filter(|i| i.kind == kind).count();
+        assert_eq!(imported(ItemKind::Agent), 2);
+        assert_eq!(imported(ItemKind::Channel), 11);
+        assert_eq!(imported(ItemKind::Memory), 0);
+        assert_eq!(imported(ItemKind::Session), 0);
+        assert!(target.path().join("agents/coder/agent.toml").exists());
+        assert!(target.path().join("config.toml").exists());
+        assert!(target.path().join("migration_report.md").exists());
+        assert!(report.skipped.iter().any(|s| s.name == "cron"));
+
+        let err = migrate_config_str("{ agents: [", target.path(), &options).unwrap_err();
+        assert!(matches!(err, MigrateError::Json5Parse(_)));
+    }
 
+    #[test]
+    fn test_validate_after() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        create_json5_workspace(source.path());
         let options = MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: false,
+            validate_after: true,
+            ..Default::default()
         };
-
         let report = migrate(&options).unwrap();
+        let validation: Vec<&String> = report
+            .warnings
+            .iter()
+            .filter(|w| w.starts_with("Validation:"))
+            .collect();
+        assert!(validation.is_empty(), "{validation:?}");
 
-        // Config imported
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
-        assert!(target.path().join("config.toml").exists());
+        // Usernames can't go in Telegram's numeric allowlist
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'user2' are not numeric user IDs")));
+
+        // A broken manifest is reported
+        std::fs::write(target.path().join("agents/coder/agent.toml"), "name = [").unwrap();
+        let mut report = MigrationReport::default();
+        validate_output(target.path(), &options, &mut report);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("coder"));
+    }
+
+    #[test]
+    fn test_memory_details_in_dry_run() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        create_json5_workspace(source.path());
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let details = |name: &str| {
+            report
+                .imported
+                .iter()
+                .find(|i| i.name == name)
+                .and_then(|i| i.details.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            details("coder/MEMORY.md"),
+            ItemDetails {
+                bytes: 31,
+                lines: 2,
+                entries: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            details("researcher/MEMORY.md"),
+            ItemDetails {
+                bytes: 45,
+                lines: 2,
+                entries: 1,
+                ..Default::default()
+            }
+        );
+        let md = report.to_markdown();
+        assert!(md.contains("## Memory Size"));
+        assert!(md.contains("| coder/MEMORY.md | 31 B | 2 | 1 |"));
+        assert!(!target.path().join("agents").exists());
+    }
+
+    #[test]
+    fn test_json5_memory_tunables() {
+        let config = |json5: &str| {
+            let target = TempDir::new().unwrap();
+            let report =
+                migrate_config_str(json5, target.path(), &MigrateOptions::default()).unwrap();
+            let config: toml::Value = toml::from_str(
+                &std::fs::read_to_string(target.path().join("config.toml")).unwrap(),
+            )
+            .unwrap();
+            (config["memory"].clone(), report)
+        };
+
+        let (memory, report) =
+            config(r#"{ memory: { decayRate: 0.2, maxEntries: 5000, ttlDays: 30 } }"#);
+        assert_eq!(memory["decay_rate"].as_float(), Some(0.2_f32 as f64));
+        assert_eq!(memory["consolidation_threshold"].as_integer(), Some(5000));
+        assert!(!report.skipped.iter().any(|s| s.name == "memory"));
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.name == "memory.ttlDays" && s.reason.contains("30 days")));
+
+        // Nothing configured: the default, and no threshold
+        let (memory, report) = config(r#"{ agents: { list: [ { id: "coder" } ] } }"#);
+        assert_eq!(memory["decay_rate"].as_float(), Some(0.05_f32 as f64));
+        assert!(memory.get("consolidation_threshold").is_none());
+        assert!(!report.skipped.iter().any(|s| s.name.starts_with("memory")));
+
+        // Out-of-range values fall back with a warning; other keys are still reported
+        let (memory, report) = config(r#"{ memory: { decay_rate: 4, backend: "lancedb" } }"#);
+        assert_eq!(memory["decay_rate"].as_float(), Some(0.05_f32 as f64));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("memory.decayRate 4")));
+        assert!(report.skipped.iter().any(|s| s.name == "memory"));
+    }
+
+    #[test]
+    fn test_json5_full_migration() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        create_json5_workspace(source.path());
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+
+        // Every generated manifest loads
+        assert!(!report
+            .skipped
+            .iter()
+            .any(|s| s.reason.contains("agent.toml is invalid")));
+        assert!(!target.path().join("rejected").exists());
+
+        // Config imported
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
+        assert!(target.path().join("config.toml").exists());
 
         // Agents imported
         let agent_items: Vec<_> = report
@@ -3259,6 +8700,14 @@ mod tests {
         assert!(target.path().join("agents/coder/agent.toml").exists());
         assert!(target.path().join("agents/researcher/agent.toml").exists());
 
+        // The manifest records which OpenClaw agent it came from
+        let coder = std::fs::read_to_string(target.path().join("agents/coder/agent.toml")).unwrap();
+        assert!(coder.contains("source_id = \"coder\""));
+        assert!(coder.contains("source_name = \"Coder\""));
+        let manifest: openfang_types::agent::AgentManifest = toml::from_str(&coder).unwrap();
+        assert_eq!(manifest.name, "Coder");
+        assert_eq!(manifest.metadata["source_id"], "coder");
+
         // Channels imported (11 supported channels from fixture)
         let channel_items: Vec<_> = report
             .imported
@@ -3283,6 +8732,25 @@ mod tests {
             "missing google_chat in config: {config_toml}"
         );
 
+        // Every referenced env var is on the checklist
+        for var in [
+            "ANTHROPIC_API_KEY",
+            "TELEGRAM_BOT_TOKEN",
+            "DISCORD_BOT_TOKEN",
+            "SLACK_BOT_TOKEN",
+            "SLACK_APP_TOKEN",
+            "MATRIX_ACCESS_TOKEN",
+        ] {
+            assert!(
+                report.required_env_vars.iter().any(|v| v == var),
+                "{var} missing from {:?}",
+                report.required_env_vars
+            );
+        }
+        assert!(report
+            .to_markdown()
+            .contains("## Required Environment Variables"));
+
         // Secrets extracted
         let secret_items: Vec<_> = report
             .imported
@@ -3338,13 +8806,22 @@ mod tests {
             .path()
             .join("agents/researcher/imported_memory.md")
             .exists());
+        let jsonl =
+            std::fs::read_to_string(target.path().join("agents/coder/imported_memory.jsonl"))
+                .unwrap();
+        let entry: serde_json::Value = serde_json::from_str(jsonl.trim()).unwrap();
+        assert_eq!(entry["tag"], "Coder Memory");
+        assert_eq!(entry["content"], "- Prefers Rust");
 
         // Sessions imported
         assert!(report
             .imported
             .iter()
             .any(|i| i.kind == ItemKind::Session && i.name.contains("session")));
-        assert!(target.path().join("imported_sessions/main.jsonl").exists());
+        assert!(target
+            .path()
+            .join("agents/coder/imported_sessions/main.jsonl")
+            .exists());
 
         // Workspace imported
         assert!(report
@@ -3407,7 +8884,7 @@ mod tests {
         let target = TempDir::new().unwrap();
         let json5_content = r#"{
   channels: {
-    telegram: { botToken: "123", allowFrom: ["alice"], enabled: true },
+    telegram: { botToken: "123", allowFrom: ["12345", "alice"], enabled: true },
     discord: { token: "abc", enabled: true },
     slack: { botToken: "xoxb", appToken: "xapp" }
   }
@@ -3415,7 +8892,12 @@ mod tests {
         let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
         let mut report = MigrationReport::default();
 
-        let channels = migrate_channels_from_json(&root, target.path(), false, &mut report);
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        );
         assert!(channels.is_some());
         let ch = channels.unwrap();
         let ch_table = ch.as_table().unwrap();
@@ -3427,8 +8909,8 @@ mod tests {
         let tg = ch_table["telegram"].as_table().unwrap();
         assert_eq!(tg["bot_token_env"].as_str().unwrap(), "TELEGRAM_BOT_TOKEN");
         let users = tg["allowed_users"].as_array().unwrap();
-        assert_eq!(users.len(), 1);
-        assert_eq!(users[0].as_str().unwrap(), "alice");
+        assert_eq!(users, &vec![toml::Value::Integer(12345)]);
+        assert!(report.warnings.iter().any(|w| w.contains("'alice'")));
 
         // 3 channel imports
         assert_eq!(
@@ -3469,6 +8951,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         migrate(&options).unwrap();
@@ -3486,6 +8969,21 @@ mod tests {
         assert!(coder_toml.contains("model = \"llama-3.3-70b-versatile\""));
         assert!(coder_toml.contains("provider = \"anthropic\""));
         assert!(coder_toml.contains("model = \"claude-haiku-4-5-20251001\""));
+
+        // Description comes from the config; agents without one keep the boilerplate
+        let coder: toml::Value = toml::from_str(&coder_toml).unwrap();
+        assert_eq!(
+            coder["description"].as_str(),
+            Some("Writes and reviews \"production\" code")
+        );
+        let researcher: toml::Value = toml::from_str(
+            &std::fs::read_to_string(target.path().join("agents/researcher/agent.toml")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            researcher["description"].as_str(),
+            Some("Migrated from OpenClaw agent 'researcher'")
+        );
     }
 
     #[test]
@@ -3500,6 +8998,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         migrate(&options).unwrap();
@@ -3509,7 +9008,15 @@ mod tests {
             std::fs::read_to_string(target.path().join("agents/researcher/agent.toml")).unwrap();
         assert!(researcher_toml.contains("web_fetch"));
         assert!(researcher_toml.contains("web_search"));
-        assert!(researcher_toml.contains("profile = \"research\""));
+        let parsed: toml::Value = toml::from_str(&researcher_toml).unwrap();
+        assert_eq!(parsed["profile"].as_str(), Some("research"));
+        assert!(parsed["capabilities"].get("profile").is_none());
+        let manifest: openfang_types::agent::AgentManifest =
+            toml::from_str(&researcher_toml).unwrap();
+        assert_eq!(
+            manifest.profile,
+            Some(openfang_types::agent::ToolProfile::Research)
+        );
     }
 
     #[test]
@@ -3524,6 +9031,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         let report = migrate(&options).unwrap();
@@ -3575,17 +9083,19 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         migrate(&options).unwrap();
 
-        let imported_dir = target.path().join("imported_sessions");
-        assert!(imported_dir.exists());
-        assert!(imported_dir.join("main.jsonl").exists());
-        assert!(imported_dir.join("agent_coder_main.jsonl").exists());
+        // Both belong to coder, the first agent: its own session keeps the name
+        let imported_dir = target.path().join("agents/coder/imported_sessions");
+        assert!(!target.path().join("imported_sessions").exists());
+        let content = std::fs::read_to_string(imported_dir.join("main.jsonl")).unwrap();
+        assert!(content.contains("write code"));
 
         // Verify content preserved
-        let content = std::fs::read_to_string(imported_dir.join("main.jsonl")).unwrap();
+        let content = std::fs::read_to_string(imported_dir.join("main-2.jsonl")).unwrap();
         assert!(content.contains("hello"));
     }
 
@@ -3620,6 +9130,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         let report = migrate(&options).unwrap();
@@ -3680,6 +9191,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         let report = migrate(&options).unwrap();
@@ -3703,342 +9215,4801 @@ mod tests {
     }
 
     #[test]
-    fn test_json5_dry_run() {
+    fn test_markdown_skills_copied() {
         let source = TempDir::new().unwrap();
         let target = TempDir::new().unwrap();
-
-        create_json5_workspace(source.path());
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ skills: { entries: { "summarize": {}, "scraper": {} } } }"#,
+        )
+        .unwrap();
+        let summarize = source.path().join("skills/summarize");
+        std::fs::create_dir_all(summarize.join("prompts")).unwrap();
+        std::fs::write(summarize.join("SKILL.md"), "# Summarize\nCondense text.").unwrap();
+        std::fs::write(summarize.join("prompts/short.txt"), "Be brief.").unwrap();
+        let scraper = source.path().join("skills/community/scraper");
+        std::fs::create_dir_all(&scraper).unwrap();
+        std::fs::write(scraper.join("package.json"), "{}").unwrap();
+        std::fs::write(scraper.join("index.js"), "").unwrap();
+        std::fs::write(scraper.join("README.md"), "# Scraper").unwrap();
 
         let options = MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: true,
+            ..Default::default()
         };
-
         let report = migrate(&options).unwrap();
-        assert!(report.dry_run);
-        assert!(!report.imported.is_empty());
 
-        // No files created
-        assert!(!target.path().join("config.toml").exists());
-        assert!(!target.path().join("agents").exists());
-        assert!(!target.path().join("imported_sessions").exists());
+        let skills = target.path().join("skills");
+        assert_eq!(
+            std::fs::read_to_string(skills.join("summarize/SKILL.md")).unwrap(),
+            "# Summarize\nCondense text."
+        );
+        assert!(skills.join("summarize/prompts/short.txt").exists());
+        assert!(!skills.join("scraper").exists());
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Skill && i.name == "summarize"));
+        // The Node skill keeps the reinstall notice
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.kind == ItemKind::Skill && s.name == "1 skill entries"));
+
+        // Legacy workspaces copy file-only skills from skills/custom too
+        let legacy = TempDir::new().unwrap();
+        std::fs::write(legacy.path().join("config.yaml"), "provider: anthropic\n").unwrap();
+        let notes = legacy.path().join("skills/custom/notes");
+        std::fs::create_dir_all(&notes).unwrap();
+        std::fs::write(notes.join("SKILL.md"), "Take notes.").unwrap();
+        let target = TempDir::new().unwrap();
+        let report = migrate(&MigrateOptions {
+            source_dir: legacy.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..options
+        })
+        .unwrap();
+        assert!(target.path().join("skills/notes/SKILL.md").exists());
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Skill && i.name == "notes"));
     }
 
     #[test]
-    fn test_json5_empty_config() {
+    fn test_skill_entry_outside_skills_dir() {
         let source = TempDir::new().unwrap();
         let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ skills: { entries: { "../outside": {} } } }"#,
+        )
+        .unwrap();
+        let outside = source.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("SKILL.md"), "# Outside").unwrap();
 
-        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
-
-        let options = MigrateOptions {
+        let report = migrate(&MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: false,
-        };
-
-        let report = migrate(&options).unwrap();
-
-        // Should still produce a config
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
-        assert!(target.path().join("config.toml").exists());
-
-        // No agents should be an info, not crash
-        assert!(report.warnings.iter().any(|w| w.contains("No agents")));
-    }
-
-    #[test]
-    fn test_model_ref_split() {
-        let (p, m) = split_model_ref("anthropic/claude-sonnet-4-20250514");
-        assert_eq!(p, "anthropic");
-        assert_eq!(m, "claude-sonnet-4-20250514");
-
-        let (p, m) = split_model_ref("deepseek/deepseek-chat");
-        assert_eq!(p, "deepseek");
-        assert_eq!(m, "deepseek-chat");
-
-        let (p, m) = split_model_ref("google/gemini-2.5-flash");
-        assert_eq!(p, "google");
-        assert_eq!(m, "gemini-2.5-flash");
-
-        let (p, m) = split_model_ref("groq/llama-3.3-70b-versatile");
-        assert_eq!(p, "groq");
-        assert_eq!(m, "llama-3.3-70b-versatile");
-
-        // No slash
-        let (p, m) = split_model_ref("some-model");
-        assert_eq!(p, "anthropic");
-        assert_eq!(m, "some-model");
+            ..Default::default()
+        })
+        .unwrap();
 
-        // Empty
-        let (p, m) = split_model_ref("");
-        assert_eq!(p, "anthropic");
-        assert_eq!(m, "");
+        assert!(!target.path().join("outside").exists());
+        assert!(!report.imported.iter().any(|i| i.kind == ItemKind::Skill));
+        assert!(report.skipped.iter().any(|s| s.kind == ItemKind::Skill
+            && s.name == "../outside"
+            && s.reason == "Skill name is not a plain directory name"));
     }
 
     #[test]
-    fn test_json5_unknown_provider_passthrough() {
+    fn test_sessions_converted() {
         let source = TempDir::new().unwrap();
         let target = TempDir::new().unwrap();
-
-        let json5_content = r#"{
-  agents: {
-    list: [
-      { id: "test-agent", model: "mycompany/custom-llm-v3" }
-    ]
-  }
-}"#;
-        std::fs::write(source.path().join("openclaw.json"), json5_content).unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }, { id: "coder" }] } }"#,
+        )
+        .unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::write(
+            sessions.join("agent:coder:main.jsonl"),
+            r#"{"type":"session","id":"abc","timestamp":"2025-03-01T10:00:00Z"}
+{"role":"user","content":"list the files","ts":"2025-03-01T10:00:01Z"}
+{"type":"message","message":{"role":"assistant","content":[{"type":"text","text":"Listing."},{"type":"toolCall","id":"call_1","name":"shell_exec","arguments":{"command":"ls"}}]},"timestamp":"2025-03-01T10:00:02Z"}
+{"type":"message","message":{"role":"toolResult","toolCallId":"call_1","content":[{"type":"text","text":"main.rs"}]}}
+not json
+{"role":"narrator","content":"?"}
+{"role":"assistant","content":"There is one file: main.rs"}
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            sessions.join("main.jsonl"),
+            "{\"type\":\"session\",\"id\":\"x\"}\n",
+        )
+        .unwrap();
 
         let options = MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: false,
+            convert_sessions: true,
+            ..Default::default()
         };
-
         let report = migrate(&options).unwrap();
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Agent));
 
-        let agent_toml =
-            std::fs::read_to_string(target.path().join("agents/test-agent/agent.toml")).unwrap();
-        assert!(agent_toml.contains("provider = \"mycompany\""));
-        assert!(agent_toml.contains("model = \"custom-llm-v3\""));
-        assert!(agent_toml.contains("api_key_env = \"MYCOMPANY_API_KEY\""));
-    }
+        let dest = target
+            .path()
+            .join("agents/coder/imported_sessions")
+            .join(format!(
+                "{}.jsonl",
+                session_uuid("coder", "agent:coder:main.jsonl")
+            ));
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&dest)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["role"], "user");
+        assert_eq!(lines[0]["content"], "list the files");
+        assert_eq!(lines[0]["timestamp"], "2025-03-01T10:00:01+00:00");
+        assert_eq!(lines[1]["role"], "assistant");
+        assert_eq!(lines[1]["content"], "Listing.");
+        assert_eq!(lines[1]["tool_use"][0]["type"], "tool_use");
+        assert_eq!(lines[1]["tool_use"][0]["name"], "shell_exec");
+        assert_eq!(lines[1]["tool_use"][0]["input"]["command"], "ls");
+        assert_eq!(lines[2]["role"], "user");
+        assert_eq!(lines[2]["tool_use"][0]["tool_use_id"], "call_1");
+        assert_eq!(lines[2]["tool_use"][0]["content"], "main.rs");
+        // Untimed lines carry the previous timestamp forward
+        assert_eq!(lines[2]["timestamp"], "2025-03-01T10:00:02+00:00");
+        assert_eq!(lines[3]["content"], "There is one file: main.rs");
 
-    // ================================================================
-    // Existing tests (kept — now test YAML legacy path + shared utils)
-    // ================================================================
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w == "sessions/agent:coder:main.jsonl: skipped 2 of 7 lines as unparseable"));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "coder: 1 converted sessions"));
+
+        // A log with nothing convertible falls back to a raw copy
+        assert!(target
+            .path()
+            .join("agents/main/imported_sessions/main.jsonl")
+            .exists());
+        assert!(!target
+            .path()
+            .join("agents/main/imported_sessions")
+            .join(format!("{}.jsonl", session_uuid("main", "main.jsonl")))
+            .exists());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("sessions/main.jsonl: no messages could be converted")));
+        // Converted logs are archival only
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("will not resume them")));
+    }
 
     #[test]
-    fn test_full_migration() {
+    fn test_session_tool_records() {
         let source = TempDir::new().unwrap();
         let target = TempDir::new().unwrap();
-
-        create_legacy_yaml_workspace(source.path());
-
-        let options = MigrateOptions {
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }] } }"#,
+        )
+        .unwrap();
+        let page = "<p>lorem ipsum</p>\n".repeat(16_000);
+        let records = [
+            serde_json::json!({"role": "user", "content": "free space?"}),
+            // Shell round-trip under OpenClaw's `exec` name
+            serde_json::json!({"type": "message", "message": {"role": "assistant", "content": [
+                {"type": "toolCall", "id": "call_1", "name": "exec", "arguments": {"command": "df -h"}}
+            ]}}),
+            serde_json::json!({"type": "message", "message": {"role": "toolResult", "toolCallId": "call_1",
+                "content": [{"type": "text", "text": "/dev/sda1  40G  12G"}]}}),
+            // Standalone records without ids pair up in order
+            serde_json::json!({"type": "tool_use", "name": "web_fetch", "input": {"url": "https://example.com"}}),
+            serde_json::json!({"type": "tool_result", "output": page}),
+            // No OpenFang equivalent
+            serde_json::json!({"type": "message", "message": {"role": "assistant", "content": [
+                {"type": "text", "text": "Drawing it."},
+                {"type": "toolCall", "id": "call_3", "name": "canvas", "arguments": {"action": "present"}}
+            ]}}),
+            serde_json::json!({"type": "message", "message": {"role": "toolResult", "toolCallId": "call_3",
+                "isError": true, "content": "no display"}}),
+            serde_json::json!({"type": "tool_result", "tool_use_id": "call_9", "output": "stray"}),
+        ];
+        let log: String = records.iter().map(|r| format!("{r}\n")).collect();
+        std::fs::create_dir_all(source.path().join("sessions")).unwrap();
+        std::fs::write(source.path().join("sessions/main.jsonl"), log).unwrap();
+
+        migrate(&MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: false,
-        };
-
-        let report = migrate(&options).unwrap();
-
-        assert!(!report.imported.is_empty());
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Agent));
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Memory));
-        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Channel));
+            convert_sessions: true,
+            ..Default::default()
+        })
+        .unwrap();
 
-        assert!(target.path().join("config.toml").exists());
-        assert!(target.path().join("agents/coder/agent.toml").exists());
-        assert!(target
+        let dest = target
             .path()
-            .join("agents/coder/imported_memory.md")
-            .exists());
-
-        let agent_toml =
-            std::fs::read_to_string(target.path().join("agents/coder/agent.toml")).unwrap();
-        assert!(
-            agent_toml.contains("shell = [\"*\"]"),
-            "shell_exec should derive shell capability"
+            .join("agents/main/imported_sessions")
+            .join(format!("{}.jsonl", session_uuid("main", "main.jsonl")));
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&dest)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let roles: Vec<&str> = lines.iter().map(|l| l["role"].as_str().unwrap()).collect();
+        assert_eq!(
+            roles,
+            [
+                "user",
+                "assistant",
+                "user",
+                "assistant",
+                "user",
+                "assistant",
+                "system",
+                "system",
+                "system"
+            ]
         );
-        assert!(agent_toml.contains("file_read"));
-        assert!(agent_toml.contains("file_write"));
-        assert!(agent_toml.contains("shell_exec"));
 
-        let config_toml = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
+        let call = &lines[1]["tool_use"][0];
+        assert_eq!(call["name"], "shell_exec");
+        assert_eq!(call["input"]["command"], "df -h");
+        assert_eq!(lines[2]["tool_use"][0]["tool_use_id"], call["id"]);
+        assert_eq!(lines[2]["tool_use"][0]["content"], "/dev/sda1  40G  12G");
+
+        let fetch = &lines[3]["tool_use"][0];
+        assert_eq!(fetch["name"], "web_fetch");
+        assert_eq!(fetch["id"], "migrated_call_1");
+        let result = &lines[4]["tool_use"][0];
+        assert_eq!(result["tool_use_id"], "migrated_call_1");
+        assert_eq!(result["content"].as_str().unwrap().len(), page.len());
+
+        // The unmapped call keeps its text, and the call, its result and the
+        // stray result become notes
+        assert_eq!(lines[5]["content"], "Drawing it.");
+        assert!(lines[5].get("tool_use").is_none());
+        let note = lines[6]["content"].as_str().unwrap();
+        assert!(note.starts_with(
+            "[OpenClaw tool call 'canvas' (call_3), which has no OpenFang equivalent]"
+        ));
+        assert!(note.contains("\"present\""));
+        assert_eq!(
+            lines[7]["content"],
+            "[OpenClaw tool result for 'canvas' (call_3), failed]\nno display"
+        );
+        assert_eq!(
+            lines[8]["content"],
+            "[OpenClaw tool result (call_9) with no matching call]\nstray"
+        );
+    }
+
+    #[test]
+    fn test_session_bad_lines() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }] } }"#,
+        )
+        .unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        // Garbage mid-file and a last line cut off mid-write
+        std::fs::write(
+            sessions.join("crashed.jsonl"),
+            r#"{"role":"user","content":"one"}
+{"role":"assistant","content":"two"}
+\x00\x00 garbage
+{"role":"user","content":"three"}
+{"role":"assistant","content":"fo"#,
+        )
+        .unwrap();
+        std::fs::write(
+            sessions.join("mangled.jsonl"),
+            "{\"role\":\"user\",\"content\":\"ok\"}\nnope\nnope\n",
+        )
+        .unwrap();
+
+        let run = |session_bad_line_limit: f64| {
+            let target = TempDir::new().unwrap();
+            let report = migrate(&MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                convert_sessions: true,
+                session_bad_line_limit,
+                ..Default::default()
+            })
+            .unwrap();
+            (target, report)
+        };
+
+        let (target, report) = run(crate::DEFAULT_SESSION_BAD_LINE_LIMIT);
+        let converted = std::fs::read_to_string(
+            target
+                .path()
+                .join("agents/main/imported_sessions")
+                .join(format!("{}.jsonl", session_uuid("main", "crashed.jsonl"))),
+        )
+        .unwrap();
+        assert_eq!(converted.lines().count(), 3);
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w == "sessions/crashed.jsonl: skipped 2 of 5 lines as unparseable"));
+        // Two of three lines bad is over the 50% limit
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.name == "sessions/mangled.jsonl"
+                && s.reason == "2 of 3 lines unparseable (limit 50%)"));
+        assert!(!target
+            .path()
+            .join("agents/main/imported_sessions/mangled.jsonl")
+            .exists());
+
+        // A higher limit keeps it
+        let (_, report) = run(0.7);
+        assert!(report.skipped.iter().all(|s| s.kind != ItemKind::Session));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "main: 2 converted sessions"));
+    }
+
+    #[test]
+    fn test_sessions_routed_by_name() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }, { id: "web" }, { id: "web_dev" }] } }"#,
+        )
+        .unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        for name in [
+            "agent:web:telegram:42.jsonl",
+            "agent_web_dev_daily.jsonl",
+            "agent_ghost_main.jsonl",
+            "main.jsonl",
+        ] {
+            std::fs::write(
+                sessions.join(name),
+                "{\"role\":\"user\",\"content\":\"hi\"}\n",
+            )
+            .unwrap();
+        }
+
+        let report = migrate(&MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let agents = target.path().join("agents");
+        // Colon form
+        assert!(agents
+            .join("web/imported_sessions/telegram_42.jsonl")
+            .exists());
+        // Underscore form, matching the longest agent id
+        assert!(agents
+            .join("web_dev/imported_sessions/daily.jsonl")
+            .exists());
+        // Unmatchable and main-session logs go to the default agent
+        assert!(agents
+            .join("main/imported_sessions/agent_ghost_main.jsonl")
+            .exists());
+        assert!(agents.join("main/imported_sessions/main.jsonl").exists());
+        assert!(!target.path().join("imported_sessions").exists());
+
+        let sessions: Vec<&str> = report
+            .imported
+            .iter()
+            .filter(|i| i.kind == ItemKind::Session)
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(
+            sessions,
+            [
+                "main: 2 session files",
+                "web: 1 session files",
+                "web_dev: 1 session files"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_sessions() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }, { id: "coder" }] } }"#,
+        )
+        .unwrap();
+        let sessions = source.path().join("sessions");
+        for dir in ["telegram", "archive/2024"] {
+            std::fs::create_dir_all(sessions.join(dir)).unwrap();
+        }
+        let line = "{\"role\":\"user\",\"content\":\"hi\"}\n";
+        for name in [
+            "main.jsonl",
+            "telegram/12345.jsonl",
+            "telegram/67890.jsonl",
+            "archive/2024/agent:coder:main.jsonl",
+            "archive/2024/old.jsonl",
+        ] {
+            std::fs::write(sessions.join(name), line).unwrap();
+        }
+
+        let report = migrate(&MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let main = target.path().join("agents/main/imported_sessions");
+        for name in [
+            "main.jsonl",
+            "telegram/12345.jsonl",
+            "telegram/67890.jsonl",
+            "archive/2024/old.jsonl",
+        ] {
+            assert!(main.join(name).exists(), "{name}");
+        }
+        assert!(target
+            .path()
+            .join("agents/coder/imported_sessions/archive/2024/main.jsonl")
+            .exists());
+
+        let sessions: Vec<&str> = report
+            .imported
+            .iter()
+            .filter(|i| i.kind == ItemKind::Session && !i.name.contains("workspace"))
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(
+            sessions,
+            [
+                "coder: 1 session files",
+                "main: 4 session files (2 from telegram)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_session_files() {
+        use std::io::Write;
+
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "main" }] } }"#,
+        )
+        .unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(sessions.join("archive")).unwrap();
+        let line = "{\"role\":\"user\",\"content\":\"from the archive\"}\n";
+        std::fs::write(sessions.join("main.jsonl"), line).unwrap();
+        std::fs::write(sessions.join("main.jsonl~"), line).unwrap();
+        std::fs::write(sessions.join("snapshot.json"), "{}").unwrap();
+        std::fs::write(sessions.join("README"), "notes").unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(line.as_bytes()).unwrap();
+        std::fs::write(
+            sessions.join("archive/2024-01.jsonl.gz"),
+            gz.finish().unwrap(),
+        )
+        .unwrap();
+        std::fs::write(sessions.join("archive/broken.jsonl.gz"), "not gzip").unwrap();
+
+        let run_limited = |decompress_sessions: bool, sessions_size_limit: Option<u64>| {
+            let target = TempDir::new().unwrap();
+            let report = migrate(&MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                decompress_sessions,
+                sessions_size_limit,
+                ..Default::default()
+            })
+            .unwrap();
+            (target, report)
+        };
+        let run = |decompress_sessions: bool| run_limited(decompress_sessions, None);
+
+        let (target, report) = run(false);
+        let imported = target.path().join("agents/main/imported_sessions");
+        assert!(imported.join("main.jsonl").exists());
+        assert!(!imported.join("archive").exists());
+        assert!(report.warnings.iter().any(|w| w
+            == "sessions/: skipped 1 (no extension), 1 .json, 2 .jsonl.gz, 1 .jsonl~ \u{2014} \
+                only plain .jsonl is migrated; set decompress_sessions to include .jsonl.gz archives"));
+
+        let (target, report) = run(true);
+        let imported = target.path().join("agents/main/imported_sessions");
+        let archived = std::fs::read_to_string(imported.join("archive/2024-01.jsonl")).unwrap();
+        assert_eq!(archived, line);
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("sessions/archive/broken.jsonl.gz: could not decompress")));
+        assert!(report.warnings.iter().any(|w| w
+            == "sessions/: skipped 1 (no extension), 1 .json, 1 .jsonl~ \u{2014} \
+                only plain .jsonl is migrated"));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "main: 2 session files"));
+
+        // Decompression stops at the size budget, so a bomb can't fill memory
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        gz.write_all(&line.as_bytes().repeat(100_000)).unwrap();
+        std::fs::write(sessions.join("archive/bomb.jsonl.gz"), gz.finish().unwrap()).unwrap();
+        let (target, report) = run_limited(true, Some(64 * 1024));
+        let imported = target.path().join("agents/main/imported_sessions");
+        assert!(!imported.join("archive/bomb.jsonl").exists());
+        assert!(imported.join("archive/2024-01.jsonl").exists());
+        assert!(report.warnings.iter().any(|w| w
+            == "sessions/archive/bomb.jsonl.gz: could not decompress (decompresses to more \
+                than 64.0 KB); it was not migrated"));
+    }
+
+    #[test]
+    fn test_scan_gzip() {
+        use std::io::Write;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("log.jsonl.gz");
+        let text = "{\"ts\":\"2024-03-01T00:00:00Z\"}\n{\"ts\":\"2024-01-01T00:00:00Z\"}\n";
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(text.as_bytes()).unwrap();
+        std::fs::write(&path, gz.finish().unwrap()).unwrap();
+
+        let (size, newest) = scan_gzip(&path, 1024, true).unwrap();
+        assert_eq!(size, text.len() as u64);
+        assert_eq!(newest.unwrap().to_rfc3339(), "2024-03-01T00:00:00+00:00");
+        assert_eq!(scan_gzip(&path, 1024, false).unwrap(), (size, None));
+        assert!(scan_gzip(&path, 16, false).is_err());
+    }
+
+    #[test]
+    fn test_sessions_since_cutoff() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::write(
+            sessions.join("old.jsonl"),
+            "{\"role\":\"user\",\"content\":\"hi\",\"ts\":\"2024-01-01T00:00:00Z\"}\n\
+             {\"role\":\"assistant\",\"content\":\"hello\",\"ts\":\"2024-01-02T08:00:00Z\"}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            sessions.join("new.jsonl"),
+            // Older first line, recent last line (epoch millis)
+            "{\"role\":\"user\",\"content\":\"hi\",\"timestamp\":1704067200000}\n\
+             {\"role\":\"user\",\"content\":\"again\",\"timestamp\":1767225600000}\n\n",
+        )
+        .unwrap();
+        std::fs::write(sessions.join("undated.jsonl"), "{\"role\":\"user\"}\n").unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            sessions_since: Some("2025-06-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let imported = target.path().join("imported_sessions");
+        assert!(imported.join("new.jsonl").exists());
+        assert!(imported.join("undated.jsonl").exists());
+        assert!(!imported.join("old.jsonl").exists());
+        assert!(report.imported.iter().any(|i| i.name == "2 session files"));
+        let skipped = report
+            .skipped
+            .iter()
+            .find(|s| s.kind == ItemKind::Session)
+            .unwrap();
+        assert_eq!(skipped.name, "1 session files (126 B)");
+        assert!(skipped.reason.contains("2025-06-01"));
+    }
+
+    #[test]
+    fn test_sessions_max_age_days() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        let now = chrono::Utc::now();
+        let line = |t: chrono::DateTime<chrono::Utc>| {
+            format!(
+                "{{\"role\":\"user\",\"content\":\"hi\",\"ts\":\"{}\"}}\n",
+                t.to_rfc3339()
+            )
+        };
+        let write = |name: &str, content: &str, modified_days_ago: i64| {
+            let path = sessions.join(name);
+            std::fs::write(&path, content).unwrap();
+            let modified = now - chrono::Duration::days(modified_days_ago);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified.into())
+                .unwrap();
+        };
+        // The newest embedded timestamp wins over the file time
+        let recent =
+            line(now - chrono::Duration::days(200)) + &line(now - chrono::Duration::days(1));
+        write("recent.jsonl", &recent, 400);
+        let stale = line(now - chrono::Duration::days(200));
+        write("stale.jsonl", &stale, 0);
+        // Without timestamps the file time decides
+        write("undated-old.jsonl", "{\"role\":\"user\"}\n", 120);
+        write("undated-new.jsonl", "{\"role\":\"user\"}\n", 3);
+
+        let run = |dry_run: bool| {
+            let target = TempDir::new().unwrap();
+            let report = migrate(&MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                sessions_max_age_days: Some(90),
+                dry_run,
+                ..Default::default()
+            })
+            .unwrap();
+            (target, report)
+        };
+
+        let excluded = format!(
+            "2 session files ({})",
+            format_bytes((stale.len() + "{\"role\":\"user\"}\n".len()) as u64)
+        );
+        let (target, report) = run(true);
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.kind == ItemKind::Session && s.name == excluded));
+        assert!(!target.path().join("imported_sessions").exists());
+
+        let (target, report) = run(false);
+        assert!(report.skipped.iter().any(|s| s.name == excluded));
+        let imported = target.path().join("imported_sessions");
+        assert!(imported.join("recent.jsonl").exists());
+        assert!(imported.join("undated-new.jsonl").exists());
+        assert!(!imported.join("stale.jsonl").exists());
+        assert!(!imported.join("undated-old.jsonl").exists());
+    }
+
+    #[test]
+    fn test_sessions_size_limit() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        let now = chrono::Utc::now();
+        // Name order differs from age order; only the file time dates these
+        let write = |name: &str, size: usize, modified_days_ago: i64| {
+            let path = sessions.join(name);
+            let pad = "x".repeat(size - "{\"role\":\"user\",\"pad\":\"\"}\n".len());
+            std::fs::write(&path, format!("{{\"role\":\"user\",\"pad\":\"{pad}\"}}\n")).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified((now - chrono::Duration::days(modified_days_ago)).into())
+                .unwrap();
+        };
+        write("a-second.jsonl", 400, 2);
+        write("b-oldest.jsonl", 100, 4);
+        write("c-newest.jsonl", 400, 1);
+        // Would fit the remaining budget, but is older than a log that did not
+        write("d-third.jsonl", 100, 3);
+
+        let run = |dry_run: bool| {
+            let target = TempDir::new().unwrap();
+            let report = migrate(&MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                sessions_size_limit: Some(crate::report::parse_size("850B").unwrap()),
+                dry_run,
+                ..Default::default()
+            })
+            .unwrap();
+            (target, report)
+        };
+
+        let (_, preview) = run(true);
+        let (target, report) = run(false);
+        for report in [&preview, &report] {
+            let skipped = report
+                .skipped
+                .iter()
+                .find(|s| s.kind == ItemKind::Session)
+                .unwrap();
+            assert_eq!(skipped.name, "2 session files (200 B)");
+            assert!(skipped.reason.contains("raise sessions_size_limit"));
+            assert!(report.imported.iter().any(|i| i.name == "2 session files"));
+        }
+        let imported = target.path().join("imported_sessions");
+        assert!(imported.join("c-newest.jsonl").exists());
+        assert!(imported.join("a-second.jsonl").exists());
+        assert!(!imported.join("d-third.jsonl").exists());
+        assert!(!imported.join("b-oldest.jsonl").exists());
+        assert_eq!(report.bytes_copied, 800);
+    }
+
+    #[test]
+    fn test_json5_dry_run() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        create_json5_workspace(source.path());
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+        assert!(report.dry_run);
+        assert!(!report.imported.is_empty());
+
+        // No files created
+        assert!(!target.path().join("config.toml").exists());
+        assert!(!target.path().join("agents").exists());
+        assert!(!target.path().join("imported_sessions").exists());
+    }
+
+    #[test]
+    fn test_dry_run_conflicts() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        create_json5_workspace(source.path());
+        std::fs::write(target.path().join("config.toml"), "# existing\n").unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(report.conflicts[0].ends_with("config.toml"));
+
+        // Pre-existing file untouched
+        let content = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
+        assert_eq!(content, "# existing\n");
+    }
+
+    #[test]
+    fn test_json5_empty_config() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+
+        // Should still produce a config
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
+        assert!(target.path().join("config.toml").exists());
+
+        // No agents should be an info, not crash
+        assert!(report.warnings.iter().any(|w| w.contains("No agents")));
+    }
+
+    #[test]
+    fn test_model_ref_split() {
+        let (p, m) = split_model_ref("anthropic/claude-sonnet-4-20250514");
+        assert_eq!(p, "anthropic");
+        assert_eq!(m, "claude-sonnet-4-20250514");
+
+        let (p, m) = split_model_ref("deepseek/deepseek-chat");
+        assert_eq!(p, "deepseek");
+        assert_eq!(m, "deepseek-chat");
+
+        let (p, m) = split_model_ref("google/gemini-2.5-flash");
+        assert_eq!(p, "google");
+        assert_eq!(m, "gemini-2.5-flash");
+
+        let (p, m) = split_model_ref("groq/llama-3.3-70b-versatile");
+        assert_eq!(p, "groq");
+        assert_eq!(m, "llama-3.3-70b-versatile");
+
+        // No slash
+        let (p, m) = split_model_ref("some-model");
+        assert_eq!(p, "anthropic");
+        assert_eq!(m, "some-model");
+
+        // Empty
+        let (p, m) = split_model_ref("");
+        assert_eq!(p, "anthropic");
+        assert_eq!(m, "");
+
+        // Colon separator and provider casing; model case is preserved
+        let (p, m) = split_model_ref("OpenAI:gpt-4o");
+        assert_eq!(p, "openai");
+        assert_eq!(m, "gpt-4o");
+
+        let (p, m) = split_model_ref("Anthropic/Claude-3");
+        assert_eq!(p, "anthropic");
+        assert_eq!(m, "Claude-3");
+
+        // Ollama tags are not a provider separator
+        let (p, m) = split_model_ref("ollama/llama3:8b");
+        assert_eq!(p, "ollama");
+        assert_eq!(m, "llama3:8b");
+        let (p, m) = split_model_ref("llama3:8b");
+        assert_eq!(p, "anthropic");
+        assert_eq!(m, "llama3:8b");
+    }
+
+    #[test]
+    fn test_json5_unknown_provider_passthrough() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        let json5_content = r#"{
+  agents: {
+    list: [
+      { id: "test-agent", model: "mycompany/custom-llm-v3" },
+      { id: "local", model: "vllm/llama-3-8b" }
+    ]
+  }
+}"#;
+        std::fs::write(source.path().join("openclaw.json"), json5_content).unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Agent));
+
+        let agent_toml =
+            std::fs::read_to_string(target.path().join("agents/test-agent/agent.toml")).unwrap();
+        assert!(agent_toml.contains("provider = \"mycompany\""));
+        assert!(agent_toml.contains("model = \"custom-llm-v3\""));
+        assert!(agent_toml.contains("api_key_env = \"MYCOMPANY_API_KEY\""));
+
+        // Written anyway, but flagged; providers the runtime knows are not
+        let unrecognized: Vec<&String> = report
+            .warnings
+            .iter()
+            .filter(|w| w.contains("unrecognized provider"))
+            .collect();
+        assert_eq!(unrecognized.len(), 1, "{unrecognized:?}");
+        assert!(
+            unrecognized[0].starts_with("Agent 'test-agent': unrecognized provider 'mycompany'")
+        );
+    }
+
+    // ================================================================
+    // Existing tests (kept — now test YAML legacy path + shared utils)
+    // ================================================================
+
+    #[test]
+    fn test_full_migration() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        create_legacy_yaml_workspace(source.path());
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+
+        assert!(!report.imported.is_empty());
+        assert!(!target.path().join("rejected").exists());
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Config));
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Agent));
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Memory));
+        assert!(report.imported.iter().any(|i| i.kind == ItemKind::Channel));
+
+        assert!(target.path().join("config.toml").exists());
+        assert!(target.path().join("agents/coder/agent.toml").exists());
+        assert!(target
+            .path()
+            .join("agents/coder/imported_memory.md")
+            .exists());
+
+        let agent_toml =
+            std::fs::read_to_string(target.path().join("agents/coder/agent.toml")).unwrap();
+        assert!(
+            agent_toml.contains("shell = [\"*\"]"),
+            "shell_exec should derive shell capability"
+        );
+        assert!(agent_toml.contains("file_read"));
+        assert!(agent_toml.contains("file_write"));
+        assert!(agent_toml.contains("shell_exec"));
+
+        let config_toml = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
         assert!(config_toml.contains("[channels.telegram]"));
         assert!(!target.path().join("channels_import.toml").exists());
 
-        assert!(target.path().join("migration_report.md").exists());
+        assert!(target.path().join("migration_report.md").exists());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        create_legacy_yaml_workspace(source.path());
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+        assert!(report.dry_run);
+        assert!(!report.imported.is_empty());
+
+        assert!(!target.path().join("config.toml").exists());
+    }
+
+    #[test]
+    fn test_source_not_found() {
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: "/nonexistent/path".into(),
+            target_dir: std::env::temp_dir().join("test_migrate_not_found"),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let result = migrate(&options);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_tool_mapping() {
+        assert_eq!(map_tool_name("read_file"), Some("file_read"));
+        assert_eq!(map_tool_name("write_file"), Some("file_write"));
+        assert_eq!(map_tool_name("execute_command"), Some("shell_exec"));
+        assert_eq!(map_tool_name("fetch_url"), Some("web_fetch"));
+        assert_eq!(map_tool_name("memory_search"), Some("memory_recall"));
+        assert_eq!(map_tool_name("unknown_tool"), None);
+        // New Claude-style mappings
+        assert_eq!(map_tool_name("Read"), Some("file_read"));
+        assert_eq!(map_tool_name("Write"), Some("file_write"));
+        assert_eq!(map_tool_name("Bash"), Some("shell_exec"));
+        assert_eq!(map_tool_name("Glob"), Some("file_list"));
+        assert_eq!(map_tool_name("Grep"), Some("file_list"));
+        assert_eq!(map_tool_name("WebSearch"), Some("web_search"));
+        assert_eq!(map_tool_name("WebFetch"), Some("web_fetch"));
+        assert_eq!(map_tool_name("sessions_send"), Some("agent_send"));
+        assert_eq!(map_tool_name("sessions_spawn"), Some("agent_send"));
+    }
+
+    #[test]
+    fn test_provider_mapping() {
+        assert_eq!(map_provider("anthropic"), "anthropic");
+        assert_eq!(map_provider("claude"), "anthropic");
+        assert_eq!(map_provider("openai"), "openai");
+        assert_eq!(map_provider("gpt"), "openai");
+        assert_eq!(map_provider("groq"), "groq");
+        assert_eq!(map_provider("custom"), "custom");
+        assert_eq!(map_provider("google"), "google");
+        assert_eq!(map_provider("gemini"), "google");
+        assert_eq!(map_provider("xai"), "xai");
+        assert_eq!(map_provider("grok"), "xai");
+        assert_eq!(map_provider("azure"), "azure-openai");
+        assert_eq!(map_provider("Azure-OpenAI"), "azure-openai");
+        assert_eq!(map_provider("bedrock"), "bedrock");
+        assert_eq!(map_provider("aws-bedrock"), "bedrock");
+        assert_eq!(map_provider("vertex"), "vertex");
+        assert_eq!(map_provider("vertex-ai"), "vertex");
+    }
+
+    #[test]
+    fn test_cloud_provider_model_refs() {
+        let r = parse_model_ref("azure/gpt-4o@my-deployment");
+        assert_eq!(r.provider, "azure-openai");
+        assert_eq!(r.model, "gpt-4o");
+        assert_eq!(r.deployment.as_deref(), Some("my-deployment"));
+
+        // No deployment suffix
+        let r = parse_model_ref("azure/gpt-4o");
+        assert_eq!(r.model, "gpt-4o");
+        assert!(r.deployment.is_none());
+
+        // `@` only means deployment for Azure
+        let r = parse_model_ref("bedrock/anthropic.claude-sonnet-4");
+        assert_eq!(r.provider, "bedrock");
+        assert_eq!(r.model, "anthropic.claude-sonnet-4");
+        assert!(r.deployment.is_none());
+
+        let (p, m) = split_model_ref("vertex/gemini-2.5-pro");
+        assert_eq!(p, "vertex");
+        assert_eq!(m, "gemini-2.5-pro");
+
+        assert_eq!(default_api_key_env("azure-openai"), "AZURE_OPENAI_API_KEY");
+        assert_eq!(default_api_key_env("bedrock"), "AWS_ACCESS_KEY_ID");
+        assert_eq!(
+            default_api_key_env("vertex"),
+            "GOOGLE_APPLICATION_CREDENTIALS"
+        );
+        assert!(provider_credential_note("bedrock").is_some());
+        assert!(provider_credential_note("openai").is_none());
+    }
+
+    #[test]
+    fn test_cloud_providers_need_base_url() {
+        for provider in ["azure-openai", "bedrock", "vertex"] {
+            assert!(!is_openfang_provider(provider, None));
+            assert!(is_openfang_provider(
+                provider,
+                Some("http://litellm:4000/v1")
+            ));
+        }
+        assert!(is_openfang_provider("anthropic", None));
+        assert!(is_openfang_provider("vllm", None));
+        // Still recognized as a provider prefix
+        assert_eq!(
+            parse_model_ref("bedrock:anthropic.claude-sonnet-4").provider,
+            "bedrock"
+        );
+        assert!(bare_model_warning("vertex:gemini-2.5-pro").is_none());
+    }
+
+    #[test]
+    fn test_normalize_model_id() {
+        let cases = [
+            (
+                "anthropic",
+                "claude-3-5-sonnet-20240620",
+                "claude-sonnet-4-20250514",
+            ),
+            (
+                "anthropic",
+                "claude-3-opus-20240229",
+                "claude-opus-4-20250514",
+            ),
+            ("openai", "gpt-4-turbo", "gpt-4o"),
+            ("openai", "gpt-3.5-turbo", "gpt-4o-mini"),
+            ("google", "gemini-1.5-pro", "gemini-2.5-pro"),
+            ("google", "gemini-1.5-flash", "gemini-2.5-flash"),
+        ];
+        for (provider, retired, successor) in cases {
+            let (model, note) = normalize_model_id(provider, retired);
+            assert_eq!(model, successor);
+            let note = note.unwrap();
+            assert!(note.contains(retired) && note.contains(successor));
+        }
+
+        // Current IDs pass through untouched
+        let (model, note) = normalize_model_id("anthropic", "claude-sonnet-4-20250514");
+        assert_eq!(model, "claude-sonnet-4-20250514");
+        assert!(note.is_none());
+
+        // Aliases are provider-scoped
+        let (model, note) = normalize_model_id("openrouter", "gpt-4-turbo");
+        assert_eq!(model, "gpt-4-turbo");
+        assert!(note.is_none());
+    }
+
+    #[test]
+    fn test_json5_retired_model_alias() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      {
+        id: "old",
+        model: { primary: "openai/gpt-4-turbo", fallbacks: ["google/gemini-1.5-pro"] }
+      }
+    ]
+  }
+}"#;
+
+        // Default: remapped, with a per-agent warning
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), json5_content).unwrap();
+        let options = MigrateOptions {
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let agent_toml =
+            std::fs::read_to_string(target.path().join("agents/old/agent.toml")).unwrap();
+        assert!(agent_toml.contains("model = \"gpt-4o\""));
+        assert!(agent_toml.contains("model = \"gemini-2.5-pro\""));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'old'") && w.contains("gpt-4-turbo is retired")));
+
+        // Opt-out: IDs preserved verbatim
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            preserve_model_ids: true,
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let agent_toml =
+            std::fs::read_to_string(target.path().join("agents/old/agent.toml")).unwrap();
+        assert!(agent_toml.contains("model = \"gpt-4-turbo\""));
+        assert!(agent_toml.contains("model = \"gemini-1.5-pro\""));
+        assert!(!report.warnings.iter().any(|w| w.contains("retired")));
+    }
+
+    #[test]
+    fn test_json5_cloud_provider_agent() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+
+        let json5_content = r#"{
+  models: { providers: { "amazon-bedrock": { baseUrl: "http://litellm:4000/v1" } } },
+  agents: {
+    list: [
+      {
+        id: "cloud",
+        model: {
+          primary: "azure/gpt-4o@prod-gpt4o",
+          fallbacks: ["bedrock/anthropic.claude-sonnet-4", "vertex/gemini-2.5-pro"]
+        }
+      }
+    ]
+  }
+}"#;
+        std::fs::write(source.path().join("openclaw.json"), json5_content).unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: false,
+            ..Default::default()
+        };
+
+        let report = migrate(&options).unwrap();
+
+        let agent_toml =
+            std::fs::read_to_string(target.path().join("agents/cloud/agent.toml")).unwrap();
+        let parsed: toml::Value = toml::from_str(&agent_toml).unwrap();
+        let model = parsed["model"].as_table().unwrap();
+        assert_eq!(model["provider"].as_str().unwrap(), "azure-openai");
+        assert_eq!(model["model"].as_str().unwrap(), "gpt-4o");
+        // No manifest field for the deployment, and no driver without a base_url
+        assert!(model.get("deployment").is_none());
+        assert!(model.get("base_url").is_none());
+        let cloud_warnings: Vec<&String> = report
+            .warnings
+            .iter()
+            .filter(|w| w.starts_with("Agent 'cloud': "))
+            .collect();
+        assert!(cloud_warnings
+            .iter()
+            .any(|w| w.contains("Azure deployment 'prod-gpt4o' was dropped")));
+        assert!(cloud_warnings
+            .iter()
+            .any(|w| w.contains("unrecognized provider 'azure-openai'")));
+        assert!(cloud_warnings
+            .iter()
+            .any(|w| w.contains("unrecognized provider 'vertex'")));
+        // Bedrock is reached through the configured proxy
+        assert!(!cloud_warnings
+            .iter()
+            .any(|w| w.contains("unrecognized provider 'bedrock'")));
+        assert_eq!(
+            model["api_key_env"].as_str().unwrap(),
+            "AZURE_OPENAI_API_KEY"
+        );
+
+        let fallbacks = parsed["fallback_models"].as_array().unwrap();
+        assert_eq!(fallbacks[0]["provider"].as_str().unwrap(), "bedrock");
+        assert_eq!(
+            fallbacks[0]["base_url"].as_str().unwrap(),
+            "http://litellm:4000/v1"
+        );
+        assert_eq!(
+            fallbacks[0]["api_key_env"].as_str().unwrap(),
+            "AWS_ACCESS_KEY_ID"
+        );
+        assert_eq!(fallbacks[1]["provider"].as_str().unwrap(), "vertex");
+        assert_eq!(
+            fallbacks[1]["api_key_env"].as_str().unwrap(),
+            "GOOGLE_APPLICATION_CREDENTIALS"
+        );
+
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("cloud") && w.contains("AWS_SECRET_ACCESS_KEY")));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("cloud") && w.contains("GOOGLE_APPLICATION_CREDENTIALS")));
+    }
+
+    #[test]
+    fn test_tools_for_profile() {
+        let minimal = tools_for_profile("minimal");
+        assert_eq!(minimal.len(), 2);
+        assert!(minimal.contains(&"file_read".to_string()));
+
+        let coding = tools_for_profile("coding");
+        assert!(coding.contains(&"shell_exec".to_string()));
+
+        let full = tools_for_profile("full");
+        assert!(full.contains(&"*".to_string()));
+
+        let automation = tools_for_profile("automation");
+        assert!(automation.len() >= 10);
+        assert!(automation.contains(&"shell_exec".to_string()));
+        assert!(automation.contains(&"web_fetch".to_string()));
+
+        let browser = tools_for_profile("browser");
+        assert!(browser.contains(&"browser_navigate".to_string()));
+        assert!(!browser.contains(&"shell_exec".to_string()));
+        assert!(!browser.contains(&"*".to_string()));
+
+        let devops = tools_for_profile("devops");
+        assert!(devops.contains(&"shell_exec".to_string()));
+        assert!(devops.contains(&"docker_exec".to_string()));
+        assert!(!devops.contains(&"*".to_string()));
+
+        let writing = tools_for_profile("writing");
+        assert!(writing.contains(&"file_write".to_string()));
+        assert!(!writing.contains(&"shell_exec".to_string()));
+
+        for known in ["minimal", "browser", "devops", "writing", "full"] {
+            assert!(unknown_profile_warning(known).is_none(), "{known}");
+        }
+        let warning = unknown_profile_warning("kitchen-sink").unwrap();
+        assert!(warning.contains("'kitchen-sink'") && warning.contains("Full"));
+        assert_eq!(tools_for_profile("kitchen-sink"), vec!["*"]);
+    }
+
+    #[test]
+    fn test_convert_agent() {
+        let dir = TempDir::new().unwrap();
+        let yaml_path = dir.path().join("agent.yaml");
+        std::fs::write(
+            &yaml_path,
+            "name: test-agent\ndescription: Test\ntools:\n  - read_file\n  - web_search\n",
+        )
+        .unwrap();
+
+        let converted = convert_legacy_agent(
+            &yaml_path,
+            "test-agent",
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let toml_str = converted.toml;
+        let unmapped = converted.unmapped_tools;
+        assert!(toml_str.contains("name = \"test-agent\""));
+        assert!(toml_str.contains("file_read"));
+        assert!(toml_str.contains("web_search"));
+        assert!(
+            toml_str.contains("network = [\"*\"]"),
+            "web_search should derive network capability"
+        );
+        assert!(unmapped.is_empty());
+    }
+
+    #[test]
+    fn test_legacy_agent_profile_hint() {
+        let dir = TempDir::new().unwrap();
+        let yaml_path = dir.path().join("agent.yaml");
+        std::fs::write(&yaml_path, "name: scout\ntool_profile: research\n").unwrap();
+
+        let converted = convert_legacy_agent(
+            &yaml_path,
+            "scout",
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let parsed: toml::Value = toml::from_str(&converted.toml).unwrap();
+        assert_eq!(parsed["profile"].as_str(), Some("research"));
+        assert!(parsed["capabilities"].get("profile").is_none());
+        assert!(converted.toml.contains("web_search"));
+
+        // A profile OpenFang doesn't know stays out of the manifest keys
+        std::fs::write(&yaml_path, "name: odd\ntool_profile: kitchen-sink\n").unwrap();
+        let converted = convert_legacy_agent(
+            &yaml_path,
+            "odd",
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let parsed: toml::Value = toml::from_str(&converted.toml).unwrap();
+        assert!(parsed.get("profile").is_none());
+        assert!(converted
+            .toml
+            .contains("# OpenClaw tool profile 'kitchen-sink' has no OpenFang equivalent"));
+        assert!(converted
+            .warnings
+            .iter()
+            .any(|w| w.contains("Unknown tool profile 'kitchen-sink'")));
+
+        // No profile → no hint
+        std::fs::write(&yaml_path, "name: plain\ntools:\n  - read_file\n").unwrap();
+        let converted = convert_legacy_agent(
+            &yaml_path,
+            "plain",
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        assert!(!converted.toml.contains("profile ="));
+    }
+
+    #[test]
+    fn test_capability_derivation() {
+        let tools = vec!["shell_exec".into(), "web_fetch".into(), "agent_send".into()];
+        let caps = derive_capabilities(&tools);
+        assert_eq!(caps.shell, vec!["*".to_string()]);
+        assert_eq!(caps.network, vec!["*".to_string()]);
+        assert_eq!(caps.agent_message, vec!["*".to_string()]);
+        assert!(caps.agent_spawn);
+    }
+
+    #[test]
+    fn test_unmapped_tools_reported() {
+        let dir = TempDir::new().unwrap();
+        let yaml_path = dir.path().join("agent.yaml");
+        std::fs::write(
+            &yaml_path,
+            "name: test\ntools:\n  - read_file\n  - some_custom_tool\n  - another_unknown\n",
+        )
+        .unwrap();
+
+        let converted = convert_legacy_agent(
+            &yaml_path,
+            "test",
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let toml_str = converted.toml;
+        let unmapped = converted.unmapped_tools;
+        assert!(toml_str.contains("file_read"));
+        // Only kept as a trailing comment, never as an active tool
+        let manifest: toml::Value = toml::from_str(&toml_str).unwrap();
+        let tools = manifest["capabilities"]["tools"].as_array().unwrap();
+        assert!(!tools.iter().any(|t| t.as_str() == Some("some_custom_tool")));
+        assert!(toml_str.contains("# unmapped tools: some_custom_tool, another_unknown"));
+        assert_eq!(unmapped.len(), 2);
+        assert!(unmapped.contains(&"some_custom_tool".to_string()));
+        assert!(unmapped.contains(&"another_unknown".to_string()));
+    }
+
+    #[test]
+    fn test_scan_workspace() {
+        let source = TempDir::new().unwrap();
+        create_legacy_yaml_workspace(source.path());
+
+        let result = scan_openclaw_workspace(source.path());
+        assert!(result.has_config);
+        assert_eq!(result.agents.len(), 1);
+        assert_eq!(result.agents[0].name, "coder");
+        assert!(result.agents[0].has_memory);
+        assert_eq!(result.channels.len(), 1);
+        assert!(result.channels.contains(&"telegram".to_string()));
+    }
+
+    #[test]
+    fn test_scan_without_config() {
+        let source = TempDir::new().unwrap();
+        let memory = source.path().join("memory/coder");
+        std::fs::create_dir_all(&memory).unwrap();
+        std::fs::write(memory.join("MEMORY.md"), "- likes Rust\n").unwrap();
+        std::fs::create_dir_all(source.path().join("workspaces/writer")).unwrap();
+        std::fs::create_dir_all(source.path().join("sessions")).unwrap();
+
+        let result = scan_openclaw_workspace(source.path());
+        assert!(!result.has_config);
+        assert!(result.has_memory);
+        assert_eq!(result.agent_count, 2);
+        let coder = result.agents.iter().find(|a| a.name == "coder").unwrap();
+        assert!(coder.has_memory && coder.has_sessions && !coder.has_workspace);
+        let writer = result.agents.iter().find(|a| a.name == "writer").unwrap();
+        assert!(writer.has_workspace && !writer.has_memory);
+    }
+
+    #[test]
+    fn test_scan_json5_workspace() {
+        let source = TempDir::new().unwrap();
+        create_json5_workspace(source.path());
+
+        let result = scan_openclaw_workspace(source.path());
+        assert!(result.has_config);
+        assert_eq!(result.agents.len(), 2);
+        let coder = result.agents.iter().find(|a| a.name == "Coder").unwrap();
+        assert_eq!(coder.description, "Writes and reviews \"production\" code");
+        let researcher = result
+            .agents
+            .iter()
+            .find(|a| a.name == "researcher")
+            .unwrap();
+        assert!(researcher.description.is_empty());
+        // All 13 channels detected by scanner
+        assert_eq!(
+            result.channels.len(),
+            13,
+            "expected 13 channels, got {:?}",
+            result.channels
+        );
+        assert!(result.channels.contains(&"telegram".to_string()));
+        assert!(result.channels.contains(&"discord".to_string()));
+        assert!(result.channels.contains(&"slack".to_string()));
+        assert!(result.channels.contains(&"whatsapp".to_string()));
+        assert!(result.channels.contains(&"signal".to_string()));
+        assert!(result.channels.contains(&"matrix".to_string()));
+        assert!(result.channels.contains(&"irc".to_string()));
+        assert!(result.channels.contains(&"mattermost".to_string()));
+        assert!(result.channels.contains(&"feishu".to_string()));
+        assert!(result.channels.contains(&"teams".to_string()));
+        assert!(result.channels.contains(&"imessage".to_string()));
+        assert!(result.channels.contains(&"bluebubbles".to_string()));
+        assert!(result.has_memory);
+    }
+
+    #[test]
+    fn test_json5_bom_and_crlf() {
+        let dir = TempDir::new().unwrap();
+        let json5 = "\u{feff}{\r\n  // comment\r\n  agents: {\r\n    list: [\r\n      { id: \"coder\", },\r\n    ],\r\n  },\r\n}\r\n";
+        let config_path = dir.path().join("openclaw.json");
+        std::fs::write(&config_path, json5).unwrap();
+
+        let root = parse_openclaw_json5(json5, &config_path).unwrap();
+        assert_eq!(root.agents.unwrap().list[0].id, "coder");
+
+        let result = scan_openclaw_workspace(dir.path());
+        assert_eq!(result.agents.len(), 1);
+
+        // Errors point at the byte offset within the original file
+        let broken = "\u{feff}{\r\n  agents: ]\r\n}";
+        let err = parse_openclaw_json5(broken, &config_path)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at byte"), "{err}");
+        let offset: usize = err
+            .split("at byte ")
+            .nth(1)
+            .and_then(|s| s.split(' ').next())
+            .and_then(|s| s.parse().ok())
+            .unwrap();
+        assert_eq!(&broken[offset..offset + 1], "]");
+    }
+
+    #[test]
+    fn test_sanitize_agent_id() {
+        assert_eq!(sanitize_agent_id("agent:coder"), "agent-coder");
+        assert_eq!(sanitize_agent_id("team/Research Bot"), "team-research-bot");
+        assert_eq!(sanitize_agent_id("a::\\//b"), "a-b");
+        assert_eq!(sanitize_agent_id("Ärger"), "ärger");
+        assert_eq!(sanitize_agent_id("coder"), "coder");
+        assert_eq!(sanitize_agent_id(":/"), "agent");
+        assert_eq!(sanitize_agent_id(".."), "agent");
+    }
+
+    #[test]
+    fn test_unsafe_agent_ids_migrated() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "agent:coder" }, { id: "team/ops" }, { id: "Agent Coder" } ] } }"#,
+        )
+        .unwrap();
+        let mem = source.path().join("memory").join("agent:coder");
+        std::fs::create_dir_all(&mem).unwrap();
+        std::fs::write(mem.join("MEMORY.md"), "remember this").unwrap();
+        let ws = source.path().join("workspaces").join("Agent Coder");
+        std::fs::create_dir_all(&ws).unwrap();
+        std::fs::write(ws.join("notes.txt"), "hi").unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let agents = target.path().join("agents");
+        let coder = std::fs::read_to_string(agents.join("agent-coder/agent.toml")).unwrap();
+        let manifest: toml::Value = toml::from_str(&coder).unwrap();
+        assert_eq!(
+            manifest["metadata"]["source_id"].as_str(),
+            Some("agent:coder")
+        );
+        assert!(agents.join("team-ops/agent.toml").exists());
+        assert!(agents.join("agent-coder/imported_memory.md").exists());
+
+        // "Agent Coder" collides with "agent:coder" and gets a suffix
+        assert!(agents.join("agent-coder-2/agent.toml").exists());
+        assert!(agents.join("agent-coder-2/workspace/notes.txt").exists());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'Agent Coder' collides") && w.contains("agent-coder-2")));
+    }
+
+    #[test]
+    fn test_duplicate_agent_ids() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "coder", identity: "First" },
+                { id: "coder", identity: "Second" },
+            ] } }"#,
+        )
+        .unwrap();
+
+        // Default policy: the later definition is renamed
+        let target = TempDir::new().unwrap();
+        let mut options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        let first = std::fs::read_to_string(agents.join("coder/agent.toml")).unwrap();
+        let second = std::fs::read_to_string(agents.join("coder-2/agent.toml")).unwrap();
+        assert!(first.contains("First"));
+        assert!(second.contains("Second"));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'coder' is defined more than once")
+                && w.contains("agents/coder/")
+                && w.contains("agents/coder-2/")));
+
+        // Skip policy: the first wins and the second is reported
+        let target = TempDir::new().unwrap();
+        options.target_dir = target.path().to_path_buf();
+        options.duplicate_agents = DuplicateAgentPolicy::Skip;
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        let first = std::fs::read_to_string(agents.join("coder/agent.toml")).unwrap();
+        assert!(first.contains("First"));
+        assert!(!agents.join("coder-2").exists());
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.name == "coder" && s.reason.contains("Duplicate agent id")));
+
+        // Bare duplicate entries never overwrite each other's manifest
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "coder" }, { id: "coder" }] } }"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        options.target_dir = target.path().to_path_buf();
+        options.duplicate_agents = DuplicateAgentPolicy::Rename;
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        assert!(agents.join("coder/agent.toml").exists());
+        assert!(agents.join("coder-2/agent.toml").exists());
+        assert_eq!(
+            report
+                .imported
+                .iter()
+                .filter(|i| i.kind == ItemKind::Agent)
+                .count(),
+            2
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("defined more than once")));
+    }
+
+    #[test]
+    fn test_legacy_agent_dir_collision() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("config.yaml"), "provider: anthropic\n").unwrap();
+        for name in ["Coder", "coder"] {
+            let dir = source.path().join("agents").join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("agent.yaml"), format!("name: {name}\n")).unwrap();
+        }
+
+        let target = TempDir::new().unwrap();
+        let mut options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        assert!(agents.join("coder/agent.toml").exists());
+        assert!(agents.join("coder-2/agent.toml").exists());
+        assert!(report.warnings.iter().any(|w| w.contains("collides")));
+
+        let target = TempDir::new().unwrap();
+        options.target_dir = target.path().to_path_buf();
+        options.duplicate_agents = DuplicateAgentPolicy::Skip;
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        assert!(agents.join("coder/agent.toml").exists());
+        assert!(!agents.join("coder-2").exists());
+        assert_eq!(
+            report
+                .imported
+                .iter()
+                .filter(|i| i.kind == ItemKind::Agent)
+                .count(),
+            1
+        );
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.name == "coder" && s.reason.contains("collides with 'Coder'")));
+    }
+
+    #[test]
+    fn test_invalid_manifest_rejected() {
+        let target = TempDir::new().unwrap();
+        let mut report = MigrationReport::default();
+
+        let valid = "name = \"ok\"\n";
+        assert!(!reject_invalid_manifest(
+            "ok",
+            "ok",
+            valid,
+            target.path(),
+            false,
+            &mut report
+        ));
+        assert!(report.skipped.is_empty());
+
+        // max_tokens overflows the kernel's u32 field
+        let invalid = "name = \"huge\"\n\n[model]\nmax_tokens = 99999999999\n";
+        assert!(reject_invalid_manifest(
+            "huge",
+            "huge",
+            invalid,
+            target.path(),
+            false,
+            &mut report
+        ));
+        let skipped = report
+            .skipped
+            .iter()
+            .find(|s| s.name == "huge")
+            .expect("invalid manifest should be skipped");
+        assert!(skipped.reason.contains("agent.toml is invalid"));
+        let dump = target.path().join("rejected/huge.toml");
+        assert!(skipped.reason.contains(&dump.display().to_string()));
+        let dumped = std::fs::read_to_string(dump).unwrap();
+        assert!(dumped.contains("max_tokens = 99999999999"));
+    }
+
+    #[test]
+    fn test_provider_reference_check() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  models: { providers: { openai: { apiKey: "sk-x" }, mistral: {} } },
+  agents: { list: [
+    { id: "writer", model: "openai/gpt-4o" },
+    { id: "fast", model: { primary: "groq/llama-3.3-70b-versatile", fallbacks: ["openai/gpt-4o-mini"] } },
+  ] },
+}"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let provider_warnings: Vec<&String> = report
+            .warnings
+            .iter()
+            .filter(|w| w.contains("models.providers"))
+            .collect();
+        assert_eq!(provider_warnings.len(), 2, "{provider_warnings:?}");
+        assert!(provider_warnings
+            .iter()
+            .any(|w| w.contains("Agent 'fast'") && w.contains("'groq'")));
+        assert!(provider_warnings
+            .iter()
+            .any(|w| w.contains("defines 'mistral'") && w.contains("no migrated agent")));
+    }
+
+    #[test]
+    fn test_output_paths_override() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ channels: { telegram: { botToken: "123:ABC" } } }"#,
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            output_paths: crate::OutputPaths {
+                config: "openfang.toml".into(),
+                secrets: ".env".into(),
+                report: "report.md".into(),
+            },
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        assert!(target.path().join("report.md").exists());
+        assert!(!target.path().join("migration_report.md").exists());
+        assert!(target.path().join("openfang.toml").exists());
+        assert!(!target.path().join("config.toml").exists());
+        let secrets = std::fs::read_to_string(target.path().join(".env")).unwrap();
+        assert!(secrets.contains("TELEGRAM_BOT_TOKEN=123:ABC"));
+        assert!(!target.path().join("secrets.env").exists());
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Secret && i.destination == ".env"));
+    }
+
+    #[test]
+    fn test_quiet_migration_report_unchanged() {
+        let source = TempDir::new().unwrap();
+        create_json5_workspace(source.path());
+
+        let run = |quiet: bool| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                quiet,
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            let imported: Vec<_> = report
+                .imported
+                .iter()
+                .map(|i| (i.kind, i.name.clone()))
+                .collect();
+            let skipped: Vec<_> = report.skipped.iter().map(|s| s.name.clone()).collect();
+            (imported, skipped, report.warnings, report.bytes_copied)
+        };
+
+        let loud = run(false);
+        let quiet = run(true);
+        assert!(!quiet.0.is_empty());
+        assert_eq!(loud, quiet);
+    }
+
+    #[test]
+    fn test_max_agents_limit() {
+        let source = TempDir::new().unwrap();
+        let mut list: Vec<String> = (0..500).map(|i| format!("{{ id: \"gen-{i}\" }}")).collect();
+        list[7] = format!(
+            "{{ id: \"bloated\", description: \"{}\" }}",
+            "x".repeat(4096)
+        );
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            format!("{{ agents: {{ list: [{}] }} }}", list.join(",")),
+        )
+        .unwrap();
+
+        let scan = scan_openclaw_workspace(source.path());
+        assert_eq!(scan.agent_count, 500);
+
+        let run = |max_agents: usize, exclude: &[&str]| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                dry_run: true,
+                max_agents,
+                exclude_agents: exclude.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            };
+            migrate(&options)
+        };
+
+        let err = run(crate::DEFAULT_MAX_AGENTS, &[]).unwrap_err();
+        match &err {
+            MigrateError::TooManyAgents {
+                found,
+                limit,
+                largest,
+            } => {
+                assert_eq!((*found, *limit), (500, 200));
+                assert!(largest.starts_with("'bloated' ("), "{largest}");
+                assert_eq!(largest.matches(" bytes)").count(), 3);
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+        assert!(err.to_string().contains("raise max_agents"));
+
+        // A raised limit, or filters that bring the count down, let it run
+        let report = run(500, &[]).unwrap();
+        assert_eq!(
+            report
+                .imported
+                .iter()
+                .filter(|i| i.kind == ItemKind::Agent)
+                .count(),
+            500
+        );
+        assert!(run(200, &["gen-*"]).is_ok());
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "coder", tools: { allow: ["read", "quantum_flux"] } },
+            ] } }"#,
+        )
+        .unwrap();
+
+        let run = |strict: bool| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                strict,
+                ..Default::default()
+            };
+            let result = migrate(&options);
+            (result, target.path().join("config.toml").exists())
+        };
+
+        let (result, wrote_config) = run(false);
+        let report = result.unwrap();
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'quantum_flux' has no OpenFang equivalent")));
+        assert!(wrote_config);
+
+        let (result, wrote_config) = run(true);
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("agent 'coder'"), "{err}");
+        assert!(err.contains("quantum_flux"), "{err}");
+        assert!(!wrote_config);
+
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ channels: { carrier_pigeon: { enabled: true } } }"#,
+        )
+        .unwrap();
+        let err = run(true).0.unwrap_err().to_string();
+        assert!(err.contains("channel 'carrier_pigeon'"), "{err}");
+
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "odd", model: "acme/model-x" } ] } }"#,
+        )
+        .unwrap();
+        let err = run(true).0.unwrap_err().to_string();
+        assert!(
+            err.contains("agent 'odd'") && err.contains("'acme'"),
+            "{err}"
+        );
+        assert!(run(false).0.is_ok());
+
+        // A base_url makes any provider loadable as an OpenAI-compatible endpoint
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  models: { providers: { acme: { baseUrl: "https://llm.acme.test/v1" } } },
+  agents: { list: [ { id: "odd", model: "acme/model-x" } ] },
+}"#,
+        )
+        .unwrap();
+        assert!(run(true).0.is_ok());
+
+        // Channels a normal run skips for lack of an adapter
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ channels: { imessage: { cliPath: "/usr/local/bin/imsg" } } }"#,
+        )
+        .unwrap();
+        let err = run(true).0.unwrap_err().to_string();
+        assert!(
+            err.contains("channel 'imessage' has no OpenFang adapter"),
+            "{err}"
+        );
+        assert!(run(false).0.is_ok());
+    }
+
+    #[test]
+    fn test_strict_mode_legacy_yaml() {
+        let source = TempDir::new().unwrap();
+        create_legacy_yaml_workspace(source.path());
+        let run = || {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                strict: true,
+                ..Default::default()
+            };
+            let result = migrate(&options);
+            (result, target.path().join("config.toml").exists())
+        };
+        assert!(run().0.is_ok());
+
+        let messaging = source.path().join("messaging");
+        std::fs::write(messaging.join("bluebubbles.yaml"), "type: bluebubbles\n").unwrap();
+        let (result, wrote_config) = run();
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("channel 'bluebubbles'"), "{err}");
+        assert!(!wrote_config);
+        std::fs::remove_file(messaging.join("bluebubbles.yaml")).unwrap();
+
+        std::fs::write(
+            source.path().join("agents/coder/agent.yaml"),
+            "name: coder\ntools:\n  - quantum_flux\n",
+        )
+        .unwrap();
+        let err = run().0.unwrap_err().to_string();
+        assert!(
+            err.contains("agent 'coder'") && err.contains("quantum_flux"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_agent_filters() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "coder" },
+                { id: "writer" },
+                { id: "test-alpha" },
+                { id: "test-beta" },
+            ] } }"#,
+        )
+        .unwrap();
+        for id in ["coder", "writer", "test-alpha"] {
+            let memory = source.path().join("memory").join(id);
+            std::fs::create_dir_all(&memory).unwrap();
+            std::fs::write(memory.join("MEMORY.md"), format!("{id} notes")).unwrap();
+            let ws = source.path().join("workspaces").join(id);
+            std::fs::create_dir_all(&ws).unwrap();
+            std::fs::write(ws.join("file.txt"), id).unwrap();
+        }
+
+        let run = |include: Option<&[&str]>, exclude: &[&str]| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                include_agents: include.map(|i| i.iter().map(|s| s.to_string()).collect()),
+                exclude_agents: exclude.iter().map(|s| s.to_string()).collect(),
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            let mut migrated: Vec<String> = std::fs::read_dir(target.path().join("agents"))
+                .unwrap()
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().into_owned())
+                .collect();
+            migrated.sort();
+            let mut excluded: Vec<String> = report
+                .skipped
+                .iter()
+                .filter(|s| s.reason == "excluded by filter")
+                .map(|s| s.name.clone())
+                .collect();
+            excluded.sort();
+            (migrated, excluded, target)
+        };
+
+        // Include only: memory and workspaces of other agents stay behind
+        let (migrated, excluded, _target) = run(Some(&["coder"]), &[]);
+        assert_eq!(migrated, vec!["coder"]);
+        assert_eq!(excluded, vec!["test-alpha", "test-beta", "writer"]);
+
+        // Exclude only, with a pattern
+        let (migrated, excluded, target) = run(None, &["test-*"]);
+        assert_eq!(migrated, vec!["coder", "writer"]);
+        assert_eq!(excluded, vec!["test-alpha", "test-beta"]);
+        let agents = target.path().join("agents");
+        assert!(agents.join("writer/imported_memory.md").exists());
+        assert!(agents.join("writer/workspace/file.txt").exists());
+
+        // Both: exclusion wins over inclusion
+        let (migrated, _, _target) = run(Some(&["*"]), &["test-?eta", "writer"]);
+        assert_eq!(migrated, vec!["coder", "test-alpha"]);
+
+        assert!(glob_matches("test-*", "test-"));
+        assert!(glob_matches("*-a*z", "x-abcz"));
+        assert!(!glob_matches("test-*", "prod-test-1"));
+    }
+
+    #[test]
+    fn test_agent_id_selection() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        create_json5_workspace(source.path());
+
+        let report = migrate(&MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            agent_ids: Some(vec!["coder".to_string()]),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let agents = target.path().join("agents");
+        assert!(agents.join("coder/agent.toml").exists());
+        assert!(agents.join("coder/imported_memory.md").exists());
+        assert!(!agents.join("researcher").exists());
+        assert!(report.skipped.iter().any(|s| s.kind == ItemKind::Agent
+            && s.name == "researcher"
+            && s.reason == "not in selection"));
+        assert!(report
+            .imported
+            .iter()
+            .all(|i| !i.name.contains("researcher")));
+    }
+
+    #[test]
+    fn test_agent_renames() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "default" }, { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let mem = source.path().join("memory").join("coder");
+        std::fs::create_dir_all(&mem).unwrap();
+        std::fs::write(mem.join("MEMORY.md"), "uses rust").unwrap();
+        let ws = source.path().join("workspaces").join("default");
+        std::fs::create_dir_all(&ws).unwrap();
+        std::fs::write(ws.join("todo.txt"), "ship it").unwrap();
+
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            agent_renames: [
+                ("default".to_string(), "assistant".to_string()),
+                ("coder".to_string(), "dev".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+
+        let agents = target.path().join("agents");
+        let dev = std::fs::read_to_string(agents.join("dev/agent.toml")).unwrap();
+        let manifest: toml::Value = toml::from_str(&dev).unwrap();
+        assert_eq!(manifest["name"].as_str(), Some("dev"));
+        assert_eq!(manifest["metadata"]["source_id"].as_str(), Some("coder"));
+        assert!(agents.join("dev/imported_memory.md").exists());
+        assert!(agents.join("assistant/agent.toml").exists());
+        assert!(agents.join("assistant/workspace/todo.txt").exists());
+        assert!(!agents.join("coder").exists());
+        assert!(!agents.join("default").exists());
+    }
+
+    #[test]
+    fn test_agent_rename_conflict() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "default" }, { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            agent_renames: [("default".to_string(), "coder".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let err = migrate(&options).unwrap_err();
+        assert!(matches!(err, MigrateError::AgentRename(_)));
+        assert_eq!(std::fs::read_dir(target.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_agent_custom_api_key_env() {
+        // The snake_case spelling; test_json5_agent_api_key_env covers apiKeyEnv
+        let root: OpenClawRoot = json5::from_str(
+            r#"{ agents: { list: [ {
+                id: "coder",
+                model: { primary: "openai/gpt-4o", fallbacks: ["groq/llama-3.3-70b-versatile"] },
+                api_key_env: "CUSTOM_KEY",
+            } ] } }"#,
+        )
+        .unwrap();
+        let agents = root.agents.as_ref().unwrap();
+        let converted = convert_agent_from_json(
+            &agents.list[0],
+            None,
+            Some(&root),
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let manifest: toml::Value = toml::from_str(&converted.toml).unwrap();
+        assert_eq!(
+            manifest["model"]["api_key_env"].as_str(),
+            Some("CUSTOM_KEY")
+        );
+        assert_eq!(manifest["model"]["provider"].as_str(), Some("openai"));
+        // The override names the primary's credential; fallbacks keep their own
+        assert_eq!(
+            manifest["fallback_models"][0]["api_key_env"].as_str(),
+            Some("GROQ_API_KEY")
+        );
+    }
+
+    #[test]
+    fn test_json5_agent_api_key_env() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: {
+                defaults: { apiKeyEnv: "TEAM_ANTHROPIC_KEY" },
+                list: [
+                    { id: "coder", apiKeyEnv: "MY_TEAM_ANTHROPIC_KEY" },
+                    { id: "writer" },
+                    { id: "ops", model: "openai/gpt-4o", apiKeyEnv: "", apiKey: "sk-ops-123" },
+                ],
+            } }"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let agents = target.path().join("agents");
+        let read = |id: &str| {
+            let text = std::fs::read_to_string(agents.join(id).join("agent.toml")).unwrap();
+            let manifest: toml::Value = toml::from_str(&text).unwrap();
+            manifest["model"]["api_key_env"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+
+        // Agent override beats defaults; defaults beat the provider default
+        assert_eq!(read("coder"), "MY_TEAM_ANTHROPIC_KEY");
+        assert_eq!(read("writer"), "TEAM_ANTHROPIC_KEY");
+
+        // Literal key lands in secrets.env under an agent-specific name
+        assert_eq!(read("ops"), "OPS_OPENAI_API_KEY");
+        let secrets = std::fs::read_to_string(target.path().join("secrets.env")).unwrap();
+        assert!(secrets.contains("OPS_OPENAI_API_KEY=sk-ops-123"));
+        assert!(!std::fs::read_to_string(agents.join("ops/agent.toml"))
+            .unwrap()
+            .contains("sk-ops-123"));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Secret && i.name == "OPS_OPENAI_API_KEY"));
+    }
+
+    #[test]
+    fn test_unmapped_tools_kept_as_comment() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "coder", tools: { allow: ["read", "quantum_flux", "teleport_zz"] } },
+            ] } }"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+
+        let text = std::fs::read_to_string(target.path().join("agents/coder/agent.toml")).unwrap();
+        assert!(text.contains("# unmapped tools: quantum_flux, teleport_zz"));
+        let manifest: toml::Value = toml::from_str(&text).unwrap();
+        let tools: Vec<&str> = manifest["capabilities"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|t| t.as_str())
+            .collect();
+        assert!(tools.contains(&"file_read"));
+        assert!(!tools.contains(&"quantum_flux"));
+        assert!(!tools.contains(&"teleport_zz"));
+    }
+
+    #[test]
+    fn test_agent_migration_notes() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "coder", model: "anthropic/claude-sonnet-4-20250514", tools: { allow: ["read", "quantum_flux"] } },
+            ] } }"#,
+        )
+        .unwrap();
+        let memory = source.path().join("memory/coder");
+        std::fs::create_dir_all(&memory).unwrap();
+        std::fs::write(memory.join("MEMORY.md"), "- remembers things").unwrap();
+
+        let target = TempDir::new().unwrap();
+        let mut options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+
+        let notes_path = target.path().join("agents/coder/MIGRATION_NOTES.md");
+        let notes = std::fs::read_to_string(&notes_path).unwrap();
+        assert!(notes.contains("`coder`"));
+        assert!(notes.contains("`anthropic/claude-sonnet-4-20250514` (set on the agent)"));
+        assert!(notes.contains("- file_read"));
+        assert!(notes.contains("Dropped (no OpenFang equivalent):\n\n- quantum_flux"));
+        assert!(notes.contains("- memory/coder/MEMORY.md"));
+
+        // Suppressed on dry-run and when disabled
+        let target = TempDir::new().unwrap();
+        options.target_dir = target.path().to_path_buf();
+        options.dry_run = true;
+        migrate(&options).unwrap();
+        assert!(!target
+            .path()
+            .join("agents/coder/MIGRATION_NOTES.md")
+            .exists());
+
+        options.dry_run = false;
+        options.write_agent_notes = false;
+        migrate(&options).unwrap();
+        assert!(target.path().join("agents/coder/agent.toml").exists());
+        assert!(!target
+            .path()
+            .join("agents/coder/MIGRATION_NOTES.md")
+            .exists());
+    }
+
+    #[test]
+    fn test_identity_file_references() {
+        let source = TempDir::new().unwrap();
+        let ids = source.path().join("identities");
+        std::fs::create_dir_all(&ids).unwrap();
+        std::fs::write(ids.join("coder.md"), "You write careful Rust.\n").unwrap();
+        std::fs::write(ids.join("base.md"), "You are the team assistant.").unwrap();
+
+        let mut warnings = Vec::new();
+        assert_eq!(
+            resolve_identity("You are terse.", source.path(), &mut warnings),
+            "You are terse."
+        );
+        assert_eq!(
+            resolve_identity("./identities/coder.md", source.path(), &mut warnings),
+            "You write careful Rust."
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(
+            resolve_identity("./identities/missing.md", source.path(), &mut warnings),
+            "./identities/missing.md"
+        );
+        assert!(warnings[0].contains("missing.md"));
+
+        // Agent-level and defaults-level references both resolve during migration
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: {
+                defaults: { identity: "identities/base.md" },
+                list: [ { id: "coder", identity: "./identities/coder.md" }, { id: "helper" } ],
+            } }"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+        let prompt = |id: &str| {
+            let path = target.path().join("agents").join(id).join("agent.toml");
+            let manifest: toml::Value =
+                toml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+            manifest["model"]["system_prompt"]
+                .as_str()
+                .unwrap()
+                .trim_end()
+                .to_string()
+        };
+        assert_eq!(
+            prompt("coder"),
+            "You are the team assistant.\n\nYou write careful Rust."
+        );
+        assert_eq!(prompt("helper"), "You are the team assistant.");
+    }
+
+    #[test]
+    fn test_identity_composes_with_defaults() {
+        let json5_content = r#"{
+  agents: {
+    defaults: { identity: "Always cite sources." },
+    list: [
+      { id: "both", identity: "You are a researcher." },
+      { id: "shared" },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.unwrap();
+        let prompt = |i: usize, options: &MigrateOptions| {
+            let converted = convert_agent_from_json(
+                &agents.list[i],
+                agents.defaults.as_ref(),
+                None,
+                options,
+                &ToolMap::default(),
+            )
+            .unwrap();
+            let manifest: toml::Value = toml::from_str(&converted.toml).unwrap();
+            manifest["model"]["system_prompt"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let options = MigrateOptions::default();
+        assert_eq!(
+            prompt(0, &options),
+            "Always cite sources.\n\nYou are a researcher."
+        );
+        assert_eq!(prompt(1, &options), "Always cite sources.");
+
+        // Agent-only identity, with no defaults to compose
+        let converted =
+            convert_agent_from_json(&agents.list[0], None, None, &options, &ToolMap::default())
+                .unwrap();
+        assert!(converted.toml.contains("You are a researcher."));
+        assert!(!converted.toml.contains("Always cite sources."));
+
+        // Replace semantics on request
+        let options = MigrateOptions {
+            replace_default_identity: true,
+            ..Default::default()
+        };
+        assert_eq!(prompt(0, &options), "You are a researcher.");
+        assert_eq!(prompt(1, &options), "Always cite sources.");
+    }
+
+    #[test]
+    fn test_manifest_serialized_structure() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      {
+        id: "coder",
+        name: "Code \"Bot\"",
+        model: { primary: "anthropic/claude-sonnet-4-20250514", fallbacks: ["groq/llama-3.3-70b-versatile"] },
+        identity: "Line one.\nLine \"two\".",
+        tools: { allow: ["Read", "Bash"], profile: "coding" },
+      },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let converted = convert_agent_from_json(
+            &root.agents.unwrap().list[0],
+            None,
+            None,
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+
+        let actual: toml::Value = toml::from_str(&converted.toml).unwrap();
+        let expected: toml::Value = toml::from_str(
+            r#"
+name = 'Code "Bot"'
+version = "0.1.0"
+description = "Migrated from OpenClaw agent 'coder'"
+author = "openfang"
+module = "builtin:chat"
+profile = "coding"
+
+[model]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+system_prompt = '''Line one.
+Line "two".'''
+api_key_env = "ANTHROPIC_API_KEY"
+
+[[fallback_models]]
+provider = "groq"
+model = "llama-3.3-70b-versatile"
+api_key_env = "GROQ_API_KEY"
+
+[capabilities]
+tools = ["file_read", "shell_exec"]
+memory_read = ["*"]
+memory_write = ["self.*"]
+shell = ["*"]
+
+[metadata]
+source_id = "coder"
+source_name = 'Code "Bot"'
+"#,
+        )
+        .unwrap();
+        assert_eq!(actual, expected);
+
+        // The output is a valid OpenFang manifest
+        let manifest: openfang_types::agent::AgentManifest =
+            toml::from_str(&converted.toml).unwrap();
+        assert_eq!(manifest.name, "Code \"Bot\"");
+        assert_eq!(manifest.capabilities.shell, vec!["*"]);
+    }
+
+    #[test]
+    fn test_memory_scope_from_config() {
+        let json5_content = r#"{
+  memory: { shared: true },
+  agents: {
+    list: [
+      { id: "team" },
+      { id: "loner", memory: { shared: false } },
+      { id: "scoped", memory: { namespaces: ["projects", "kb.*"] } },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.as_ref().unwrap();
+        let convert = |i: usize, root: Option<&OpenClawRoot>| {
+            convert_agent_from_json(
+                &agents.list[i],
+                None,
+                root,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+        let memory_of = |converted: &ConvertedAgent| {
+            let v: toml::Value = toml::from_str(&converted.toml).unwrap();
+            let list = |key: &str| -> Vec<String> {
+                v["capabilities"][key]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(|t| t.as_str().map(str::to_string))
+                    .collect()
+            };
+            (list("memory_read"), list("memory_write"))
+        };
+
+        // Shared (from the root block)
+        let team = convert(0, Some(&root));
+        assert_eq!(team.memory_scope.as_deref(), Some("shared"));
+        assert_eq!(
+            memory_of(&team),
+            (vec!["*".into()], vec!["self.*".into(), "shared.*".into()])
+        );
+
+        // Isolated (agent overrides root)
+        let loner = convert(1, Some(&root));
+        assert_eq!(loner.memory_scope.as_deref(), Some("isolated"));
+        assert_eq!(
+            memory_of(&loner),
+            (vec!["self.*".into()], vec!["self.*".into()])
+        );
+
+        // Explicit namespaces
+        let scoped = convert(2, Some(&root));
+        let globs: Vec<String> = vec!["self.*".into(), "projects.*".into(), "kb.*".into()];
+        assert_eq!(memory_of(&scoped), (globs.clone(), globs));
+        assert_eq!(
+            scoped.memory_scope.as_deref(),
+            Some("namespaces projects, kb.*")
+        );
+
+        // Nothing configured keeps the defaults
+        let plain = convert(0, None);
+        assert!(plain.memory_scope.is_none());
+        assert_eq!(memory_of(&plain), (vec!["*".into()], vec!["self.*".into()]));
+    }
+
+    #[test]
+    fn test_memory_scope_in_report() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  memory: { shared: false },
+  agents: {
+    list: [
+      { id: "lead", subagents: [{ id: "helper", memory: { shared: true } }] },
+    ],
+  },
+}"#,
+        )
+        .unwrap();
+        let report = migrate(&MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let scope_of = |name: &str| {
+            report
+                .imported
+                .iter()
+                .find(|i| i.kind == ItemKind::Agent && i.name == name)
+                .and_then(|i| i.details.as_ref())
+                .and_then(|d| d.memory_scope.clone())
+        };
+        assert_eq!(scope_of("lead").as_deref(), Some("isolated"));
+        assert_eq!(
+            scope_of("lead__helper (subagent of lead)").as_deref(),
+            Some("shared")
+        );
+        let md = report.to_markdown();
+        assert!(md.contains("| lead__helper (subagent of lead) (memory: shared) |"));
+        // Agents are not listed among memory sizes
+        assert!(!md.contains("## Memory Size"));
+    }
+
+    #[test]
+    fn test_supported_channels() {
+        assert_eq!(SUPPORTED_CHANNELS.first(), Some(&"telegram"));
+        assert_eq!(SUPPORTED_CHANNELS.last(), Some(&"feishu"));
+        assert_eq!(SUPPORTED_CHANNELS.len(), 11);
+        for name in [
+            "telegram",
+            "discord",
+            "slack",
+            "google_chat",
+            "teams",
+            "feishu",
+        ] {
+            assert!(channel_is_supported(name), "{name}");
+        }
+        assert!(!channel_is_supported("bluebubbles"));
+        assert!(!channel_is_supported("imessage"));
+        assert!(!channel_is_supported("carrier_pigeon"));
+    }
+
+    #[test]
+    fn test_secret_env_preserves_layout() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secrets.env");
+        std::fs::write(
+            &path,
+            "# Team credentials\nSLACK_BOT_TOKEN=old\n\n# Bots\nexport DISCORD_BOT_TOKEN = old\n",
+        )
+        .unwrap();
+
+        write_secret_env(&path, "TELEGRAM_BOT_TOKEN", "tg").unwrap();
+        write_secret_env(&path, "DISCORD_BOT_TOKEN", "dc").unwrap();
+        write_secret_env(&path, "SLACK_BOT_TOKEN", "sl").unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Team credentials\nSLACK_BOT_TOKEN=sl\n\n# Bots\nexport DISCORD_BOT_TOKEN=dc\nTELEGRAM_BOT_TOKEN=tg\n"
+        );
+    }
+
+    #[test]
+    fn test_verify_copies() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let ws = source.path().join("workspaces").join("coder");
+        std::fs::create_dir_all(ws.join("src")).unwrap();
+        std::fs::write(ws.join("README.md"), "# project").unwrap();
+        std::fs::write(ws.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(ws.join("src/big.bin"), vec![7u8; 200 * 1024]).unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            verify_copies: true,
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        assert!(report.notes.iter().any(|n| n.ends_with("verified 3 files")));
+        assert!(!report.warnings.iter().any(|w| w.contains("Checksum")));
+        assert_eq!(
+            file_fingerprint(&ws.join("src/big.bin")).unwrap(),
+            file_fingerprint(&target.path().join("agents/coder/workspace/src/big.bin")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unchanged_manifests_not_rewritten() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        let config = source.path().join("openclaw.json");
+        std::fs::write(
+            &config,
+            r#"{ agents: { list: [ { id: "coder" }, { id: "writer" } ] } }"#,
+        )
+        .unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+
+        let agents = target.path().join("agents");
+        let mtime = |id: &str| {
+            std::fs::metadata(agents.join(id).join("agent.toml"))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        // Backdate the manifests so a rewrite would be visible
+        let old = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        for id in ["coder", "writer"] {
+            std::fs::File::options()
+                .write(true)
+                .open(agents.join(id).join("agent.toml"))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        // writer changes, coder doesn't
+        std::fs::write(
+            &config,
+            r#"{ agents: { list: [ { id: "coder" }, { id: "writer", identity: "New voice" } ] } }"#,
+        )
+        .unwrap();
+        let report = migrate(&options).unwrap();
+
+        assert_eq!(mtime("coder"), old);
+        assert_ne!(mtime("writer"), old);
+        assert!(report
+            .unchanged
+            .iter()
+            .any(|i| i.kind == ItemKind::Agent && i.name == "coder"));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Agent && i.name == "writer"));
+        assert!(!report.imported.iter().any(|i| i.name == "coder"));
+        assert!(report.to_markdown().contains("Unchanged: 1 items"));
+    }
+
+    #[test]
+    fn test_incremental_workspace_copy() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let ws = source.path().join("workspaces").join("coder");
+        std::fs::create_dir_all(ws.join("src")).unwrap();
+        for i in 0..5 {
+            std::fs::write(ws.join(format!("src/f{i}.rs")), format!("// {i}")).unwrap();
+        }
+
+        let mut options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            incremental_copies: true,
+            ..Default::default()
+        };
+        let first = migrate(&options).unwrap();
+        assert!(!first.notes.iter().any(|n| n.contains("unchanged")));
+
+        // Only the edited file is copied again
+        std::fs::write(ws.join("src/f0.rs"), "// edited, longer").unwrap();
+        let second = migrate(&options).unwrap();
+        assert!(second
+            .notes
+            .iter()
+            .any(|n| n.ends_with("4 files unchanged, skipped")));
+        let copied =
+            std::fs::read_to_string(target.path().join("agents/coder/workspace/src/f0.rs"))
+                .unwrap();
+        assert_eq!(copied, "// edited, longer");
+
+        // Checksums decide when verifying
+        options.verify_copies = true;
+        let third = migrate(&options).unwrap();
+        assert!(third
+            .notes
+            .iter()
+            .any(|n| n.ends_with("5 files unchanged, skipped")));
+    }
+
+    #[test]
+    fn test_json5_agent_tags() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [
+                { id: "ops", tags: ["infra", "on-call"] },
+                { id: "plain" },
+                { id: "quoted", tags: ["say \"hi\""] },
+            ] } }"#,
+        )
+        .unwrap();
+
+        let scan = scan_openclaw_workspace(source.path());
+        let scanned = |name: &str| scan.agents.iter().find(|a| a.name == name).unwrap();
+        assert_eq!(scanned("ops").tags, vec!["infra", "on-call"]);
+        assert!(scanned("plain").tags.is_empty());
+
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+        let manifest = |id: &str| -> openfang_types::agent::AgentManifest {
+            let path = target.path().join("agents").join(id).join("agent.toml");
+            toml::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+        assert_eq!(manifest("ops").tags, vec!["infra", "on-call"]);
+        assert!(manifest("plain").tags.is_empty());
+        assert_eq!(manifest("quoted").tags, vec!["say \"hi\""]);
+    }
+
+    #[test]
+    fn test_model_params_migrated() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      {
+        id: "tuned",
+        model: {
+          primary: "openai/gpt-4o",
+          params: { temperature: 0.2, maxOutputTokens: 2048, reasoningEffort: "high", topP: 0.9 },
+        },
+      },
+      { id: "huge", model: { primary: "openai/gpt-4o", params: { maxOutputTokens: 99999999999 } } },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let list = root.agents.unwrap().list;
+        let convert = |entry| {
+            convert_agent_from_json(
+                entry,
+                None,
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+        let converted = convert(&list[0]);
+
+        let parsed: toml::Value = toml::from_str(&converted.toml).unwrap();
+        let model = parsed["model"].as_table().unwrap();
+        assert_eq!(model["temperature"].as_float(), Some(0.2));
+        assert_eq!(model["max_tokens"].as_integer(), Some(2048));
+        // Only fields the kernel has are written; the rest are reported
+        for key in [
+            "reasoning_effort",
+            "top_p",
+            "extra",
+            "reasoningEffort",
+            "topP",
+        ] {
+            assert!(model.get(key).is_none(), "{key} written");
+        }
+        for key in ["'reasoningEffort'", "'topP'"] {
+            assert!(converted
+                .warnings
+                .iter()
+                .any(|w| w.contains(key) && w.contains("was dropped")));
+        }
+
+        // The kernel picks up the recognized params
+        let manifest: openfang_types::agent::AgentManifest =
+            toml::from_str(&converted.toml).unwrap();
+        assert_eq!(manifest.model.max_tokens, 2048);
+        assert!((manifest.model.temperature - 0.2).abs() < f32::EPSILON);
+
+        // Caps beyond the kernel's u32 are clamped so the manifest still loads
+        let huge = convert(&list[1]);
+        let manifest: openfang_types::agent::AgentManifest = toml::from_str(&huge.toml).unwrap();
+        assert_eq!(manifest.model.max_tokens, u32::MAX);
+        assert!(huge
+            .warnings
+            .iter()
+            .any(|w| w.contains("99999999999") && w.contains("clamped")));
+    }
+
+    #[test]
+    fn test_heartbeat_and_schedule_migrated() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      { id: "watcher", heartbeat: { every: "30m", prompt: "check the queue" } },
+      { id: "digest", schedule: { every: "daily" } },
+      { id: "cron-job", schedule: { cron: "0 9 * * 1-5", prompt: "standup" } },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let list = root.agents.unwrap().list;
+        let convert = |entry| {
+            convert_agent_from_json(
+                entry,
+                None,
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+
+        let watcher = convert(&list[0]);
+        let parsed: toml::Value = toml::from_str(&watcher.toml).unwrap();
+        assert_eq!(
+            parsed["schedule"]["periodic"]["cron"].as_str(),
+            Some("every 30m")
+        );
+        assert_eq!(
+            parsed["metadata"]["schedule_prompt"].as_str(),
+            Some("check the queue")
+        );
+        let manifest: openfang_types::agent::AgentManifest = toml::from_str(&watcher.toml).unwrap();
+        assert!(matches!(
+            manifest.schedule,
+            openfang_types::agent::ScheduleMode::Periodic { ref cron } if cron == "every 30m"
+        ));
+
+        let digest = convert(&list[1]);
+        let parsed: toml::Value = toml::from_str(&digest.toml).unwrap();
+        assert_eq!(
+            parsed["schedule"]["periodic"]["cron"].as_str(),
+            Some("every 1d")
+        );
+
+        let cron_job = convert(&list[2]);
+        let parsed: toml::Value = toml::from_str(&cron_job.toml).unwrap();
+        assert!(parsed.get("schedule").is_none());
+        assert!(cron_job
+            .warnings
+            .iter()
+            .any(|w| w.contains("'0 9 * * 1-5'") && w.contains("cron migration")));
+
+        assert_eq!(parse_interval_secs("1h30m"), Some(5400));
+        assert_eq!(parse_interval_secs("90"), Some(90));
+        assert_eq!(parse_interval_secs("soon"), None);
+        assert_eq!(every_expression(5400), "every 90m");
+    }
+
+    #[test]
+    fn test_agent_limits_migrated() {
+        let json5_content = r#"{
+  agents: {
+    defaults: { maxOutputTokens: 1024, historyLimit: 40 },
+    list: [
+      { id: "own", maxContextTokens: 32000, maxOutputTokens: 2048, historyLimit: 10 },
+      { id: "inherits" },
+      { id: "tiny", maxOutputTokens: 8, maxContextTokens: 4, historyLimit: 0 },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.unwrap();
+        let convert = |entry| {
+            convert_agent_from_json(
+                entry,
+                agents.defaults.as_ref(),
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+
+        let own = convert(&agents.list[0]);
+        let parsed: toml::Value = toml::from_str(&own.toml).unwrap();
+        assert_eq!(parsed["model"]["max_tokens"].as_integer(), Some(2048));
+        // No kernel field for the other two, so they are reported with their values
+        assert!(parsed.get("limits").is_none());
+        let skipped: Vec<(&str, &str)> = own
+            .skipped
+            .iter()
+            .map(|s| (s.name.as_str(), s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (
+                    "own.maxContextTokens",
+                    "32000 tokens; OpenFang has no per-agent context limit, so it was not applied"
+                ),
+                (
+                    "own.historyLimit",
+                    "10 messages; OpenFang has no per-agent history limit, so it was not applied"
+                ),
+            ]
+        );
+        let manifest: openfang_types::agent::AgentManifest = toml::from_str(&own.toml).unwrap();
+        assert_eq!(manifest.model.max_tokens, 2048);
+
+        let inherits = convert(&agents.list[1]);
+        let parsed: toml::Value = toml::from_str(&inherits.toml).unwrap();
+        assert_eq!(parsed["model"]["max_tokens"].as_integer(), Some(1024));
+        let skipped: Vec<&str> = inherits.skipped.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(skipped, vec!["inherits.historyLimit"]);
+        assert!(inherits.skipped[0].reason.starts_with("40 messages"));
+        assert!(!inherits.warnings.iter().any(|w| w.contains("below")));
+
+        let tiny = convert(&agents.list[2]);
+        let parsed: toml::Value = toml::from_str(&tiny.toml).unwrap();
+        assert_eq!(parsed["model"]["max_tokens"].as_integer(), Some(8));
+        assert!(tiny
+            .warnings
+            .iter()
+            .any(|w| w.contains("maxOutputTokens 8 is below 64")));
+    }
+
+    #[test]
+    fn test_bare_model_warns() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  agents: {
+    defaults: { model: "gpt-4o" },
+    list: [
+      { id: "bare", model: { primary: "gpt-4o", fallbacks: ["openai/gpt-4o-mini"] } },
+      { id: "prefixed", model: "openai:gpt-4o" },
+    ],
+  },
+}"#,
+        )
+        .unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let note = "model 'gpt-4o' had no provider prefix; assumed anthropic";
+        assert!(report.warnings.contains(&format!("Default model: {note}")));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'bare'") && w.ends_with(note)));
+        assert!(!report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'prefixed'") && w.contains("no provider prefix")));
+
+        let manifest: toml::Value = toml::from_str(
+            &std::fs::read_to_string(target.path().join("agents/bare/agent.toml")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["model"]["provider"].as_str(), Some("anthropic"));
+        assert_eq!(manifest["model"]["model"].as_str(), Some("gpt-4o"));
+        let config: toml::Value =
+            toml::from_str(&std::fs::read_to_string(target.path().join("config.toml")).unwrap())
+                .unwrap();
+        assert_eq!(
+            config["default_model"]["provider"].as_str(),
+            Some("anthropic")
+        );
+    }
+
+    #[test]
+    fn test_memory_index_imported() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" }, { id: "writer" } ] } }"#,
+        )
+        .unwrap();
+        let index_dir = source.path().join("memory-search");
+        std::fs::create_dir_all(&index_dir).unwrap();
+        let conn = rusqlite::Connection::open(index_dir.join("index.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT);
+             INSERT INTO meta VALUES ('schema_version', '1');
+             CREATE TABLE chunks (
+                 id INTEGER PRIMARY KEY, agent_id TEXT, path TEXT, source TEXT,
+                 start_line INTEGER, end_line INTEGER, text TEXT, embedding BLOB
+             );
+             INSERT INTO chunks VALUES
+                 (1, 'writer', 'memory/writer/MEMORY.md', 'memory', 1, 4, 'Prefers British spelling', x'00'),
+                 (2, NULL, 'memory/coder/MEMORY.md', 'memory', 10, 12, 'Repo uses rustfmt defaults', x'00'),
+                 (3, NULL, 'sessions/main.jsonl', 'sessions', NULL, NULL, 'Pruned note from main', x'00'),
+                 (4, 'ghost', NULL, 'memory', NULL, NULL, 'Nobody owns this', x'00');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let run = |import_memory_index: bool| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                import_memory_index,
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            (target, report)
+        };
+
+        // Off by default: skipped as before
+        let (target, report) = run(false);
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.name == "memory-search/index.db" && s.reason.contains("rebuild")));
+        assert!(!target
+            .path()
+            .join("agents/coder/imported_memory_index.jsonl")
+            .exists());
+
+        let (target, report) = run(true);
+        let read = |agent: &str| -> Vec<serde_json::Value> {
+            std::fs::read_to_string(
+                target
+                    .path()
+                    .join(format!("agents/{agent}/imported_memory_index.jsonl")),
+            )
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+        };
+        let writer = read("writer");
+        assert_eq!(writer.len(), 1);
+        assert_eq!(writer[0]["text"], "Prefers British spelling");
+        assert_eq!(writer[0]["path"], "memory/writer/MEMORY.md");
+        assert_eq!(writer[0]["end_line"], 4);
+        // No agent column: owned via the path, or the first agent for main
+        let coder = read("coder");
+        assert_eq!(coder.len(), 2);
+        assert_eq!(coder[0]["text"], "Repo uses rustfmt defaults");
+        assert_eq!(coder[1]["text"], "Pruned note from main");
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("1 chunks belong to no migrated agent")));
+        assert!(report.notes.iter().any(|n| n.contains("embeddings")));
+        assert!(!report.skipped.iter().any(|s| s.name.contains("index.db")));
+    }
+
+    #[test]
+    fn test_vector_index_copied() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(source.path().join("memory-search")).unwrap();
+        std::fs::write(
+            source.path().join("memory-search/index.db"),
+            b"SQLite format 3\0",
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            copy_vector_index: true,
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let copied = std::fs::read(target.path().join("memory/index.db")).unwrap();
+        assert_eq!(copied, b"SQLite format 3\0");
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "memory-search/index.db" && i.destination.ends_with("index.db")));
+        assert!(!report.skipped.iter().any(|s| s.name.contains("index.db")));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("copied as-is") && w.contains("dimensions")));
+    }
+
+    #[test]
+    fn test_memory_index_unreadable_skipped() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let index_dir = source.path().join("memory-search");
+        std::fs::create_dir_all(&index_dir).unwrap();
+        let run = |db: &dyn Fn(&Path)| {
+            let _ = std::fs::remove_file(index_dir.join("index.db"));
+            db(&index_dir.join("index.db"));
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                import_memory_index: true,
+                ..Default::default()
+            };
+            migrate(&options)
+                .unwrap()
+                .skipped
+                .into_iter()
+                .find(|s| s.name == "memory-search/index.db")
+                .unwrap()
+                .reason
+        };
+
+        // Not a database at all
+        let reason = run(&|p| std::fs::write(p, "not sqlite").unwrap());
+        assert!(reason.starts_with("Could not read SQLite vector index"));
+
+        // A schema version we don't know
+        let reason = run(&|p| {
+            rusqlite::Connection::open(p)
+                .unwrap()
+                .execute_batch(
+                    "CREATE TABLE meta (key TEXT, value TEXT);
+                     INSERT INTO meta VALUES ('schema_version', '7');
+                     CREATE TABLE chunks (text TEXT);",
+                )
+                .unwrap()
+        });
+        assert!(reason.contains("unrecognized schema version 7"));
+
+        // No chunks table
+        let reason = run(&|p| {
+            rusqlite::Connection::open(p)
+                .unwrap()
+                .execute_batch("CREATE TABLE vectors (id INTEGER);")
+                .unwrap()
+        });
+        assert!(reason.contains("no chunks table"));
+    }
+
+    #[test]
+    fn test_incomplete_channel_skipped() {
+        let root: OpenClawRoot = json5::from_str(
+            r#"{
+  channels: {
+    matrix: { userId: "@bot:example.com", accessToken: "syt_x" },
+    irc: {},
+    telegram: {},
+  },
+}"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            target_dir: target.path().to_path_buf(),
+            dry_run: true,
+            ..Default::default()
+        };
+        let mut report = MigrationReport::default();
+        let channels =
+            migrate_channels_from_json(&root, target.path(), &options, &mut report).unwrap();
+
+        assert!(channels.get("matrix").is_none());
+        assert!(channels.get("irc").is_none());
+        // A missing token is left to the env var the table points at
+        assert_eq!(
+            channels["telegram"]["bot_token_env"].as_str(),
+            Some("TELEGRAM_BOT_TOKEN")
+        );
+        assert!(!report.skipped.iter().any(|s| s.name == "telegram"));
+        let reason = |name: &str| {
+            report
+                .skipped
+                .iter()
+                .find(|s| s.name == name)
+                .map(|s| s.reason.clone())
+                .unwrap()
+        };
+        assert_eq!(
+            reason("matrix"),
+            "Incomplete config: missing required field `homeserver`"
+        );
+        assert_eq!(
+            reason("irc"),
+            "Incomplete config: missing required fields `host`, `nick`"
+        );
+        assert!(!report.imported.iter().any(|i| i.name == "matrix"));
+        assert!(!report
+            .required_env_vars
+            .contains(&"MATRIX_ACCESS_TOKEN".to_string()));
+    }
+
+    #[test]
+    fn test_shared_memory_copied() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let memory = source.path().join("memory");
+        std::fs::create_dir_all(memory.join("shared")).unwrap();
+        std::fs::create_dir_all(memory.join("coder")).unwrap();
+        std::fs::write(memory.join("GLOBAL.md"), "# Team\nShip on Fridays.\n").unwrap();
+        std::fs::write(memory.join("shared/notes.md"), "- staging is flaky\n").unwrap();
+        std::fs::write(memory.join("coder/MEMORY.md"), "- likes tabs\n").unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let shared = target.path().join("shared_memory");
+        assert_eq!(
+            std::fs::read_to_string(shared.join("GLOBAL.md")).unwrap(),
+            "# Team\nShip on Fridays.\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(shared.join("shared/notes.md")).unwrap(),
+            "- staging is flaky\n"
+        );
+        let memory_items: Vec<&str> = report
+            .imported
+            .iter()
+            .filter(|i| i.kind == ItemKind::Memory)
+            .map(|i| i.name.as_str())
+            .collect();
+        assert!(memory_items.contains(&"memory/GLOBAL.md"));
+        assert!(memory_items.contains(&"memory/shared/notes.md"));
+        assert!(memory_items.contains(&"coder/MEMORY.md"));
+        assert!(target
+            .path()
+            .join("agents/coder/imported_memory.md")
+            .exists());
+        assert!(!report
+            .warnings
+            .iter()
+            .any(|w| w.contains("No agent matches")));
+    }
+
+    #[test]
+    fn test_imported_memory_rerun() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let mem_dir = source.path().join("memory/coder");
+        std::fs::create_dir_all(&mem_dir).unwrap();
+        std::fs::write(mem_dir.join("MEMORY.md"), "- likes tabs\n").unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let dest = target.path().join("agents/coder/imported_memory.md");
+        let new_dest = target.path().join("agents/coder/imported_memory.new.md");
+        let edited = |report: &MigrationReport| {
+            report
+                .warnings
+                .iter()
+                .any(|w| w.contains("was edited since it was migrated"))
+        };
+
+        migrate(&options).unwrap();
+        let first = std::fs::read_to_string(&dest).unwrap();
+        assert!(first.starts_with(PROVENANCE_PREFIX));
+        assert_eq!(split_provenance(&first).1, "- likes tabs\n");
+
+        // Unchanged re-run
+        let report = migrate(&options).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), first);
+        assert!(!edited(&report) && !new_dest.exists());
+
+        // Source changed, destination untouched: overwritten
+        std::fs::write(mem_dir.join("MEMORY.md"), "- likes spaces\n").unwrap();
+        let report = migrate(&options).unwrap();
+        let second = std::fs::read_to_string(&dest).unwrap();
+        assert_eq!(split_provenance(&second).1, "- likes spaces\n");
+        assert!(!edited(&report) && !new_dest.exists());
+
+        // Destination edited locally: kept, new import written alongside
+        let local = second.replace("spaces", "spaces, 4 wide");
+        std::fs::write(&dest, &local).unwrap();
+        std::fs::write(mem_dir.join("MEMORY.md"), "- likes tabs again\n").unwrap();
+        let report = migrate(&options).unwrap();
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), local);
+        let new = std::fs::read_to_string(&new_dest).unwrap();
+        assert_eq!(split_provenance(&new).1, "- likes tabs again\n");
+        assert!(edited(&report));
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.destination.ends_with("imported_memory.new.md")));
+        // The structured entries stay in step with the kept markdown
+        let coder = target.path().join("agents/coder");
+        let jsonl = std::fs::read_to_string(coder.join("imported_memory.jsonl")).unwrap();
+        assert!(jsonl.contains("likes spaces") && !jsonl.contains("tabs again"));
+        let new_jsonl = std::fs::read_to_string(coder.join("imported_memory.new.jsonl")).unwrap();
+        assert!(new_jsonl.contains("likes tabs again"));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("imported_memory.new.jsonl")));
+    }
+
+    #[test]
+    fn test_blank_identity_falls_back() {
+        let json5_content = r#"{
+  agents: {
+    defaults: { identity: "You work for Acme." },
+    list: [
+      { id: "blank", identity: "" },
+      { id: "spaces", identity: "  \n " },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.unwrap();
+        for entry in &agents.list {
+            let converted = convert_agent_from_json(
+                entry,
+                agents.defaults.as_ref(),
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap();
+            let manifest: toml::Value = toml::from_str(&converted.toml).unwrap();
+            assert_eq!(
+                manifest["model"]["system_prompt"].as_str(),
+                Some("You work for Acme."),
+                "agent {}",
+                entry.id
+            );
+        }
+
+        // Without a defaults identity the generated prompt is used
+        let converted = convert_agent_from_json(
+            &agents.list[0],
+            None,
+            None,
+            &MigrateOptions::default(),
+            &ToolMap::default(),
+        )
+        .unwrap();
+        let manifest: toml::Value = toml::from_str(&converted.toml).unwrap();
+        assert!(manifest["model"]["system_prompt"]
+            .as_str()
+            .unwrap()
+            .starts_with("You are blank, an AI agent"));
+    }
+
+    #[test]
+    fn test_memory_encodings() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" }, { id: "writer" }, { id: "tester" } ] } }"#,
+        )
+        .unwrap();
+        let memory = source.path().join("memory");
+        for agent in ["coder", "writer", "tester"] {
+            std::fs::create_dir_all(memory.join(agent)).unwrap();
+        }
+        // UTF-16LE with a BOM
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(
+            "- Prefers naïve café examples\n"
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes),
+        );
+        std::fs::write(memory.join("coder/MEMORY.md"), utf16).unwrap();
+        // Latin-1 bytes in otherwise UTF-8 text
+        std::fs::write(
+            memory.join("writer/MEMORY.md"),
+            b"- Caf\xe9 at 9, r\xe9sum\xe9 due\n",
+        )
+        .unwrap();
+        // A directory where the file should be can't be read, but doesn't stop the run
+        std::fs::create_dir_all(memory.join("tester/MEMORY.md")).unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let read = |agent: &str| {
+            let text = std::fs::read_to_string(
+                target
+                    .path()
+                    .join(format!("agents/{agent}/imported_memory.md")),
+            )
+            .unwrap();
+            split_provenance(&text).1.to_string()
+        };
+        assert_eq!(read("coder"), "- Prefers naïve café examples\n");
+        assert_eq!(
+            read("writer"),
+            "- Caf\u{FFFD} at 9, r\u{FFFD}sum\u{FFFD} due\n"
+        );
+        assert!(report.warnings.iter().any(|w| w.contains("coder")
+            && w.contains("converted from UTF-16LE (0 invalid characters replaced)")));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("writer") && w.contains("replaced 3 invalid byte sequences")));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("Could not read") && w.contains("tester")));
+        assert!(target.path().join("agents/tester/agent.toml").exists());
+    }
+
+    #[test]
+    fn test_journal_files_migrated() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let mem = source.path().join("memory/coder");
+        std::fs::create_dir_all(&mem).unwrap();
+        std::fs::write(mem.join("MEMORY.md"), "- likes tabs\n").unwrap();
+        for (date, note) in [
+            ("2025-01-12", "Set up CI"),
+            ("2025-01-13", "Fixed flaky test"),
+            ("2025-01-14", "Released 1.2"),
+        ] {
+            std::fs::write(mem.join(format!("{date}.md")), format!("{note}\n")).unwrap();
+        }
+        std::fs::write(mem.join("notes-2025.md"), "not a journal").unwrap();
+
+        let run = |recent_journals_in_memory: usize| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                recent_journals_in_memory,
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            let memory =
+                std::fs::read_to_string(target.path().join("agents/coder/imported_memory.md"))
+                    .unwrap();
+            (target, report, split_provenance(&memory).1.to_string())
+        };
+
+        let (target, report, memory) = run(0);
+        let journal_dir = target.path().join("agents/coder/imported_journal");
+        let mut copied: Vec<String> = std::fs::read_dir(&journal_dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        copied.sort();
+        assert_eq!(copied, ["2025-01-12.md", "2025-01-13.md", "2025-01-14.md"]);
+        assert_eq!(
+            std::fs::read_to_string(journal_dir.join("2025-01-14.md")).unwrap(),
+            "Released 1.2\n"
+        );
+        let items: Vec<&str> = report
+            .imported
+            .iter()
+            .filter(|i| i.kind == ItemKind::Memory)
+            .map(|i| i.name.as_str())
+            .collect();
+        assert_eq!(items, ["coder: MEMORY.md + 3 journal files"]);
+        assert_eq!(memory, "- likes tabs\n");
+
+        // The newest two are appended for recall
+        let (_, _, memory) = run(2);
+        assert_eq!(
+            memory,
+            "- likes tabs\n\n# Journal 2025-01-13\n\nFixed flaky test\n\n# Journal 2025-01-14\n\nReleased 1.2\n"
+        );
+
+        // The same day in the legacy layout: identical is imported once, a
+        // different one under its own name
+        let legacy = source.path().join("agents/coder");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("2025-01-12.md"), "Set up CI\n").unwrap();
+        std::fs::write(legacy.join("2025-01-13.md"), "Paired on the parser\n").unwrap();
+        let (target, report, _) = run(0);
+        let journal_dir = target.path().join("agents/coder/imported_journal");
+        let mut copied: Vec<String> = std::fs::read_dir(&journal_dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        copied.sort();
+        assert_eq!(
+            copied,
+            [
+                "2025-01-12.md",
+                "2025-01-13-2.md",
+                "2025-01-13.md",
+                "2025-01-14.md"
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(journal_dir.join("2025-01-13.md")).unwrap(),
+            "Fixed flaky test\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(journal_dir.join("2025-01-13-2.md")).unwrap(),
+            "Paired on the parser\n"
+        );
+        assert!(report.warnings.iter().any(|w| w
+            .starts_with("coder: journal 2025-01-13.md differs between memory layouts; ")
+            && w.ends_with("was imported as 2025-01-13-2.md")));
+    }
+
+    #[test]
+    fn test_memory_front_matter() {
+        let dest = TempDir::new().unwrap();
+        let options = MigrateOptions::default();
+        let import = |content: &str| {
+            let mut report = MigrationReport::default();
+            write_imported_memory(dest.path(), content, "coder", &options, &mut report).unwrap();
+            let md = std::fs::read_to_string(dest.path().join("imported_memory.md")).unwrap();
+            let entries: Vec<serde_json::Value> =
+                std::fs::read_to_string(dest.path().join("imported_memory.jsonl"))
+                    .unwrap()
+                    .lines()
+                    .map(|l| serde_json::from_str(l).unwrap())
+                    .collect();
+            (split_provenance(&md).1.to_string(), entries)
+        };
+
+        let (md, entries) = import(
+            "---\nupdated: 2024-11-02\npriority: high\ntags: work, rust\nowner: sam\n---\n\n## Notes\n- ship it\n",
+        );
+        assert_eq!(
+            md,
+            "<!-- front-matter\nowner: sam\n-->\n\n## Notes\n- ship it\n"
+        );
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["tag"], "Notes");
+        assert_eq!(entries[0]["updated"], "2024-11-02");
+        assert_eq!(entries[0]["priority"], "high");
+        assert_eq!(entries[0]["tags"], serde_json::json!(["work", "rust"]));
+        assert!(entries[0].get("owner").is_none());
+
+        // No front-matter: untouched, no extra fields
+        let (md, entries) = import("## Notes\n- ship it\n");
+        assert_eq!(md, "## Notes\n- ship it\n");
+        assert!(entries[0].get("updated").is_none());
+
+        // Malformed (unclosed, or not a mapping) front-matter is left as content
+        for content in [
+            "---\nupdated: 2024-11-02\n## Notes\n- ship it\n",
+            "---\n- a list, not keys\n---\n## Notes\n",
+        ] {
+            let (md, entries) = import(content);
+            assert_eq!(md, content);
+            assert!(entries.iter().all(|e| e.get("updated").is_none()));
+        }
+    }
+
+    #[test]
+    fn test_split_memory_markdown() {
+        let content = "# Memory\n\
+Loose note before any section.\n\
+\n\
+## Project Context\n\
+- Monorepo with Rust services\n\
+- Deploys on Fridays are frozen\n\
+\n\
+## Preferences ##\n\
+Formatting example:\n\
+```md\n\
+## Not a heading\n\
+```\n\
+- Prefers short answers\n\
+\n\
+## People\n\
+- Alice: tech lead\n";
+        let entries = split_memory_markdown(content);
+        let tags: Vec<Option<&str>> = entries.iter().map(|e| e.tag.as_deref()).collect();
+        assert_eq!(
+            tags,
+            vec![
+                Some("Memory"),
+                Some("Project Context"),
+                Some("Preferences"),
+                Some("People")
+            ]
+        );
+        assert_eq!(entries[0].content, "Loose note before any section.");
+        assert_eq!(
+            entries[1].content,
+            "- Monorepo with Rust services\n- Deploys on Fridays are frozen"
+        );
+        assert!(entries[2].content.contains("## Not a heading"));
+        assert!(entries[2].content.ends_with("- Prefers short answers"));
+
+        // No headings: a single untagged entry
+        let entries = split_memory_markdown("- one\n- two\n");
+        assert_eq!(
+            entries,
+            vec![MemoryEntry {
+                tag: None,
+                content: "- one\n- two".to_string()
+            }]
+        );
+
+        // Oversized sections split per bullet, keeping the heading as tag
+        let bullets: String = (0..40)
+            .map(|i| format!("- fact {i}: {}\n  continued\n", "x".repeat(80)))
+            .collect();
+        let entries = split_memory_markdown(&format!("## Facts\n{bullets}"));
+        assert_eq!(entries.len(), 40);
+        assert!(entries.iter().all(|e| e.tag.as_deref() == Some("Facts")));
+        assert!(entries[3].content.starts_with("- fact 3:"));
+        assert!(entries[3].content.ends_with("continued"));
+
+        // A long bullet-less section is chunked at ~2KB
+        let prose = "word ".repeat(1000);
+        let entries = split_memory_markdown(&format!("## Notes\n{prose}\nlast line\n"));
+        assert!(entries.len() >= 3);
+        assert!(entries
+            .iter()
+            .all(|e| e.content.len() <= MEMORY_ENTRY_MAX_BYTES));
+        assert!(entries.last().unwrap().content.ends_with("\nlast line"));
+        assert!(entries.iter().all(|e| e.content.starts_with("word")));
+
+        // Multi-byte text is never cut inside a character
+        let chunks = chunk_text(&"é".repeat(3000), MEMORY_ENTRY_MAX_BYTES);
+        assert_eq!(chunks.concat(), "é".repeat(3000));
+    }
+
+    #[test]
+    fn test_memory_layouts_merged() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "coder" }, { id: "writer" } ] } }"#,
+        )
+        .unwrap();
+        let write_memory = |layout: &str, agent: &str, content: &str| {
+            let dir = source.path().join(layout).join(agent);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("MEMORY.md"), content).unwrap();
+        };
+        write_memory("memory", "coder", "## Recent\n- Moved to Rust 2024\n");
+        write_memory("agents", "coder", "## Old\n- Used Python at first\n");
+        write_memory("memory", "writer", "- Likes em dashes\n");
+        write_memory("agents", "writer", "- Likes em dashes\n\n");
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let coder =
+            std::fs::read_to_string(target.path().join("agents/coder/imported_memory.md")).unwrap();
+        assert!(coder.contains("Moved to Rust 2024"));
+        assert!(coder.contains("Used Python at first"));
+        assert!(coder.contains("# Imported from memory/coder/MEMORY.md"));
+        assert!(coder.contains("# Imported from agents/coder/MEMORY.md"));
+        assert!(coder.find("Moved to Rust").unwrap() < coder.find("Used Python").unwrap());
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.kind == ItemKind::Memory
+                && i.name.starts_with("coder/MEMORY.md (merged from")));
+
+        // Identical content in both layouts is imported once, without separators
+        let writer =
+            std::fs::read_to_string(target.path().join("agents/writer/imported_memory.md"))
+                .unwrap();
+        assert_eq!(split_provenance(&writer).1, "- Likes em dashes\n");
+        assert!(report.imported.iter().any(|i| i.name == "writer/MEMORY.md"));
+    }
+
+    #[test]
+    fn test_default_memory_remapped() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "assistant" }, { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        for (dir, text) in [
+            ("default", "Main session notes"),
+            ("retired-bot", "Nobody owns this"),
+        ] {
+            let mem = source.path().join("memory").join(dir);
+            std::fs::create_dir_all(&mem).unwrap();
+            std::fs::write(mem.join("MEMORY.md"), text).unwrap();
+        }
+
+        let run = |default_agent_target: Option<&str>| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                default_agent_target: default_agent_target.map(str::to_string),
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            (target, report)
+        };
+
+        // Falls back to the first agent in the list
+        let (target, report) = run(None);
+        let agents = target.path().join("agents");
+        let memory = std::fs::read_to_string(agents.join("assistant/imported_memory.md")).unwrap();
+        assert_eq!(split_provenance(&memory).1, "Main session notes");
+        assert!(!agents.join("default").exists());
+        assert!(!agents.join("retired-bot").exists());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("memory/retired-bot/MEMORY.md")));
+
+        // Or to the configured target
+        let (target, report) = run(Some("coder"));
+        let agents = target.path().join("agents");
+        assert!(agents.join("coder/imported_memory.md").exists());
+        assert!(!agents.join("assistant/imported_memory.md").exists());
+        assert!(!report
+            .warnings
+            .iter()
+            .any(|w| w.contains("Default agent target")));
+
+        // An unknown target is reported and the first agent used instead
+        let (target, report) = run(Some("nobody"));
+        let agents = target.path().join("agents");
+        assert!(agents.join("assistant/imported_memory.md").exists());
+        assert!(report.warnings.iter().any(|w| w.contains(
+            "Default agent target 'nobody' is not a migrated agent; main-session memory and \
+             sessions go to 'assistant' instead"
+        )));
+    }
+
+    #[test]
+    fn test_redirected_memory_dir() {
+        let source = TempDir::new().unwrap();
+        let elsewhere = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            format!(
+                r#"{{
+  memory: {{ dir: "{}" }},
+  agents: {{ list: [
+    {{ id: "coder" }},
+    {{ id: "writer", memory: {{ path: "notes/writer.md" }} }},
+    {{ id: "ghost", memory: {{ path: "notes/missing.md" }} }},
+  ] }},
+}}"#,
+                elsewhere.path().display().to_string().replace('\\', "/")
+            ),
+        )
+        .unwrap();
+        let coder = elsewhere.path().join("coder");
+        std::fs::create_dir_all(&coder).unwrap();
+        std::fs::write(coder.join("MEMORY.md"), "Coder notes from /srv").unwrap();
+        std::fs::create_dir_all(source.path().join("notes")).unwrap();
+        std::fs::write(source.path().join("notes/writer.md"), "Writer notes").unwrap();
+
+        let target = TempDir::new().unwrap();
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let agents = target.path().join("agents");
+        assert_eq!(
+            split_provenance(
+                &std::fs::read_to_string(agents.join("coder/imported_memory.md")).unwrap()
+            )
+            .1,
+            "Coder notes from /srv"
+        );
+        assert_eq!(
+            split_provenance(
+                &std::fs::read_to_string(agents.join("writer/imported_memory.md")).unwrap()
+            )
+            .1,
+            "Writer notes"
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("'ghost'") && w.contains("notes/missing.md")));
+
+        // A configured dir that doesn't exist is reported
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ memory: { dir: "/nonexistent/claw/memory" }, agents: { list: [ { id: "coder" } ] } }"#,
+        )
+        .unwrap();
+        let report = migrate(&options).unwrap();
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("memory.dir '/nonexistent/claw/memory' does not exist")));
+    }
+
+    #[test]
+    fn test_agents_map_form() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  agents: {
+    defaults: { model: "openai/gpt-4o" },
+    researcher: { name: "Researcher", id: "ignored" },
+    coder: { name: "Coder", model: "anthropic/claude-sonnet-4-20250514" },
+  },
+}"#,
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let agents = target.path().join("agents");
+        let researcher: toml::Value =
+            toml::from_str(&std::fs::read_to_string(agents.join("researcher/agent.toml")).unwrap())
+                .unwrap();
+        assert_eq!(
+            researcher["metadata"]["source_id"].as_str(),
+            Some("researcher")
+        );
+        assert_eq!(researcher["model"]["provider"].as_str(), Some("openai"));
+        assert!(agents.join("coder/agent.toml").exists());
+        assert!(!agents.join("defaults").exists());
+        assert_eq!(
+            report
+                .imported
+                .iter()
+                .filter(|i| i.kind == ItemKind::Agent)
+                .count(),
+            2
+        );
+
+        // Source order is kept
+        let root: OpenClawRoot =
+            json5::from_str(r#"{ agents: { zeta: {}, alpha: {}, enabled: true } }"#).unwrap();
+        let ids: Vec<String> = root
+            .agents
+            .unwrap()
+            .list
+            .into_iter()
+            .map(|e| e.id)
+            .collect();
+        assert_eq!(ids, vec!["zeta", "alpha"]);
+
+        // With a `list`, other keys are not agents
+        let root: OpenClawRoot =
+            json5::from_str(r#"{ agents: { list: [ { id: "a" } ], extra: {} } }"#).unwrap();
+        assert_eq!(root.agents.unwrap().list.len(), 1);
+    }
+
+    #[test]
+    fn test_oversized_memory_chunked() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "logger" } ] } }"#,
+        )
+        .unwrap();
+        // ~5 MB of appended daily logs
+        let line = format!("- {}\n", "event ".repeat(16));
+        let day = line.repeat(50);
+        let memory: String = (0..1100)
+            .map(|i| format!("## Day {i:04}\n{day}\n"))
+            .collect();
+        assert!(memory.len() > 5 * 1024 * 1024);
+        let mem_dir = source.path().join("memory/logger");
+        std::fs::create_dir_all(&mem_dir).unwrap();
+        std::fs::write(mem_dir.join("MEMORY.md"), &memory).unwrap();
+
+        let run = |memory_size_limit: Option<u64>| {
+            let target = TempDir::new().unwrap();
+            let options = MigrateOptions {
+                source: crate::MigrateSource::OpenClaw,
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                memory_size_limit,
+                ..Default::default()
+            };
+            let report = migrate(&options).unwrap();
+            (target, report)
+        };
+
+        let (target, report) = run(Some(crate::DEFAULT_MEMORY_SIZE_LIMIT));
+        let dir = target.path().join("agents/logger");
+        assert!(!dir.join("imported_memory.md").exists());
+        let mut chunks: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .filter(|n| n.starts_with("imported_memory_"))
+            .collect();
+        chunks.sort();
+        assert_eq!(chunks[0], "imported_memory_001.md");
+        let kept: Vec<String> = chunks
+            .iter()
+            .map(|n| std::fs::read_to_string(dir.join(n)).unwrap())
+            .collect();
+        let kept_bytes: usize = kept.iter().map(String::len).sum();
+        assert!(kept_bytes as u64 <= crate::DEFAULT_MEMORY_SIZE_LIMIT);
+        assert!(kept_bytes > 512 * 1024);
+        // Chunks start at a heading, and the newest day is kept but not the oldest
+        let kept: Vec<&str> = kept.iter().map(|c| split_provenance(c).1).collect();
+        let kept_bytes: usize = kept.iter().map(|c| c.len()).sum();
+        assert!(kept_bytes as u64 <= crate::DEFAULT_MEMORY_SIZE_LIMIT);
+        assert!(kept.iter().all(|c| c.starts_with("## Day ")));
+        assert!(kept.last().unwrap().contains("## Day 1099"));
+        assert!(!kept.iter().any(|c| c.contains("## Day 0000\n")));
+        let warning = report
+            .warnings
+            .iter()
+            .find(|w| w.contains("Memory for 'logger'"))
+            .unwrap();
+        assert!(warning.contains("5.") && warning.contains("MB, over the 1.0 MB limit"));
+        assert!(warning.contains(&format!("the most recent {} of", chunks.len())));
+
+        // Unlimited keeps the file whole
+        let (target, report) = run(None);
+        let full = std::fs::read_to_string(target.path().join("agents/logger/imported_memory.md"))
+            .unwrap();
+        assert_eq!(split_provenance(&full).1, memory);
+        assert!(!report.warnings.iter().any(|w| w.contains("Memory for")));
     }
 
     #[test]
-    fn test_dry_run() {
+    fn test_fallback_precedence() {
+        let simple = |m: &str| Some(OpenClawAgentModel::Simple(m.to_string()));
+        let detailed = |fallbacks: &[&str]| {
+            Some(OpenClawAgentModel::Detailed(OpenClawAgentModelDetailed {
+                primary: Some("anthropic/claude-sonnet-4-20250514".to_string()),
+                fallbacks: fallbacks.iter().map(|f| f.to_string()).collect(),
+                ..Default::default()
+            }))
+        };
+        let order =
+            serde_json::json!(["anthropic", "openai/gpt-4o", "groq/llama-3.3-70b-versatile"]);
+        let primary = "groq/llama-3.3-70b-versatile";
+
+        // (agent model, defaults model, auth order?, expected)
+        type Case = (
+            Option<OpenClawAgentModel>,
+            Option<OpenClawAgentModel>,
+            bool,
+            Vec<&'static str>,
+        );
+        let cases: Vec<Case> = vec![
+            (None, None, false, vec![]),
+            (None, None, true, vec!["openai/gpt-4o"]),
+            (simple("x/a"), None, false, vec![]),
+            (simple("x/a"), simple("x/b"), false, vec![]),
+            (simple("x/a"), simple("x/b"), true, vec!["openai/gpt-4o"]),
+            (simple("x/a"), detailed(&["d/1"]), false, vec!["d/1"]),
+            (simple("x/a"), detailed(&[]), true, vec!["openai/gpt-4o"]),
+            (detailed(&["a/1"]), None, true, vec!["a/1"]),
+            (detailed(&["a/1"]), simple("x/b"), false, vec!["a/1"]),
+            (detailed(&["a/1"]), detailed(&["d/1"]), true, vec!["a/1"]),
+            (detailed(&[]), detailed(&["d/1"]), true, vec!["d/1"]),
+            (detailed(&[]), simple("x/b"), false, vec![]),
+            (detailed(&[]), simple("x/b"), true, vec!["openai/gpt-4o"]),
+            (None, detailed(&["d/1", "d/2"]), false, vec!["d/1", "d/2"]),
+        ];
+        for (i, (agent_model, defaults_model, with_order, expected)) in
+            cases.into_iter().enumerate()
+        {
+            let agent = OpenClawAgentEntry {
+                id: "a".to_string(),
+                model: agent_model,
+                ..Default::default()
+            };
+            let defaults = OpenClawAgentDefaults {
+                model: defaults_model,
+                ..Default::default()
+            };
+            let fallbacks = extract_fallback_models(
+                &agent,
+                Some(&defaults),
+                with_order.then_some(&order),
+                primary,
+            );
+            assert_eq!(fallbacks, expected, "case {i}");
+        }
+    }
+
+    #[test]
+    fn test_root_tools_template() {
+        let json5_content = r#"{
+  tools: { profile: "minimal" },
+  agents: { list: [ { id: "plain" }, { id: "own", tools: { allow: ["Bash"] } } ] },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.as_ref().unwrap();
+        let convert = |i: usize, root: Option<&OpenClawRoot>| {
+            convert_agent_from_json(
+                &agents.list[i],
+                None,
+                root,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+        let tools_of = |toml_str: &str| -> Vec<String> {
+            let v: toml::Value = toml::from_str(toml_str).unwrap();
+            v["capabilities"]["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect()
+        };
+
+        let plain = convert(0, Some(&root));
+        assert_eq!(tools_of(&plain.toml), tools_for_profile("minimal"));
+        // Agents with their own tools ignore the root template
+        let own = convert(1, Some(&root));
+        assert_eq!(tools_of(&own.toml), vec!["shell_exec"]);
+
+        // Root allow minus deny
+        let root = OpenClawRoot {
+            tools: Some(OpenClawRootTools {
+                allow: Some(vec!["read_file".into(), "Bash".into(), "web_search".into()]),
+                deny: Some(vec!["exec".into()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let plain = convert(0, Some(&root));
+        assert_eq!(tools_of(&plain.toml), vec!["file_read", "web_search"]);
+    }
+
+    #[test]
+    fn test_subagents_migrated() {
         let source = TempDir::new().unwrap();
         let target = TempDir::new().unwrap();
-
-        create_legacy_yaml_workspace(source.path());
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  agents: {
+    list: [
+      {
+        id: "lead",
+        model: "openai/gpt-4o",
+        tools: { allow: ["read_file"] },
+        subagents: [
+          { id: "tester", identity: "You write tests." },
+          { id: "docs", name: "Docs Writer", model: "anthropic/claude-haiku-4-5-20251001" },
+        ],
+      },
+    ],
+  },
+}"#,
+        )
+        .unwrap();
 
         let options = MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
-            dry_run: true,
+            ..Default::default()
         };
-
         let report = migrate(&options).unwrap();
-        assert!(report.dry_run);
-        assert!(!report.imported.is_empty());
 
-        assert!(!target.path().join("config.toml").exists());
+        let agents = target.path().join("agents");
+        let parent = std::fs::read_to_string(agents.join("lead/agent.toml")).unwrap();
+        let parent: toml::Value = toml::from_str(&parent).unwrap();
+        assert_eq!(parent["capabilities"]["agent_spawn"].as_bool(), Some(true));
+        let messages: Vec<&str> = parent["capabilities"]["agent_message"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(messages, vec!["lead__tester", "Docs Writer"]);
+
+        // Children inherit the parent's model unless they set one
+        let tester = std::fs::read_to_string(agents.join("lead__tester/agent.toml")).unwrap();
+        assert!(tester.contains("name = \"lead__tester\""));
+        assert!(tester.contains("model = \"gpt-4o\""));
+        assert!(tester.contains("You write tests."));
+        let docs = std::fs::read_to_string(agents.join("lead__docs/agent.toml")).unwrap();
+        assert!(docs.contains("name = \"Docs Writer\""));
+        assert!(docs.contains("model = \"claude-haiku-4-5-20251001\""));
+
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "lead__tester (subagent of lead)"));
     }
 
     #[test]
-    fn test_source_not_found() {
+    fn test_scan_reports_malformed_config() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("openclaw.json"), "{ agents: { list: [ }").unwrap();
+
+        let result = scan_openclaw_workspace(dir.path());
+        assert!(result.has_config);
+        assert!(result.agents.is_empty());
+        assert_eq!(result.scan_errors.len(), 1);
+        assert!(result.scan_errors[0].contains("openclaw.json"));
+
+        // A valid config scans cleanly
+        let ok = TempDir::new().unwrap();
+        create_json5_workspace(ok.path());
+        assert!(scan_openclaw_workspace(ok.path()).scan_errors.is_empty());
+    }
+
+    #[test]
+    fn test_bytes_copied() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), "{}").unwrap();
+        let ws = source.path().join("workspaces").join("coder").join("src");
+        std::fs::create_dir_all(&ws).unwrap();
+        std::fs::write(ws.join("main.rs"), vec![b'a'; 1000]).unwrap();
+        std::fs::write(ws.parent().unwrap().join("notes.md"), vec![b'b'; 250]).unwrap();
+        let sessions = source.path().join("sessions");
+        std::fs::create_dir_all(&sessions).unwrap();
+        std::fs::write(sessions.join("s1.jsonl"), vec![b'c'; 4096]).unwrap();
+
         let options = MigrateOptions {
             source: crate::MigrateSource::OpenClaw,
-            source_dir: "/nonexistent/path".into(),
-            target_dir: std::env::temp_dir().join("test_migrate_not_found"),
-            dry_run: false,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
         };
+        let report = migrate(&options).unwrap();
+        assert_eq!(report.bytes_copied, 1000 + 250 + 4096);
 
-        let result = migrate(&options);
-        assert!(result.is_err());
+        // Nothing is copied in a dry run
+        let report = migrate(&MigrateOptions {
+            dry_run: true,
+            ..options
+        })
+        .unwrap();
+        assert_eq!(report.bytes_copied, 0);
     }
 
     #[test]
-    fn test_tool_mapping() {
-        assert_eq!(map_tool_name("read_file"), Some("file_read"));
-        assert_eq!(map_tool_name("write_file"), Some("file_write"));
-        assert_eq!(map_tool_name("execute_command"), Some("shell_exec"));
-        assert_eq!(map_tool_name("fetch_url"), Some("web_fetch"));
-        assert_eq!(map_tool_name("memory_search"), Some("memory_recall"));
-        assert_eq!(map_tool_name("unknown_tool"), None);
-        // New Claude-style mappings
-        assert_eq!(map_tool_name("Read"), Some("file_read"));
-        assert_eq!(map_tool_name("Write"), Some("file_write"));
-        assert_eq!(map_tool_name("Bash"), Some("shell_exec"));
-        assert_eq!(map_tool_name("Glob"), Some("file_list"));
-        assert_eq!(map_tool_name("Grep"), Some("file_list"));
-        assert_eq!(map_tool_name("WebSearch"), Some("web_search"));
-        assert_eq!(map_tool_name("WebFetch"), Some("web_fetch"));
-        assert_eq!(map_tool_name("sessions_send"), Some("agent_send"));
-        assert_eq!(map_tool_name("sessions_spawn"), Some("agent_send"));
+    fn test_derive_capabilities_families() {
+        let tools =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|s| s.to_string()).collect() };
+
+        // Browser tools need network access
+        let caps = derive_capabilities(&tools(&["browser_click", "browser_screenshot"]));
+        assert_eq!(caps.network, vec!["*"]);
+
+        // Schedule tools need no grant beyond the tool itself
+        let caps = derive_capabilities(&tools(&["schedule_create", "schedule_list"]));
+        assert!(caps.network.is_empty() && caps.shell.is_empty());
+
+        // Memory defaults stay as before; storing widens writes to shared memory
+        let caps = derive_capabilities(&tools(&["file_read"]));
+        assert_eq!(caps.memory_read, vec!["*"]);
+        assert_eq!(caps.memory_write, vec!["self.*"]);
+        let caps = derive_capabilities(&tools(&["memory_recall", "memory_store"]));
+        assert_eq!(caps.memory_read, vec!["*"]);
+        assert_eq!(caps.memory_write, vec!["self.*", "shared.*"]);
+
+        // The full wildcard grants everything
+        let caps = derive_capabilities(&tools(&["*"]));
+        assert_eq!(caps.memory_write, vec!["*"]);
+        assert!(caps.agent_spawn);
     }
 
     #[test]
-    fn test_provider_mapping() {
-        assert_eq!(map_provider("anthropic"), "anthropic");
-        assert_eq!(map_provider("claude"), "anthropic");
-        assert_eq!(map_provider("openai"), "openai");
-        assert_eq!(map_provider("gpt"), "openai");
-        assert_eq!(map_provider("groq"), "groq");
-        assert_eq!(map_provider("custom"), "custom");
-        assert_eq!(map_provider("google"), "google");
-        assert_eq!(map_provider("gemini"), "google");
-        assert_eq!(map_provider("xai"), "xai");
-        assert_eq!(map_provider("grok"), "xai");
+    fn test_network_scope_from_allow_domains() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      { id: "scoped", tools: { allow: ["web_fetch"], webFetch: { allowDomains: ["docs.rs", "*.github.com"] } } },
+      { id: "open", tools: { allow: ["web_fetch"] } },
+      { id: "mixed", tools: { allow: ["web_fetch", "browser_navigate"], webFetch: { allowDomains: ["docs.rs"] } } },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.unwrap();
+        let convert = |i: usize| {
+            convert_agent_from_json(
+                &agents.list[i],
+                None,
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
+
+        let scoped = convert(0);
+        let manifest: toml::Value = toml::from_str(&scoped.toml).unwrap();
+        let network: Vec<&str> = manifest["capabilities"]["network"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(
+            network,
+            ["*.github.com:443", "*.github.com:80", "docs.rs:*"]
+        );
+        assert!(scoped.warnings.is_empty());
+        // The patterns match the `host:port` the kernel checks
+        use openfang_types::capability::{capability_matches, Capability};
+        let allows = |host: &str| {
+            network.iter().any(|p| {
+                capability_matches(
+                    &Capability::NetConnect(p.to_string()),
+                    &Capability::NetConnect(host.to_string()),
+                )
+            })
+        };
+        assert!(allows("docs.rs:443"));
+        assert!(allows("api.github.com:443"));
+        assert!(allows("api.github.com:80"));
+        assert!(!allows("example.com:443"));
+
+        let open = convert(1);
+        assert!(open.toml.contains("network = [\"*\"]"));
+
+        let mixed = convert(2);
+        assert!(mixed.toml.contains("network = [\"*\"]"));
+        assert!(mixed
+            .warnings
+            .iter()
+            .any(|w| w.contains("network scope widened")));
     }
 
     #[test]
-    fn test_tools_for_profile() {
-        let minimal = tools_for_profile("minimal");
-        assert_eq!(minimal.len(), 2);
-        assert!(minimal.contains(&"file_read".to_string()));
+    fn test_shell_scope_from_allowlist() {
+        let json5_content = r#"{
+  agents: {
+    list: [
+      { id: "scoped", tools: { allow: ["Bash"], bash: { allow: ["git *", "cargo *"] } } },
+      { id: "open", tools: { allow: ["Bash"] } },
+      { id: "denied", tools: { allow: ["Bash"], bash: { deny: ["rm *"] } } },
+    ],
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let agents = root.agents.unwrap();
+        let convert = |i: usize| {
+            convert_agent_from_json(
+                &agents.list[i],
+                None,
+                None,
+                &MigrateOptions::default(),
+                &ToolMap::default(),
+            )
+            .unwrap()
+        };
 
-        let coding = tools_for_profile("coding");
-        assert!(coding.contains(&"shell_exec".to_string()));
+        let scoped = convert(0);
+        let open = convert(1);
+        let shell_of = |toml_str: &str| {
+            let v: toml::Value = toml::from_str(toml_str).unwrap();
+            v["capabilities"]["shell"].clone()
+        };
+        assert_eq!(
+            shell_of(&scoped.toml),
+            toml::Value::Array(vec!["cargo *".into(), "git *".into()])
+        );
+        assert_eq!(shell_of(&open.toml), toml::Value::Array(vec!["*".into()]));
+        assert_ne!(shell_of(&scoped.toml), shell_of(&open.toml));
 
-        let full = tools_for_profile("full");
-        assert!(full.contains(&"*".to_string()));
+        let denied = convert(2);
+        assert!(denied.toml.contains("shell = [\"*\"]"));
+        assert!(denied
+            .warnings
+            .iter()
+            .any(|w| w.contains("shell deny patterns")));
+    }
 
-        let automation = tools_for_profile("automation");
-        assert!(automation.len() >= 10);
-        assert!(automation.contains(&"shell_exec".to_string()));
-        assert!(automation.contains(&"web_fetch".to_string()));
+    #[test]
+    fn test_telegram_allowlist_fails_closed() {
+        let target = TempDir::new().unwrap();
+        let root: OpenClawRoot = json5::from_str(
+            r#"{ channels: { telegram: { botToken: "t", allowFrom: ["alice", "@bob"] } } }"#,
+        )
+        .unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+            &mut report,
+        );
+
+        // No IDs left would mean an empty allowed_users, which lets everyone in
+        assert!(channels.is_none_or(|c| c.get("telegram").is_none()));
+        let skipped = report
+            .skipped
+            .iter()
+            .find(|s| s.name == "telegram")
+            .expect("telegram should be skipped");
+        assert!(
+            skipped.reason.contains("'alice', '@bob'"),
+            "{}",
+            skipped.reason
+        );
+        assert!(!report.imported.iter().any(|i| i.name == "telegram"));
+        assert!(!report
+            .imported
+            .iter()
+            .any(|i| i.name == "TELEGRAM_BOT_TOKEN"));
     }
 
     #[test]
-    fn test_convert_agent() {
-        let dir = TempDir::new().unwrap();
-        let yaml_path = dir.path().join("agent.yaml");
+    fn test_channel_block_list() {
+        let json5_content = r#"{
+  channels: {
+    telegram: { botToken: "t", allowFrom: ["1001"], blockFrom: ["b"] },
+    discord: { token: "d", allowFrom: ["a"], denyFrom: [] },
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let target = TempDir::new().unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+            &mut report,
+        )
+        .unwrap();
+
+        let tg = &channels["telegram"]["overrides"];
+        assert_eq!(tg["allowed_users"][0].as_str(), Some("1001"));
+        // OpenFang has no block list, so it is reported rather than written
+        assert!(tg.get("blocked_users").is_none());
+        assert!(report.skipped.iter().any(|s| s.kind == ItemKind::Channel
+            && s.name == "telegram blockFrom"
+            && s.reason.contains("so b will not be blocked")));
+        // An empty block list isn't reported
+        assert!(!report.skipped.iter().any(|s| s.name == "discord blockFrom"));
+    }
+
+    #[test]
+    fn test_phone_number_carried() {
+        let json5_content = r#"{
+  channels: {
+    whatsapp: { phoneNumber: "+15551234567" },
+    signal: { phoneNumber: "+15557654321" },
+  },
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let target = TempDir::new().unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+            &mut report,
+        )
+        .unwrap();
+        // WhatsApp has no phone number field, so the number is only reported
+        assert!(channels["whatsapp"].get("phone_number").is_none());
+        assert!(report
+            .warnings
+            .contains(&whatsapp_phone_warning("+15551234567")));
+        assert_eq!(
+            channels["signal"]["phone_number"].as_str(),
+            Some("+15557654321")
+        );
+
+        // Legacy messaging YAML
+        let mut report = MigrationReport::default();
+        let source = TempDir::new().unwrap();
+        let messaging = source.path().join("messaging");
+        std::fs::create_dir_all(&messaging).unwrap();
         std::fs::write(
-            &yaml_path,
-            "name: test-agent\ndescription: Test\ntools:\n  - read_file\n  - web_search\n",
+            messaging.join("whatsapp.yaml"),
+            "type: whatsapp\nphone_number: \"+15551234567\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            messaging.join("signal.yaml"),
+            "type: signal\naccount: \"+15557654321\"\n",
         )
         .unwrap();
+        let channels = parse_legacy_channels(source.path(), target.path(), true, &mut report)
+            .unwrap()
+            .unwrap();
+        assert!(channels["whatsapp"].get("phone_number").is_none());
+        assert!(report
+            .warnings
+            .contains(&whatsapp_phone_warning("+15551234567")));
+        assert_eq!(
+            channels["signal"]["phone_number"].as_str(),
+            Some("+15557654321")
+        );
+    }
 
-        let (toml_str, unmapped) = convert_legacy_agent(&yaml_path, "test-agent").unwrap();
-        assert!(toml_str.contains("name = \"test-agent\""));
-        assert!(toml_str.contains("file_read"));
-        assert!(toml_str.contains("web_search"));
-        assert!(
-            toml_str.contains("network = [\"*\"]"),
-            "web_search should derive network capability"
+    #[test]
+    fn test_mcp_servers_migrated() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{
+  tools: {
+    mcp: {
+      github: {
+        command: "npx",
+        args: ["-y", "@modelcontextprotocol/server-github"],
+        env: { GITHUB_PERSONAL_ACCESS_TOKEN: "ghp_abc123", LOG_LEVEL: "debug" },
+        allow: ["create_issue", "list-prs"],
+      },
+      search: { transport: "http", url: "https://mcp.example.com/sse", env: { API_KEY: "${SEARCH_KEY}" } },
+      legacy: { transport: "websocket", url: "wss://old.example.com" },
+    },
+  },
+  agents: { list: [ { id: "dev", tools: { allow: ["read_file", "mcp:github"] } } ] },
+}"#,
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        let config = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
+        let parsed: toml::Value = toml::from_str(&config).unwrap();
+        let servers = parsed["mcp_servers"].as_array().unwrap();
+        assert_eq!(servers.len(), 2);
+        let github = &servers[0];
+        assert_eq!(github["name"].as_str(), Some("github"));
+        assert_eq!(github["transport"]["type"].as_str(), Some("stdio"));
+        assert_eq!(github["transport"]["command"].as_str(), Some("npx"));
+        assert_eq!(github["transport"]["args"].as_array().unwrap().len(), 2);
+        let env: Vec<&str> = github["env"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
+        assert_eq!(env, vec!["GITHUB_PERSONAL_ACCESS_TOKEN", "LOG_LEVEL"]);
+        let search = &servers[1];
+        assert_eq!(search["transport"]["type"].as_str(), Some("sse"));
+        assert_eq!(
+            search["transport"]["url"].as_str(),
+            Some("https://mcp.example.com/sse")
         );
-        assert!(unmapped.is_empty());
+        assert_eq!(search["env"][0].as_str(), Some("SEARCH_KEY"));
+
+        // Only credential-looking values land in secrets.env
+        let secrets = std::fs::read_to_string(target.path().join("secrets.env")).unwrap();
+        assert!(secrets.contains("GITHUB_PERSONAL_ACCESS_TOKEN=ghp_abc123"));
+        assert!(!secrets.contains("LOG_LEVEL"));
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("LOG_LEVEL=debug")));
+
+        // Unknown transports are skipped with a reason
+        assert!(report.skipped.iter().any(|s| s.kind == ItemKind::McpServer
+            && s.name == "legacy"
+            && s.reason.contains("websocket")));
+
+        // Agents referencing the server gain its tools
+        let agent = std::fs::read_to_string(target.path().join("agents/dev/agent.toml")).unwrap();
+        assert!(agent.contains("mcp_servers = [\"github\"]"));
+        assert!(agent.contains("\"mcp_github_create_issue\""));
+        assert!(agent.contains("\"mcp_github_list_prs\""));
+        assert!(agent.contains("\"file_read\""));
+        let manifest: openfang_types::agent::AgentManifest = toml::from_str(&agent).unwrap();
+        assert_eq!(manifest.mcp_servers, vec!["github".to_string()]);
     }
 
     #[test]
-    fn test_capability_derivation() {
-        let tools = vec!["shell_exec".into(), "web_fetch".into(), "agent_send".into()];
-        let caps = derive_capabilities(&tools);
-        assert_eq!(caps.shell, vec!["*".to_string()]);
-        assert_eq!(caps.network, vec!["*".to_string()]);
-        assert_eq!(caps.agent_message, vec!["*".to_string()]);
-        assert!(caps.agent_spawn);
+    fn test_default_model_override() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [ { id: "plain" } ] } }"#,
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            default_provider: Some("openai".to_string()),
+            default_model: Some("gpt-4o".to_string()),
+            ..Default::default()
+        };
+        migrate(&options).unwrap();
+
+        let agent = std::fs::read_to_string(target.path().join("agents/plain/agent.toml")).unwrap();
+        assert!(agent.contains("provider = \"openai\""));
+        assert!(agent.contains("model = \"gpt-4o\""));
+        assert!(agent.contains("OPENAI_API_KEY"));
+        let config = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
+        assert!(config.contains("provider = \"openai\""));
+        assert!(config.contains("model = \"gpt-4o\""));
+
+        // Legacy agents without a model get the same fallback
+        let dir = TempDir::new().unwrap();
+        let yaml_path = dir.path().join("agent.yaml");
+        std::fs::write(&yaml_path, "name: plain\n").unwrap();
+        let converted =
+            convert_legacy_agent(&yaml_path, "plain", &options, &ToolMap::default()).unwrap();
+        assert!(converted.toml.contains("provider = \"openai\""));
+        assert!(converted.toml.contains("model = \"gpt-4o\""));
     }
 
     #[test]
-    fn test_unmapped_tools_reported() {
+    fn test_tool_map_file() {
         let dir = TempDir::new().unwrap();
+        let map_path = dir.path().join("tools.toml");
+        std::fs::write(
+            &map_path,
+            "Grep = \"web_search\"\njira_create = \"jira_create\"\nvault_read = \"file_read\"\n",
+        )
+        .unwrap();
         let yaml_path = dir.path().join("agent.yaml");
         std::fs::write(
             &yaml_path,
-            "name: test\ntools:\n  - read_file\n  - some_custom_tool\n  - another_unknown\n",
+            "name: t\ntools:\n  - Grep\n  - jira_create\n  - vault_read\n",
         )
         .unwrap();
 
-        let (toml_str, unmapped) = convert_legacy_agent(&yaml_path, "test").unwrap();
-        assert!(toml_str.contains("file_read"));
-        assert!(!toml_str.contains("some_custom_tool"));
-        assert_eq!(unmapped.len(), 2);
-        assert!(unmapped.contains(&"some_custom_tool".to_string()));
-        assert!(unmapped.contains(&"another_unknown".to_string()));
+        // Unknown target rejected without trust_unknown_targets
+        let mut options = MigrateOptions {
+            tool_map: Some(map_path.clone()),
+            ..Default::default()
+        };
+        let mut warnings = Vec::new();
+        let tool_map = ToolMap::load(&options, &mut warnings).unwrap();
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("'jira_create' is not a known OpenFang tool")));
+        // Override of a builtin mapping wins, with a warning
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w
+                    .contains("'Grep' -> 'web_search' overrides builtin mapping to 'file_list'"))
+        );
+        let converted = convert_legacy_agent(&yaml_path, "t", &options, &tool_map).unwrap();
+        assert!(converted.toml.contains("\"web_search\""));
+        assert!(!converted.toml.contains("\"file_list\""));
+        // New mapping
+        assert!(converted.toml.contains("\"file_read\""));
+        assert_eq!(converted.unmapped_tools, vec!["jira_create".to_string()]);
+
+        // Trusted custom targets are kept
+        options.trust_unknown_targets = true;
+        let mut warnings = Vec::new();
+        let tool_map = ToolMap::load(&options, &mut warnings).unwrap();
+        let converted = convert_legacy_agent(&yaml_path, "t", &options, &tool_map).unwrap();
+        assert!(converted.toml.contains("\"jira_create\""));
+        assert!(converted.unmapped_tools.is_empty());
+
+        // JSON files are accepted too
+        let json_path = dir.path().join("tools.json");
+        std::fs::write(&json_path, r#"{"vault_read": "file_read"}"#).unwrap();
+        options.tool_map = Some(json_path);
+        let tool_map = ToolMap::load(&options, &mut Vec::new()).unwrap();
+        assert_eq!(tool_map.get("vault_read"), Some("file_read"));
     }
 
     #[test]
-    fn test_scan_workspace() {
+    fn test_tool_aliases() {
         let source = TempDir::new().unwrap();
-        create_legacy_yaml_workspace(source.path());
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ agents: { list: [{ id: "ops", tools: { allow: ["my_tool", "mystery_tool"] } }] } }"#,
+        )
+        .unwrap();
 
-        let result = scan_openclaw_workspace(source.path());
-        assert!(result.has_config);
-        assert_eq!(result.agents.len(), 1);
-        assert_eq!(result.agents[0].name, "coder");
-        assert!(result.agents[0].has_memory);
-        assert_eq!(result.channels.len(), 1);
-        assert!(result.channels.contains(&"telegram".to_string()));
+        let mut options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+        let toml = std::fs::read_to_string(target.path().join("agents/ops/agent.toml")).unwrap();
+        assert!(!toml.contains("\"shell_exec\""));
+        assert!(report.warnings.iter().any(|w| w.contains("my_tool")));
+
+        options.tool_aliases =
+            std::collections::HashMap::from([("my_tool".to_string(), "shell_exec".to_string())]);
+        let target = TempDir::new().unwrap();
+        options.target_dir = target.path().to_path_buf();
+        let report = migrate(&options).unwrap();
+        let toml = std::fs::read_to_string(target.path().join("agents/ops/agent.toml")).unwrap();
+        assert!(toml.contains("\"shell_exec\""));
+        assert!(!report.warnings.iter().any(|w| w.contains("my_tool")));
+        // Tools without an alias still warn
+        assert!(report.warnings.iter().any(|w| w.contains("mystery_tool")));
     }
 
     #[test]
-    fn test_scan_json5_workspace() {
-        let source = TempDir::new().unwrap();
-        create_json5_workspace(source.path());
-
-        let result = scan_openclaw_workspace(source.path());
-        assert!(result.has_config);
-        assert_eq!(result.agents.len(), 2);
-        assert!(result.agents.iter().any(|a| a.name == "Coder"));
-        assert!(result.agents.iter().any(|a| a.name == "researcher"));
-        // All 13 channels detected by scanner
+    fn test_fuzzy_tool_names() {
+        // Case/underscore-only differences are accepted outright
+        assert_eq!(suggest_tool_name("Web_Search"), Some(("web_search", true)));
+        assert_eq!(suggest_tool_name("readFile"), Some(("file_read", true)));
+        assert_eq!(suggest_tool_name("shellexec"), Some(("shell_exec", true)));
+        let (name, note) = resolve_tool_name("Web_Search", &ToolMap::default()).unwrap();
+        assert_eq!(name, "web_search");
+        assert!(note.unwrap().contains("case/underscore-insensitive"));
+
+        // Near-misses only produce a suggestion
+        assert_eq!(suggest_tool_name("web_serch"), Some(("web_search", false)));
+        assert!(resolve_tool_name("web_serch", &ToolMap::default()).is_none());
+        assert!(unmapped_tool_warning("web_serch").contains("did you mean 'web_search'?"));
+
+        // Genuinely unknown names keep the plain warning
+        assert_eq!(suggest_tool_name("some_custom_tool"), None);
         assert_eq!(
-            result.channels.len(),
-            13,
-            "expected 13 channels, got {:?}",
-            result.channels
+            unmapped_tool_warning("some_custom_tool"),
+            "tool 'some_custom_tool' has no OpenFang equivalent and was skipped"
         );
-        assert!(result.channels.contains(&"telegram".to_string()));
-        assert!(result.channels.contains(&"discord".to_string()));
-        assert!(result.channels.contains(&"slack".to_string()));
-        assert!(result.channels.contains(&"whatsapp".to_string()));
-        assert!(result.channels.contains(&"signal".to_string()));
-        assert!(result.channels.contains(&"matrix".to_string()));
-        assert!(result.channels.contains(&"irc".to_string()));
-        assert!(result.channels.contains(&"mattermost".to_string()));
-        assert!(result.channels.contains(&"feishu".to_string()));
-        assert!(result.channels.contains(&"teams".to_string()));
-        assert!(result.channels.contains(&"imessage".to_string()));
-        assert!(result.channels.contains(&"bluebubbles".to_string()));
-        assert!(result.has_memory);
     }
 
     #[test]
@@ -4062,6 +14033,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         let report = migrate(&options).unwrap();
@@ -4113,6 +14085,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_session_scope_skipped() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(
+            source.path().join("openclaw.json"),
+            r#"{ session: { scope: "per-sender" } }"#,
+        )
+        .unwrap();
+
+        let options = MigrateOptions {
+            source: crate::MigrateSource::OpenClaw,
+            source_dir: source.path().to_path_buf(),
+            target_dir: target.path().to_path_buf(),
+            ..Default::default()
+        };
+        let report = migrate(&options).unwrap();
+
+        // The kernel has no session scope setting, so nothing is written for it
+        let config = std::fs::read_to_string(target.path().join("config.toml")).unwrap();
+        let parsed: toml::Value = toml::from_str(&config).unwrap();
+        assert!(parsed.get("session").is_none());
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.kind == ItemKind::Config && s.name == "session"));
+    }
+
+    #[test]
+    fn test_map_group_policy_synonyms() {
+        for input in [
+            "mention",
+            "mention_only",
+            "mentionOnly",
+            "mentioned",
+            "tag",
+            "tagged",
+        ] {
+            assert_eq!(map_group_policy(input), "mention_only", "{input}");
+        }
+        for input in [
+            "reply_only",
+            "respond_on_reply",
+            "respondOnReply",
+            "reply",
+            "replies",
+        ] {
+            assert_eq!(map_group_policy(input), "reply_only", "{input}");
+        }
+        assert_eq!(map_group_policy("disabled"), "ignore");
+        assert_eq!(map_group_policy("open"), "all");
+        // Unknown values fall back to the kernel's default, with a warning
+        assert_eq!(map_group_policy("whenever"), "mention_only");
+        let channels: OpenClawChannels = json5::from_str(
+            r#"{
+  telegram: { groupPolicy: "whenever" },
+  slack: { groupPolicy: "mention" },
+  matrix: { rooms: [{ id: "!r:x", groupPolicy: "sometimes" }] },
+}"#,
+        )
+        .unwrap();
+        let warnings = unknown_group_policy_warnings(&channels);
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[0].starts_with("telegram group policy 'whenever'"));
+        assert!(warnings[1].starts_with("matrix group policy 'sometimes'"));
+        let parsed: openfang_types::config::GroupPolicy =
+            serde_json::from_str("\"reply_only\"").unwrap();
+        assert_eq!(parsed, openfang_types::config::GroupPolicy::ReplyOnly);
+    }
+
     #[test]
     fn test_policy_migration() {
         let target = TempDir::new().unwrap();
@@ -4122,7 +14164,7 @@ mod tests {
       botToken: "tok",
       dmPolicy: "allowlist",
       groupPolicy: "open",
-      allowFrom: ["alice", "bob"]
+      allowFrom: ["1001", "1002"]
     },
     discord: {
       token: "tok2",
@@ -4133,7 +14175,12 @@ mod tests {
         let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
         let mut report = MigrationReport::default();
 
-        let channels = migrate_channels_from_json(&root, target.path(), false, &mut report);
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        );
         assert!(channels.is_some());
         let ch_table = channels.unwrap();
         let table = ch_table.as_table().unwrap();
@@ -4142,7 +14189,7 @@ mod tests {
         let tg = table["telegram"].as_table().unwrap();
         let overrides = tg["overrides"].as_table().unwrap();
         assert_eq!(overrides["dm_policy"].as_str().unwrap(), "allowed_only");
-        assert_eq!(overrides["group_policy"].as_str().unwrap(), "respond");
+        assert_eq!(overrides["group_policy"].as_str().unwrap(), "all");
         let users = overrides["allowed_users"].as_array().unwrap();
         assert_eq!(users.len(), 2);
 
@@ -4152,6 +14199,135 @@ mod tests {
         assert_eq!(dc_overrides["dm_policy"].as_str().unwrap(), "ignore");
     }
 
+    #[test]
+    fn test_discord_guild_routing() {
+        let json5_content = r#"{
+  agents: { list: [{ id: "coder", name: "Coder" }, { id: "writer" }] },
+  channels: {
+    discord: {
+      token: "tok",
+      guilds: {
+        "123": { agent: "coder", channels: { "456": { agent: "coder" } } },
+        "789": { requireMention: true },
+      }
+    }
+  }
+}"#;
+        let target = TempDir::new().unwrap();
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        )
+        .unwrap();
+        let dc = channels["discord"].as_table().unwrap();
+        assert_eq!(dc["default_agent"].as_str(), Some("Coder"));
+        assert!(report
+            .warnings
+            .iter()
+            .all(|w| !w.contains("Discord routes")));
+
+        // Routes to several agents keep the first and warn about the rest
+        let json5_content = json5_content.replace(
+            r#""456": { agent: "coder" }"#,
+            r#""456": { agent: "writer" }"#,
+        );
+        let root: OpenClawRoot = json5::from_str(&json5_content).unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!(channels["discord"]["default_agent"].as_str(), Some("Coder"));
+        assert!(report.warnings.iter().any(|w| w.contains(
+            "Discord routes guild 123 channel 456 -> writer to other agents; \
+             OpenFang sends all Discord messages to default_agent 'Coder'"
+        )));
+
+        // Routes to agents that are not migrated are dropped with a warning
+        let json5_content = json5_content.replace(r#"agent: "coder""#, r#"agent: "ghost""#);
+        let root: OpenClawRoot = json5::from_str(&json5_content).unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!(
+            channels["discord"]["default_agent"].as_str(),
+            Some("writer")
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w
+                == "Discord routes guild 123 -> ghost name agents that are not migrated; ignored"));
+
+        // Including ones filtered out of this run
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                exclude_agents: vec!["writer".to_string()],
+                ..Default::default()
+            },
+            &mut report,
+        )
+        .unwrap();
+        assert!(channels["discord"].get("default_agent").is_none());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("guild 123 channel 456 -> writer")));
+    }
+
+    #[test]
+    fn test_source_version() {
+        let target = TempDir::new().unwrap();
+        let report = migrate_config_str(
+            r#"{ version: "2.0", agents: { list: [{ id: "main" }] } }"#,
+            target.path(),
+            &MigrateOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.source_version.as_deref(), Some("2.0"));
+        assert!(report
+            .to_markdown()
+            .contains("# Migration Report: OpenClaw -> OpenFang\n\nsource version: 2.0\n"));
+        assert!(report.warnings.iter().any(|w| w
+            == "openclaw.json declares version 2.0, but this migrator knows versions up to 1; \
+                newer settings may have been missed"));
+
+        // `schemaVersion` is read too, and known versions do not warn
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("openclaw.json"), "{ schemaVersion: 1 }").unwrap();
+        let scan = scan_openclaw_workspace(source.path());
+        assert_eq!(scan.source_version.as_deref(), Some("1"));
+        let report = migrate_config_str(
+            "{ schemaVersion: 1 }",
+            target.path(),
+            &MigrateOptions::default(),
+        )
+        .unwrap();
+        assert!(report
+            .warnings
+            .iter()
+            .all(|w| !w.contains("declares version")));
+
+        assert!(version_is_newer("1.1", "1"));
+        assert!(!version_is_newer("v1.0.0", "1"));
+        assert!(!version_is_newer("next", "1"));
+    }
+
     #[test]
     fn test_idempotent_migration() {
         let source = TempDir::new().unwrap();
@@ -4164,6 +14340,7 @@ mod tests {
             source_dir: source.path().to_path_buf(),
             target_dir: target.path().to_path_buf(),
             dry_run: false,
+            ..Default::default()
         };
 
         // Run migration twice
@@ -4202,7 +14379,12 @@ mod tests {
         let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
         let mut report = MigrationReport::default();
 
-        let channels = migrate_channels_from_json(&root, target.path(), false, &mut report);
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        );
         assert!(channels.is_some());
         let ch_table = channels.unwrap();
         let table = ch_table.as_table().unwrap();
@@ -4212,6 +14394,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_matrix_rooms_with_policies() {
+        let target = TempDir::new().unwrap();
+        let json5_content = r#"{
+  channels: {
+    matrix: {
+      homeserver: "https://matrix.example.com",
+      accessToken: "syt_tok",
+      rooms: [
+        "!plain:example.com",
+        { id: "!ops:example.com", dmPolicy: "allowlist", groupPolicy: "mention" }
+      ]
+    }
+  }
+}"#;
+        let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+        let mut report = MigrationReport::default();
+
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        );
+        let ch_table = channels.unwrap();
+        let mx = ch_table["matrix"].as_table().unwrap();
+
+        let rooms: Vec<&str> = mx["allowed_rooms"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r.as_str().unwrap())
+            .collect();
+        assert_eq!(rooms, vec!["!plain:example.com", "!ops:example.com"]);
+
+        // Per-room policies have no setting, so they are reported instead
+        assert!(mx.get("room_overrides").is_none());
+        let skipped: Vec<&SkippedItem> = report
+            .skipped
+            .iter()
+            .filter(|s| s.name.starts_with("matrix room "))
+            .collect();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].name, "matrix room !ops:example.com");
+        assert!(skipped[0]
+            .reason
+            .contains("(dmPolicy allowlist; groupPolicy mention)"));
+    }
+
     #[test]
     fn test_signal_url_construction() {
         let target = TempDir::new().unwrap();
@@ -4227,7 +14458,12 @@ mod tests {
         let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
         let mut report = MigrationReport::default();
 
-        let channels = migrate_channels_from_json(&root, target.path(), false, &mut report);
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions::default(),
+            &mut report,
+        );
         assert!(channels.is_some());
         let ch_table = channels.unwrap();
         let table = ch_table.as_table().unwrap();
@@ -4238,4 +14474,234 @@ mod tests {
         );
         assert_eq!(sig["phone_number"].as_str().unwrap(), "+15551234567");
     }
+
+    #[test]
+    fn test_telegram_allowed_chats() {
+        let target = TempDir::new().unwrap();
+        let telegram = |json5_content: &str| {
+            let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+            let mut report = MigrationReport::default();
+            let channels = migrate_channels_from_json(
+                &root,
+                target.path(),
+                &MigrateOptions {
+                    dry_run: true,
+                    ..Default::default()
+                },
+                &mut report,
+            )
+            .unwrap();
+            (channels["telegram"].as_table().unwrap().clone(), report)
+        };
+
+        // ChannelOverrides has no chat allowlist, so it is reported rather than written
+        let (tg, report) = telegram(
+            r#"{ channels: { telegram: { botToken: "t", allowedChats: ["-1001234"], groupPolicy: "allowlist" } } }"#,
+        );
+        assert!(tg["overrides"].get("group_policy").is_some());
+        assert!(tg["overrides"].get("allowed_chats").is_none());
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("allowChats (-1001234) was not migrated")));
+
+        let (tg, report) = telegram(r#"{ channels: { telegram: { botToken: "t" } } }"#);
+        assert!(tg.get("overrides").is_none());
+        assert!(!report.warnings.iter().any(|w| w.contains("allowChats")));
+    }
+
+    #[test]
+    fn test_google_chat_webhook_and_bot_user() {
+        let root: OpenClawRoot = json5::from_str(
+            r#"{
+  channels: {
+    googlechat: { webhookPath: "/webhook/gchat", botUser: "bot@example.iam.gserviceaccount.com" },
+  },
+}"#,
+        )
+        .unwrap();
+        let target = TempDir::new().unwrap();
+        let mut report = MigrationReport::default();
+        let channels = migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+            &mut report,
+        )
+        .unwrap();
+        // GoogleChatConfig has neither field, so both are reported as skipped
+        let gc = channels["google_chat"].as_table().unwrap();
+        assert!(gc.get("webhook_path").is_none());
+        assert!(gc.get("bot_user").is_none());
+        assert_eq!(
+            gc["service_account_env"].as_str(),
+            Some("GOOGLE_CHAT_SA_FILE")
+        );
+        for (name, value) in [
+            ("google_chat/webhookPath", "/webhook/gchat"),
+            ("google_chat/botUser", "bot@example.iam.gserviceaccount.com"),
+        ] {
+            assert!(report
+                .skipped
+                .iter()
+                .any(|s| s.name == name && s.reason.contains(value)));
+        }
+        assert!(report.imported.iter().any(|i| i.name == "google_chat"));
+    }
+
+    #[test]
+    fn test_whatsapp_relative_auth_dir() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        let auth = source.path().join("wa-auth");
+        std::fs::create_dir_all(&auth).unwrap();
+        std::fs::write(auth.join("creds.json"), r#"{"me":"15551234567"}"#).unwrap();
+
+        let root: OpenClawRoot =
+            json5::from_str(r#"{ channels: { whatsapp: { authDir: "wa-auth" } } }"#).unwrap();
+        let mut report = MigrationReport::default();
+        migrate_channels_from_json(
+            &root,
+            target.path(),
+            &MigrateOptions {
+                source_dir: source.path().to_path_buf(),
+                target_dir: target.path().to_path_buf(),
+                ..Default::default()
+            },
+            &mut report,
+        )
+        .unwrap();
+
+        let copied = target.path().join("credentials/whatsapp/creds.json");
+        assert_eq!(
+            std::fs::read_to_string(copied).unwrap(),
+            r#"{"me":"15551234567"}"#
+        );
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "whatsapp/credentials (1 files)"));
+    }
+
+    #[test]
+    fn test_whatsapp_nested_credentials() {
+        let source = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        let auth = source.path().join("wa-auth");
+        std::fs::create_dir_all(auth.join("keys/pre-keys")).unwrap();
+        std::fs::write(auth.join("creds.json"), "{}").unwrap();
+        std::fs::write(auth.join("keys/app-state-sync-key-1.json"), "{}").unwrap();
+        std::fs::write(auth.join("keys/pre-keys/pre-key-1.json"), "{}").unwrap();
+        std::fs::write(auth.join("keys/pre-keys/pre-key-2.json"), "{}").unwrap();
+
+        let root: OpenClawRoot =
+            json5::from_str(r#"{ channels: { whatsapp: { authDir: "wa-auth" } } }"#).unwrap();
+        let run = |dry_run: bool| {
+            let mut report = MigrationReport::default();
+            migrate_channels_from_json(
+                &root,
+                target.path(),
+                &MigrateOptions {
+                    source_dir: source.path().to_path_buf(),
+                    dry_run,
+                    ..Default::default()
+                },
+                &mut report,
+            )
+            .unwrap();
+            report
+        };
+
+        // A dry run counts what would be copied
+        let report = run(true);
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "whatsapp/credentials (4 files)"));
+        assert!(!target.path().join("credentials").exists());
+
+        let report = run(false);
+        assert!(report
+            .imported
+            .iter()
+            .any(|i| i.name == "whatsapp/credentials (4 files)"));
+        let dest = target.path().join("credentials/whatsapp");
+        assert_eq!(count_files(&dest), 4);
+        assert!(dest.join("keys/pre-keys/pre-key-2.json").exists());
+
+        // A failed copy is cleaned up and leaves the earlier copy in place
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(
+                auth.join("missing.json"),
+                auth.join("keys/pre-keys/broken.json"),
+            )
+            .unwrap();
+            let report = run(false);
+            assert!(report
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("Failed to copy WhatsApp credentials")));
+            assert!(report
+                .imported
+                .iter()
+                .all(|i| !i.name.starts_with("whatsapp/credentials")));
+            assert!(!target.path().join("credentials/whatsapp.partial").exists());
+            assert_eq!(count_files(&dest), 4);
+        }
+    }
+
+    #[test]
+    fn test_irc_auth_mode() {
+        let target = TempDir::new().unwrap();
+        let irc_report = |json5_content: &str| {
+            let root: OpenClawRoot = json5::from_str(json5_content).unwrap();
+            let mut report = MigrationReport::default();
+            let channels = migrate_channels_from_json(
+                &root,
+                target.path(),
+                &MigrateOptions {
+                    dry_run: true,
+                    ..Default::default()
+                },
+                &mut report,
+            )
+            .unwrap();
+            // The kernel's IrcConfig has no auth mechanism field
+            assert!(channels["irc"].get("auth_mode").is_none());
+            report
+        };
+
+        let report = irc_report(
+            r#"{ channels: { irc: { host: "irc.libera.chat", nick: "bot", password: "pw", sasl: true } } }"#,
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("IRC sasl authentication is not supported")));
+
+        let report = irc_report(
+            r#"{ channels: { irc: { host: "h", nick: "bot", password: "pw", sasl: false } } }"#,
+        );
+        assert!(report
+            .warnings
+            .iter()
+            .any(|w| w.contains("IRC nickserv authentication is not supported")));
+
+        let report = irc_report(
+            r#"{ channels: { irc: { host: "h", nick: "bot", password: "pw", authMode: "NickServ" } } }"#,
+        );
+        assert!(report.warnings.iter().any(|w| w.contains("nickserv")));
+
+        let report =
+            irc_report(r#"{ channels: { irc: { host: "h", nick: "bot", password: "pw" } } }"#);
+        assert!(report.warnings.is_empty());
+
+        let report =
+            irc_report(r#"{ channels: { irc: { host: "h", nick: "bot", authMode: "certfp" } } }"#);
+        assert!(report.warnings.iter().any(|w| w.contains("'certfp'")));
+    }
 }
@@ -7,12 +7,24 @@ use std::fmt;
 pub struct MigrationReport {
     /// Source framework name.
     pub source: String,
+    /// The config's own `version`/`schemaVersion`, if it declares one.
+    pub source_version: Option<String>,
     /// Items that were successfully imported.
     pub imported: Vec<MigrateItem>,
+    /// Items whose existing output already matched and was left untouched.
+    pub unchanged: Vec<MigrateItem>,
     /// Items that were skipped (with reason).
     pub skipped: Vec<SkippedItem>,
     /// Warnings generated during migration.
     pub warnings: Vec<String>,
+    /// Informational notes that need no action (e.g. copy verification results).
+    pub notes: Vec<String>,
+    /// Existing target files a real run would overwrite (populated in dry-run).
+    pub conflicts: Vec<String>,
+    /// Every env var the generated config and agents reference (`*_env` keys), sorted.
+    pub required_env_vars: Vec<String>,
+    /// Total bytes copied from the source (workspaces, sessions, credentials).
+    pub bytes_copied: u64,
     /// Whether this was a dry run.
     pub dry_run: bool,
 }
@@ -26,6 +38,23 @@ pub struct MigrateItem {
     pub name: String,
     /// Destination path.
     pub destination: String,
+    /// Size of the imported content for memory items, or the memory scope for
+    /// agents (also filled in dry-run).
+    pub details: Option<ItemDetails>,
+}
+
+/// How much content an imported item carries, and how an agent's memory is scoped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemDetails {
+    /// Size in bytes.
+    pub bytes: u64,
+    /// Number of lines.
+    pub lines: usize,
+    /// Structured entries it splits into.
+    pub entries: usize,
+    /// Memory scope taken from the source config (`shared`, `isolated`,
+    /// `namespaces ...`), for agents that had one.
+    pub memory_scope: Option<String>,
 }
 
 /// An item that was skipped.
@@ -49,6 +78,7 @@ pub enum ItemKind {
     Skill,
     Channel,
     Secret,
+    McpServer,
 }
 
 impl fmt::Display for ItemKind {
@@ -61,10 +91,58 @@ impl fmt::Display for ItemKind {
             Self::Skill => write!(f, "Skill"),
             Self::Channel => write!(f, "Channel"),
             Self::Secret => write!(f, "Secret"),
+            Self::McpServer => write!(f, "MCP Server"),
         }
     }
 }
 
+/// Format a byte count as a human-readable size (e.g. `1.5 MB`).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Parse a human-readable size such as `500MB`, `2 GiB` or `1.5g` into bytes.
+///
+/// Units are case-insensitive and, like [`format_bytes`], 1024-based whether or
+/// not they carry the `i`; a bare number is bytes.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{text}': expected a number like 500MB"))?;
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        other => {
+            return Err(format!(
+                "invalid size '{text}': unknown unit '{other}' (use B, KB, MB, GB or TB)"
+            ))
+        }
+    };
+    let bytes = number * (1u64 << shift) as f64;
+    if bytes >= u64::MAX as f64 {
+        return Err(format!("invalid size '{text}': too large"));
+    }
+    Ok(bytes.round() as u64)
+}
+
 impl MigrationReport {
     /// Generate a human-readable Markdown summary.
     pub fn to_markdown(&self) -> String {
@@ -75,12 +153,22 @@ impl MigrationReport {
             "# Migration Report: {} -> OpenFang{}\n\n",
             self.source, mode
         ));
+        if let Some(ref version) = self.source_version {
+            out.push_str(&format!("source version: {version}\n\n"));
+        }
 
         // Summary
         out.push_str("## Summary\n\n");
         out.push_str(&format!("- Imported: {} items\n", self.imported.len()));
+        if !self.unchanged.is_empty() {
+            out.push_str(&format!("- Unchanged: {} items\n", self.unchanged.len()));
+        }
         out.push_str(&format!("- Skipped: {} items\n", self.skipped.len()));
-        out.push_str(&format!("- Warnings: {}\n\n", self.warnings.len()));
+        out.push_str(&format!("- Warnings: {}\n", self.warnings.len()));
+        out.push_str(&format!(
+            "- Data copied: {}\n\n",
+            format_bytes(self.bytes_copied)
+        ));
 
         // Imported
         if !self.imported.is_empty() {
@@ -88,8 +176,49 @@ impl MigrationReport {
             out.push_str("| Type | Name | Destination |\n");
             out.push_str("|------|------|-------------|\n");
             for item in &self.imported {
+                let scope = item
+                    .details
+                    .as_ref()
+                    .and_then(|d| d.memory_scope.as_deref())
+                    .map(|scope| format!(" (memory: {scope})"))
+                    .unwrap_or_default();
                 out.push_str(&format!(
-                    "| {} | {} | {} |\n",
+                    "| {} | {}{scope} | {} |\n",
+                    item.kind, item.name, item.destination
+                ));
+            }
+            out.push('\n');
+        }
+
+        // Memory sizes
+        let sized: Vec<(&MigrateItem, &ItemDetails)> = self
+            .imported
+            .iter()
+            .filter(|item| item.kind == ItemKind::Memory)
+            .filter_map(|item| Some((item, item.details.as_ref()?)))
+            .collect();
+        if !sized.is_empty() {
+            out.push_str("## Memory Size\n\n");
+            out.push_str("| Name | Size | Lines | Entries |\n");
+            out.push_str("|------|------|-------|---------|\n");
+            for (item, details) in sized {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    item.name,
+                    format_bytes(details.bytes),
+                    details.lines,
+                    details.entries
+                ));
+            }
+            out.push('\n');
+        }
+
+        // Unchanged
+        if !self.unchanged.is_empty() {
+            out.push_str("## Unchanged\n\n");
+            for item in &self.unchanged {
+                out.push_str(&format!(
+                    "- {} {} (`{}`)\n",
                     item.kind, item.name, item.destination
                 ));
             }
@@ -119,12 +248,49 @@ impl MigrationReport {
             out.push('\n');
         }
 
+        // Notes
+        if !self.notes.is_empty() {
+            out.push_str("## Notes\n\n");
+            for n in &self.notes {
+                out.push_str(&format!("- {n}\n"));
+            }
+            out.push('\n');
+        }
+
+        // Environment checklist
+        if !self.required_env_vars.is_empty() {
+            out.push_str("## Required Environment Variables\n\n");
+            for var in &self.required_env_vars {
+                if let Some(file) = self.secret_file(var) {
+                    out.push_str(&format!("- [x] `{var}` (written to {file})\n"));
+                } else {
+                    out.push_str(&format!("- [ ] `{var}`\n"));
+                }
+            }
+            out.push('\n');
+        }
+
+        // Conflicts
+        if !self.conflicts.is_empty() {
+            out.push_str("## Conflicts\n\n");
+            out.push_str("These existing files would be overwritten:\n\n");
+            for c in &self.conflicts {
+                out.push_str(&format!("- `{c}`\n"));
+            }
+            out.push('\n');
+        }
+
         // Next steps
         out.push_str("## Next Steps\n\n");
         out.push_str("1. Review imported agent manifests in `~/.openfang/agents/`\n");
-        out.push_str(
-            "2. Review `~/.openfang/secrets.env` — verify tokens were migrated correctly\n",
-        );
+        let secrets = self
+            .imported
+            .iter()
+            .find(|i| i.kind == ItemKind::Secret)
+            .map_or("secrets.env", |i| i.destination.as_str());
+        out.push_str(&format!(
+            "2. Review `~/.openfang/{secrets}` — verify tokens were migrated correctly\n"
+        ));
         out.push_str("3. Set any remaining API keys referenced in `~/.openfang/config.toml`\n");
         out.push_str("4. Start the daemon: `openfang start`\n");
         out.push_str("5. Test your agents: `openfang agent list`\n");
@@ -132,13 +298,112 @@ impl MigrationReport {
         out
     }
 
+    /// Record an env var referenced by the generated output, keeping the list sorted.
+    pub fn require_env_var(&mut self, name: &str) {
+        if let Err(pos) = self
+            .required_env_vars
+            .binary_search_by(|v| v.as_str().cmp(name))
+        {
+            self.required_env_vars.insert(pos, name.to_string());
+        }
+    }
+
+    /// The secrets file the migration wrote a value for `name` into, if any.
+    fn secret_file(&self, name: &str) -> Option<&str> {
+        self.imported
+            .iter()
+            .find(|i| i.kind == ItemKind::Secret && i.name == name)
+            .map(|i| i.destination.as_str())
+    }
+
+    /// Write a short plain-text summary: imported and skipped counts by kind, and
+    /// the number of warnings.
+    pub fn write_summary(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.write_summary_with(w, false)
+    }
+
+    /// Like [`Self::write_summary`], with ANSI colors for terminals.
+    pub fn write_summary_colored(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.write_summary_with(w, true)
+    }
+
+    fn write_summary_with(&self, w: &mut dyn std::io::Write, color: bool) -> std::io::Result<()> {
+        const KINDS: [ItemKind; 8] = [
+            ItemKind::Config,
+            ItemKind::Agent,
+            ItemKind::Memory,
+            ItemKind::Session,
+            ItemKind::Skill,
+            ItemKind::Channel,
+            ItemKind::Secret,
+            ItemKind::McpServer,
+        ];
+        let paint = |text: String, code: &str| {
+            if color {
+                format!("\x1b[{code}m{text}\x1b[0m")
+            } else {
+                text
+            }
+        };
+        let by_kind = |kinds: &mut dyn Iterator<Item = ItemKind>| {
+            let kinds: Vec<ItemKind> = kinds.collect();
+            let parts: Vec<String> = KINDS
+                .iter()
+                .filter_map(|kind| {
+                    let n = kinds.iter().filter(|k| *k == kind).count();
+                    (n > 0).then(|| format!("{kind} {n}"))
+                })
+                .collect();
+            if parts.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", parts.join(", "))
+            }
+        };
+
+        let mode = if self.dry_run { " (dry run)" } else { "" };
+        writeln!(w, "Migration from {}{mode}", self.source)?;
+        let imported = format!("Imported: {}", self.imported.len());
+        writeln!(
+            w,
+            "  {}{}",
+            paint(imported, "32"),
+            by_kind(&mut self.imported.iter().map(|i| i.kind))
+        )?;
+        let skipped = format!("Skipped:  {}", self.skipped.len());
+        writeln!(
+            w,
+            "  {}{}",
+            if self.skipped.is_empty() {
+                skipped
+            } else {
+                paint(skipped, "33")
+            },
+            by_kind(&mut self.skipped.iter().map(|i| i.kind))
+        )?;
+        let warnings = format!("Warnings: {}", self.warnings.len());
+        writeln!(
+            w,
+            "  {}",
+            if self.warnings.is_empty() {
+                warnings
+            } else {
+                paint(warnings, "31")
+            }
+        )
+    }
+
     /// Print the report to stdout in a friendly format.
     pub fn print_summary(&self) {
         let mode = if self.dry_run { " (dry run)" } else { "" };
         println!("\n  Migration complete!{mode}\n");
         println!("  Imported: {} items", self.imported.len());
+        if !self.unchanged.is_empty() {
+            println!("  Unchanged: {} items", self.unchanged.len());
+        }
         println!("  Skipped:  {} items", self.skipped.len());
         println!("  Warnings: {}", self.warnings.len());
+        println!("  Copied:   {}", format_bytes(self.bytes_copied));
 
         if !self.imported.is_empty() {
             println!("\n  Imported:");
@@ -161,6 +426,32 @@ impl MigrationReport {
             }
         }
 
+        if !self.notes.is_empty() {
+            println!("\n  Notes:");
+            for n in &self.notes {
+                println!("    - {n}");
+            }
+        }
+
+        let missing: Vec<&String> = self
+            .required_env_vars
+            .iter()
+            .filter(|v| self.secret_file(v).is_none())
+            .collect();
+        if !missing.is_empty() {
+            println!("\n  Set these env vars:");
+            for var in missing {
+                println!("    - {var}");
+            }
+        }
+
+        if !self.conflicts.is_empty() {
+            println!("\n  Would overwrite:");
+            for c in &self.conflicts {
+                println!("    ! {c}");
+            }
+        }
+
         if !self.dry_run {
             println!("\n  Next steps:");
             println!("    openfang start");
@@ -193,6 +484,7 @@ mod tests {
                 kind: ItemKind::Agent,
                 name: "coder".to_string(),
                 destination: "~/.openfang/agents/coder/agent.toml".to_string(),
+                details: None,
             }],
             skipped: vec![SkippedItem {
                 kind: ItemKind::Skill,
@@ -201,11 +493,134 @@ mod tests {
             }],
             warnings: vec!["API key not found".to_string()],
             dry_run: true,
+            ..Default::default()
         };
         let md = report.to_markdown();
         assert!(md.contains("(Dry Run)"));
         assert!(md.contains("coder"));
         assert!(md.contains("Unsupported format"));
         assert!(md.contains("API key not found"));
+        assert!(!md.contains("## Conflicts"));
+        assert!(!md.contains("## Required Environment Variables"));
+    }
+
+    #[test]
+    fn test_write_summary() {
+        let item = |kind, name: &str| MigrateItem {
+            kind,
+            name: name.to_string(),
+            destination: String::new(),
+            details: None,
+        };
+        let report = MigrationReport {
+            source: "OpenClaw".to_string(),
+            imported: vec![
+                item(ItemKind::Agent, "coder"),
+                item(ItemKind::Channel, "telegram"),
+                item(ItemKind::Agent, "writer"),
+                item(ItemKind::Channel, "discord"),
+                item(ItemKind::Channel, "slack"),
+            ],
+            skipped: vec![SkippedItem {
+                kind: ItemKind::Channel,
+                name: "imessage".to_string(),
+                reason: "macOS-only".to_string(),
+            }],
+            warnings: vec!["check me".to_string()],
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        report.write_summary(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(
+            text,
+            "Migration from OpenClaw (dry run)\n  \
+             Imported: 5 (Agent 2, Channel 3)\n  \
+             Skipped:  1 (Channel 1)\n  \
+             Warnings: 1\n"
+        );
+
+        let mut out = Vec::new();
+        report.write_summary_colored(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("\x1b[32mImported: 5\x1b[0m (Agent 2, Channel 3)"));
+    }
+
+    #[test]
+    fn test_required_env_checklist() {
+        let mut report = MigrationReport {
+            imported: vec![MigrateItem {
+                kind: ItemKind::Secret,
+                name: "TELEGRAM_BOT_TOKEN".to_string(),
+                destination: "secrets.env".to_string(),
+                details: None,
+            }],
+            ..Default::default()
+        };
+        for var in [
+            "TELEGRAM_BOT_TOKEN",
+            "ANTHROPIC_API_KEY",
+            "TELEGRAM_BOT_TOKEN",
+        ] {
+            report.require_env_var(var);
+        }
+        assert_eq!(
+            report.required_env_vars,
+            vec!["ANTHROPIC_API_KEY", "TELEGRAM_BOT_TOKEN"]
+        );
+        let md = report.to_markdown();
+        assert!(md.contains("- [ ] `ANTHROPIC_API_KEY`"));
+        assert!(md.contains("- [x] `TELEGRAM_BOT_TOKEN` (written to secrets.env)"));
+
+        // A renamed secrets file is named as written
+        report.imported[0].destination = "openfang.env".to_string();
+        assert!(report
+            .to_markdown()
+            .contains("- [x] `TELEGRAM_BOT_TOKEN` (written to openfang.env)"));
+    }
+
+    #[test]
+    fn test_report_with_conflicts() {
+        let report = MigrationReport {
+            source: "OpenClaw".to_string(),
+            conflicts: vec!["/home/u/.openfang/config.toml".to_string()],
+            dry_run: true,
+            ..Default::default()
+        };
+        let md = report.to_markdown();
+        assert!(md.contains("## Conflicts"));
+        assert!(md.contains("/home/u/.openfang/config.toml"));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+        let report = MigrationReport {
+            bytes_copied: 2048,
+            ..Default::default()
+        };
+        assert!(report.to_markdown().contains("- Data copied: 2.0 KB"));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("500MB"), Ok(500 * 1024 * 1024));
+        assert_eq!(parse_size("2GiB"), Ok(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size(" 1.5 kb "), Ok(1536));
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("10b"), Ok(10));
+        assert_eq!(parse_size("1T"), Ok(1 << 40));
+        for bad in ["", "MB", "-5MB", "1.2.3GB", "5 parsecs", "99999999999TB"] {
+            assert!(parse_size(bad).is_err(), "{bad:?} should not parse");
+        }
+        assert_eq!(
+            parse_size(&format_bytes(5 * 1024 * 1024)),
+            Ok(5 * 1024 * 1024)
+        );
     }
 }
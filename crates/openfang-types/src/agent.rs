@@ -297,6 +297,9 @@ pub enum ToolProfile {
     Research,
     Messaging,
     Automation,
+    Browser,
+    Devops,
+    Writing,
     #[default]
     Full,
     Custom,
@@ -328,6 +331,36 @@ impl ToolProfile {
                 "memory_store",
                 "memory_recall",
             ],
+            Self::Browser => vec![
+                "browser_navigate",
+                "browser_read_page",
+                "browser_click",
+                "browser_type",
+                "browser_screenshot",
+                "browser_close",
+                "web_fetch",
+                "web_search",
+            ],
+            Self::Devops => vec![
+                "file_read",
+                "file_write",
+                "file_list",
+                "shell_exec",
+                "docker_exec",
+                "process_start",
+                "process_list",
+                "process_poll",
+                "process_kill",
+                "web_fetch",
+            ],
+            Self::Writing => vec![
+                "file_read",
+                "file_write",
+                "file_list",
+                "web_search",
+                "memory_store",
+                "memory_recall",
+            ],
             Self::Full | Self::Custom => vec!["*"],
         }
         .into_iter()
@@ -338,7 +371,9 @@ impl ToolProfile {
     /// Derive ManifestCapabilities implied by this profile.
     pub fn implied_capabilities(&self) -> ManifestCapabilities {
         let tools = self.tools();
-        let has_net = tools.iter().any(|t| t.starts_with("web_") || t == "*");
+        let has_net = tools
+            .iter()
+            .any(|t| t.starts_with("web_") || t.starts_with("browser_") || t == "*");
         let has_shell = tools.iter().any(|t| t == "shell_exec" || t == "*");
         let has_agent = tools.iter().any(|t| t.starts_with("agent_") || t == "*");
         let has_memory = tools.iter().any(|t| t.starts_with("memory_") || t == "*");
@@ -809,6 +844,34 @@ mod tests {
         assert_eq!(tools.len(), 10);
     }
 
+    #[test]
+    fn test_tool_profile_browser() {
+        let tools = ToolProfile::Browser.tools();
+        assert!(tools.contains(&"browser_navigate".to_string()));
+        assert!(!tools.contains(&"shell_exec".to_string()));
+        let caps = ToolProfile::Browser.implied_capabilities();
+        assert!(caps.network.contains(&"*".to_string()));
+        assert!(caps.shell.is_empty());
+    }
+
+    #[test]
+    fn test_tool_profile_devops() {
+        let tools = ToolProfile::Devops.tools();
+        assert!(tools.contains(&"shell_exec".to_string()));
+        assert!(tools.contains(&"docker_exec".to_string()));
+        assert_eq!(tools.len(), 10);
+    }
+
+    #[test]
+    fn test_tool_profile_writing() {
+        let tools = ToolProfile::Writing.tools();
+        assert!(tools.contains(&"file_write".to_string()));
+        assert!(!tools.contains(&"shell_exec".to_string()));
+        let caps = ToolProfile::Writing.implied_capabilities();
+        assert!(caps.shell.is_empty());
+        assert!(!caps.agent_spawn);
+    }
+
     #[test]
     fn test_tool_profile_full() {
         let tools = ToolProfile::Full.tools();
@@ -26,6 +26,8 @@ pub enum GroupPolicy {
     /// Only respond when mentioned (@bot).
     #[default]
     MentionOnly,
+    /// Only respond to replies to the bot's own messages.
+    ReplyOnly,
     /// Only respond to slash commands.
     CommandsOnly,
     /// Ignore all group messages.
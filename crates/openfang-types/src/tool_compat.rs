@@ -3,60 +3,91 @@
 //! These mappings are used by both the migration engine and the skill system
 //! to normalize OpenClaw tool names into OpenFang equivalents.
 
+/// OpenClaw tool names and their OpenFang equivalents, as `(openclaw, openfang)`.
+pub const OPENCLAW_TOOL_ALIASES: &[(&str, &str)] = &[
+    // Claude-style tool names (capitalized)
+    ("Read", "file_read"),
+    ("read", "file_read"),
+    ("read_file", "file_read"),
+    ("Write", "file_write"),
+    ("write", "file_write"),
+    ("write_file", "file_write"),
+    ("Edit", "file_write"),
+    ("edit", "file_write"),
+    ("Glob", "file_list"),
+    ("glob", "file_list"),
+    ("list_files", "file_list"),
+    ("Grep", "file_list"),
+    ("grep", "file_list"),
+    ("Bash", "shell_exec"),
+    ("bash", "shell_exec"),
+    ("exec", "shell_exec"),
+    ("execute_command", "shell_exec"),
+    ("WebSearch", "web_search"),
+    ("web_search", "web_search"),
+    ("WebFetch", "web_fetch"),
+    ("fetch_url", "web_fetch"),
+    ("web_fetch", "web_fetch"),
+    ("browser_navigate", "browser_navigate"),
+    ("memory_search", "memory_recall"),
+    ("memory_recall", "memory_recall"),
+    ("memory_save", "memory_store"),
+    ("memory_store", "memory_store"),
+    ("sessions_send", "agent_send"),
+    ("agent_message", "agent_send"),
+    ("sessions_list", "agent_list"),
+    ("agents_list", "agent_list"),
+    ("agent_list", "agent_list"),
+    ("sessions_spawn", "agent_send"),
+];
+
+/// All known OpenFang built-in tool names.
+pub const KNOWN_OPENFANG_TOOLS: &[&str] = &[
+    "file_read",
+    "file_write",
+    "file_list",
+    "shell_exec",
+    "web_search",
+    "web_fetch",
+    "browser_navigate",
+    "browser_click",
+    "browser_type",
+    "browser_screenshot",
+    "browser_read_page",
+    "browser_close",
+    "memory_recall",
+    "memory_store",
+    "agent_send",
+    "agent_list",
+    "agent_spawn",
+    "agent_kill",
+    "agent_find",
+    "task_post",
+    "task_claim",
+    "task_complete",
+    "task_list",
+    "event_publish",
+    "schedule_create",
+    "schedule_list",
+    "schedule_delete",
+    "image_analyze",
+    "location_get",
+];
+
 /// Map an OpenClaw tool name to its OpenFang equivalent.
 ///
 /// Returns `None` if the name has no known mapping (may already be
 /// an OpenFang tool name — check with [`is_known_openfang_tool`]).
 pub fn map_tool_name(openclaw_name: &str) -> Option<&'static str> {
-    match openclaw_name {
-        // Claude-style tool names (capitalized)
-        "Read" | "read" | "read_file" => Some("file_read"),
-        "Write" | "write" | "write_file" => Some("file_write"),
-        "Edit" | "edit" => Some("file_write"),
-        "Glob" | "glob" | "list_files" => Some("file_list"),
-        "Grep" | "grep" => Some("file_list"),
-        "Bash" | "bash" | "exec" | "execute_command" => Some("shell_exec"),
-        "WebSearch" | "web_search" => Some("web_search"),
-        "WebFetch" | "fetch_url" | "web_fetch" => Some("web_fetch"),
-        "browser_navigate" => Some("browser_navigate"),
-        "memory_search" | "memory_recall" => Some("memory_recall"),
-        "memory_save" | "memory_store" => Some("memory_store"),
-        "sessions_send" | "agent_message" => Some("agent_send"),
-        "sessions_list" | "agents_list" | "agent_list" => Some("agent_list"),
-        "sessions_spawn" => Some("agent_send"),
-        _ => None,
-    }
+    OPENCLAW_TOOL_ALIASES
+        .iter()
+        .find(|(oc, _)| *oc == openclaw_name)
+        .map(|(_, of)| *of)
 }
 
 /// Check if a tool name is a known OpenFang built-in tool.
 pub fn is_known_openfang_tool(name: &str) -> bool {
-    matches!(
-        name,
-        "file_read"
-            | "file_write"
-            | "file_list"
-            | "shell_exec"
-            | "web_search"
-            | "web_fetch"
-            | "browser_navigate"
-            | "memory_recall"
-            | "memory_store"
-            | "agent_send"
-            | "agent_list"
-            | "agent_spawn"
-            | "agent_kill"
-            | "agent_find"
-            | "task_post"
-            | "task_claim"
-            | "task_complete"
-            | "task_list"
-            | "event_publish"
-            | "schedule_create"
-            | "schedule_list"
-            | "schedule_delete"
-            | "image_analyze"
-            | "location_get"
-    )
+    KNOWN_OPENFANG_TOOLS.contains(&name)
 }
 
 #[cfg(test)]
@@ -147,4 +178,11 @@ mod tests {
         assert!(!is_known_openfang_tool("Read"));
         assert!(!is_known_openfang_tool("Bash"));
     }
+
+    #[test]
+    fn test_alias_targets_are_known() {
+        for (oc, of) in OPENCLAW_TOOL_ALIASES {
+            assert!(is_known_openfang_tool(of), "{oc} maps to unknown tool {of}");
+        }
+    }
 }